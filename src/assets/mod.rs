@@ -156,6 +156,9 @@ impl AssetDownloader {
 // Download tile assets (needed for platforms and ground)
         self.download_tile_assets().await?;
 
+        // Download object assets (powerups, their bullets and checkpoint bells)
+        self.download_object_assets().await?;
+
         log::info!("Finished downloading essential SuperTux sprites");
         Ok(())
     }
@@ -299,6 +302,25 @@ impl AssetDownloader {
             }
         }
 
+        // Bullets, saved under the names the projectiles use
+        let bullets = vec![
+            ("fire_bullet-0.png", "fireball.png"),
+            ("ice_bullet.png", "iceball.png")
+        ];
+
+        for (bullet, local_name) in bullets {
+            let remote_path = format!("objects/bullets/{}", bullet);
+            let local_path = format!("sprites/objects/bullets/{}", local_name);
+            if let Err(e) = self.download_file(&remote_path, &local_path).await {
+                log::warn!("Failed to download bullet {}: {}", bullet, e);
+            }
+        }
+
+        // Checkpoint bell
+        if let Err(e) = self.download_file("objects/bell/bell-m.png", "sprites/objects/bell/bell-m.png").await {
+            log::warn!("Failed to download checkpoint bell: {}", e);
+        }
+
         log::info!("Finished downloading object assets");
         Ok(())
     }
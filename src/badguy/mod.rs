@@ -3,11 +3,39 @@
 use crate::object::{GameObjectManager, Component, Transform, SpriteComponent, Health, ObjectId};
 use crate::physics::{PhysicsWorld, BodyType};
 use crate::collision::CollisionLayer;
+use crate::projectile::ProjectileKind;
 use crate::sprite::Sprite;
 use crate::math::Vector2;
 use crate::util::Result;
 use std::any::Any;
 
+/// What happened when the player touched a badguy
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PlayerContact {
+    /// Nothing, e.g. the badguy is gone
+    None,
+    /// The player landed on the badguy and stunned it
+    Stomped,
+    /// The badguy hurt the player
+    Hurt { damage: i32 },
+}
+
+/// What happened when a projectile hit a badguy
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ProjectileHit {
+    /// Nothing, e.g. the badguy is gone
+    None,
+    /// The badguy shrugged it off
+    Immune,
+    /// The badguy was defeated, worth `points`
+    Killed { points: i32 },
+    /// The badguy was frozen in place
+    Frozen,
+}
+
+/// Seconds a frozen badguy stays frozen
+const FREEZE_TIME: f32 = 4.0;
+
 /// Badguy AI state
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum BadguyState {
@@ -16,6 +44,8 @@ pub enum BadguyState {
     Chasing,
     Attacking,
     Stunned,
+    /// Frozen by an iceball: harmless and still until it thaws
+    Frozen,
     Dead,
 }
 
@@ -80,6 +110,10 @@ pub struct Badguy {
     pub points: i32, // Points awarded when defeated
     pub can_be_stomped: bool,
     pub can_be_kicked: bool,
+    /// Whether fireballs defeat it
+    pub can_be_burned: bool,
+    /// Whether iceballs freeze it
+    pub can_be_frozen: bool,
 }
 
 impl Badguy {
@@ -90,6 +124,12 @@ impl Badguy {
             BadguyType::Jumpy => (1, 150, true, false),
             BadguyType::Flying => (1, 250, false, false),
         };
+        // Jumpy is made of metal, and flying badguys are too quick to freeze
+        let (can_be_burned, can_be_frozen) = match badguy_type {
+            BadguyType::Goomba | BadguyType::Spiky => (true, true),
+            BadguyType::Jumpy => (false, true),
+            BadguyType::Flying => (true, false),
+        };
 
         Self {
             badguy_type,
@@ -97,6 +137,16 @@ impl Badguy {
             points,
             can_be_stomped,
             can_be_kicked,
+            can_be_burned,
+            can_be_frozen,
+        }
+    }
+
+    /// Check if a projectile can harm the badguy
+    pub fn is_immune_to(&self, kind: ProjectileKind) -> bool {
+        match kind {
+            ProjectileKind::Fireball => !self.can_be_burned,
+            ProjectileKind::Iceball => !self.can_be_frozen,
         }
     }
 }
@@ -265,6 +315,12 @@ impl BadguySystem {
                     ai_component.state_timer = 0.0;
                 }
             }
+            BadguyState::Frozen => {
+                if ai_component.state_timer > FREEZE_TIME {
+                    ai_component.state = BadguyState::Walking;
+                    ai_component.state_timer = 0.0;
+                }
+            }
             BadguyState::Dead => {
                 // Dead badguys don't move
                 return Ok(());
@@ -288,7 +344,7 @@ impl BadguySystem {
                     );
                 }
             }
-            BadguyState::Stunned | BadguyState::Dead => {
+            BadguyState::Stunned | BadguyState::Frozen | BadguyState::Dead => {
                 // Stop movement
                 if let Some(body) = physics_world.get_body(body_id) {
                     let current_velocity = body.velocity;
@@ -305,19 +361,27 @@ impl BadguySystem {
     }
 
     /// Handle collision between badguy and player
+    ///
+    /// Stomps are applied to the badguy here; hurting the player is left to
+    /// the caller, which knows about power-ups and invulnerability.
     pub fn handle_player_collision(
         &self,
         badguy_id: ObjectId,
         player_id: ObjectId,
         object_manager: &mut GameObjectManager,
-    ) -> Result<()> {
+    ) -> Result<PlayerContact> {
         // Get badguy data first
         let (can_be_stomped, damage, badguy_pos) = {
             let badguy_obj = match object_manager.get_object(badguy_id) {
                 Some(obj) => obj,
-                None => return Ok(()),
+                None => return Ok(PlayerContact::None),
             };
 
+            // Frozen badguys are harmless
+            if badguy_obj.get_component::<BadguyAI>().is_some_and(|ai| ai.state == BadguyState::Frozen) {
+                return Ok(PlayerContact::None);
+            }
+
             let can_be_stomped = badguy_obj.get_component::<Badguy>()
                 .map(|b| b.can_be_stomped)
                 .unwrap_or(false);
@@ -330,12 +394,15 @@ impl BadguySystem {
             (can_be_stomped, damage, badguy_pos)
         };
         
-        // Get player position
-        let player_pos = object_manager.get_object(player_id)
-            .map(|obj| obj.position())
-            .unwrap_or(Vector2::ZERO);
+        // Get the position of the player's feet
+        let player_feet = object_manager.get_object(player_id)
+            .map(|obj| {
+                let height = obj.get_component::<SpriteComponent>().map_or(32.0, |s| s.sprite.size.y);
+                obj.position().y + height
+            })
+            .unwrap_or(0.0);
 
-        let is_stomping = player_pos.y < badguy_pos.y - 16.0; // Player is above badguy
+        let is_stomping = player_feet < badguy_pos.y + 16.0; // Player is above badguy
 
         if is_stomping && can_be_stomped {
             // Stomp the badguy
@@ -348,17 +415,52 @@ impl BadguySystem {
             
             // Award points to player (this would be handled by a score system)
             log::info!("Badguy stomped!");
+            Ok(PlayerContact::Stomped)
         } else {
-            // Damage the player
-            if let Some(player_obj) = object_manager.get_object_mut(player_id) {
-                if let Some(health) = player_obj.get_component_mut::<Health>() {
-                    health.take_damage(damage);
-                    log::info!("Player took {} damage from badguy", damage);
+            Ok(PlayerContact::Hurt { damage })
+        }
+    }
+
+    /// Handle a player projectile hitting a badguy
+    ///
+    /// A killed badguy is marked dead and deactivated; removing its body is
+    /// left to the caller, as is removing the projectile.
+    pub fn handle_projectile_hit(
+        &self,
+        badguy_id: ObjectId,
+        kind: ProjectileKind,
+        object_manager: &mut GameObjectManager,
+    ) -> Result<ProjectileHit> {
+        let badguy_obj = match object_manager.get_object_mut(badguy_id) {
+            Some(obj) if obj.active => obj,
+            _ => return Ok(ProjectileHit::None),
+        };
+        let (immune, points) = match badguy_obj.get_component::<Badguy>() {
+            Some(badguy) => (badguy.is_immune_to(kind), badguy.points),
+            None => return Ok(ProjectileHit::None),
+        };
+
+        if immune {
+            return Ok(ProjectileHit::Immune);
+        }
+
+        match kind {
+            ProjectileKind::Fireball => {
+                if let Some(ai) = badguy_obj.get_component_mut::<BadguyAI>() {
+                    ai.state = BadguyState::Dead;
                 }
+                badguy_obj.active = false;
+                log::info!("Badguy burned!");
+                Ok(ProjectileHit::Killed { points })
+            }
+            ProjectileKind::Iceball => {
+                if let Some(ai) = badguy_obj.get_component_mut::<BadguyAI>() {
+                    ai.state = BadguyState::Frozen;
+                    ai.state_timer = 0.0;
+                }
+                Ok(ProjectileHit::Frozen)
             }
         }
-
-        Ok(())
     }
 }
 
@@ -3,7 +3,8 @@
 //! This utility downloads SuperTux sprites from the official repository
 //! and organizes them in the local assets directory.
 
-use rustux::assets::{AssetDownloader, SpriteDefinition};
+use rustux::assets::AssetDownloader;
+use rustux::supertux::TuxForm;
 use rustux::util::Result;
 use std::path::Path;
 
@@ -57,16 +58,17 @@ async fn main() -> Result<()> {
     // Create sprite definitions
     println!("Creating sprite definitions...");
     
-    let tux_small_def = SpriteDefinition::tux_small();
-    let def_path = assets_path.join("sprites/definitions/tux_small.json");
-    
     // Create definitions directory
-    if let Some(parent) = def_path.parent() {
-        std::fs::create_dir_all(parent)?;
+    let definitions_path = assets_path.join("sprites/definitions");
+    std::fs::create_dir_all(&definitions_path)?;
+
+    // One definition per Tux form (tux_small, tux_big, tux_fire, ...)
+    for form in TuxForm::ALL {
+        let definition = form.sprite_definition();
+        let def_path = definitions_path.join(format!("{}.json", definition.name));
+        definition.save_to_file(&def_path)?;
+        println!("✓ Created sprite definition: {}", def_path.display());
     }
-    
-    tux_small_def.save_to_file(&def_path)?;
-    println!("✓ Created sprite definition: {}", def_path.display());
 
     println!();
     println!("Asset download completed successfully!");
@@ -1,9 +1,13 @@
 //! Collision detection system for RustUX
 
 use crate::math::{Rect, Vector2, Direction};
+use crate::tilemap::TileGrid;
 use crate::util::Result;
 use std::collections::HashMap;
 
+/// Object id reported for collisions with level tiles
+pub const TILE_OBJECT_ID: u32 = 0;
+
 /// Collision layer for organizing collision objects
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum CollisionLayer {
@@ -245,7 +249,8 @@ impl CollisionSystem {
         self.set_layer_interaction(Player, Enemy, true);
         self.set_layer_interaction(Player, Item, true);
         self.set_layer_interaction(Player, Trigger, true);
-        self.set_layer_interaction(Player, Projectile, true);
+        // The player's own projectiles pass through them
+        self.set_layer_interaction(Player, Projectile, false);
         
         // Enemy interactions
         self.set_layer_interaction(Enemy, Item, false);
@@ -292,6 +297,13 @@ impl CollisionSystem {
         self.spatial_grid.remove_object(id).is_some()
     }
 
+    /// Change an object's collision type
+    pub fn set_object_collision_type(&mut self, id: u32, collision_type: CollisionType) {
+        if let Some(object) = self.spatial_grid.objects.get_mut(&id) {
+            object.collision_type = collision_type;
+        }
+    }
+
     /// Update an object's position
     pub fn update_object(&mut self, id: u32, new_rect: Rect) {
         self.spatial_grid.update_object(id, new_rect);
@@ -312,25 +324,73 @@ impl CollisionSystem {
                 continue;
             }
 
-            if let Some(intersection) = object.intersection(rect) {
-                let direction = self.get_collision_direction(rect, &object.rect);
-                let penetration = match direction {
-                    Direction::Left | Direction::Right => intersection.width,
-                    Direction::Up | Direction::Down => intersection.height,
-                };
-
-                results.push(CollisionResult {
-                    object: object.clone(),
-                    direction,
-                    penetration,
-                    contact_point: intersection.center(),
-                });
+            if let Some(result) = self.collision_result(rect, object) {
+                results.push(result);
+            }
+        }
+
+        results
+    }
+
+    /// Get collision results for a moving rectangle against tiles and objects
+    ///
+    /// Tiles are read straight from the grid instead of being registered as
+    /// collision objects, so large maps don't flood the spatial grid. Tile
+    /// hits are reported as `World` objects with id `TILE_OBJECT_ID`.
+    pub fn check_collisions_with_tiles(
+        &self,
+        rect: &Rect,
+        layer: CollisionLayer,
+        tile_grid: Option<&TileGrid>,
+    ) -> Vec<CollisionResult> {
+        let mut results = Vec::new();
+
+        if let Some(tile_grid) = tile_grid {
+            if self.layers_interact(layer, CollisionLayer::World) {
+                for (x, y, attributes) in tile_grid.collidable_cells_in_rect(rect) {
+                    let collision_type = if attributes.solid {
+                        CollisionType::Solid
+                    } else if attributes.one_way {
+                        CollisionType::Platform
+                    } else {
+                        // Slopes are resolved by the physics world
+                        continue;
+                    };
+
+                    let object = CollisionObject::new(
+                        TILE_OBJECT_ID,
+                        tile_grid.cell_rect(x, y),
+                        CollisionLayer::World,
+                        collision_type,
+                    );
+                    if let Some(result) = self.collision_result(rect, &object) {
+                        results.push(result);
+                    }
+                }
             }
         }
 
+        results.extend(self.check_collisions(rect, layer));
         results
     }
 
+    /// Build the collision result between a rectangle and an object
+    fn collision_result(&self, rect: &Rect, object: &CollisionObject) -> Option<CollisionResult> {
+        let intersection = object.intersection(rect)?;
+        let direction = self.get_collision_direction(rect, &object.rect);
+        let penetration = match direction {
+            Direction::Left | Direction::Right => intersection.width,
+            Direction::Up | Direction::Down => intersection.height,
+        };
+
+        Some(CollisionResult {
+            object: object.clone(),
+            direction,
+            penetration,
+            contact_point: intersection.center(),
+        })
+    }
+
     /// Resolve collision by moving the rectangle out of collision
     pub fn resolve_collision(&self, rect: &Rect, collision: &CollisionResult) -> Rect {
         let mut resolved_rect = *rect;
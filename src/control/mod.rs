@@ -13,7 +13,9 @@ pub enum GameAction {
     MoveRight,
     Jump,
     Duck,
+    Up,
     Run,
+    Action,
     
     // Game controls
     Pause,
@@ -24,6 +26,9 @@ pub enum GameAction {
     // Debug actions
     ToggleDebug,
     Screenshot,
+
+    // Display
+    ToggleFullscreen,
     
     // Menu navigation
     MenuUp,
@@ -206,10 +211,18 @@ impl Default for InputConfig {
             InputBinding::key(sdl2::keyboard::Keycode::Down),
             InputBinding::key(sdl2::keyboard::Keycode::S),
         ]);
+        bindings.insert(GameAction::Up, vec![
+            InputBinding::key(sdl2::keyboard::Keycode::Up),
+            InputBinding::key(sdl2::keyboard::Keycode::W),
+        ]);
         bindings.insert(GameAction::Run, vec![
             InputBinding::key(sdl2::keyboard::Keycode::LShift),
             InputBinding::key(sdl2::keyboard::Keycode::RShift),
         ]);
+        bindings.insert(GameAction::Action, vec![
+            InputBinding::key(sdl2::keyboard::Keycode::LCtrl),
+            InputBinding::key(sdl2::keyboard::Keycode::RCtrl),
+        ]);
         // Game controls
         bindings.insert(GameAction::Pause, vec![
             InputBinding::key(sdl2::keyboard::Keycode::P),
@@ -233,6 +246,11 @@ impl Default for InputConfig {
         bindings.insert(GameAction::Screenshot, vec![
             InputBinding::key(sdl2::keyboard::Keycode::F12),
         ]);
+
+        // Display
+        bindings.insert(GameAction::ToggleFullscreen, vec![
+            InputBinding::key(sdl2::keyboard::Keycode::F11),
+        ]);
         
         // Menu navigation
         bindings.insert(GameAction::MenuUp, vec![
@@ -566,6 +584,41 @@ impl Default for InputManager {
     }
 }
 
+#[cfg(test)]
+impl InputManager {
+    /// Press an action, as if the player pushed one of its buttons
+    ///
+    /// Key bindings go through SDL's key names, mouse buttons don't, so the
+    /// action gets a mouse button of its own the first time it is pressed.
+    pub(crate) fn press_for_test(&mut self, action: GameAction) {
+        let mouse_btn = self.test_mouse_button(action);
+        self.process_event(&sdl2::event::Event::MouseButtonDown { timestamp: 0, window_id: 0, which: 0, mouse_btn, clicks: 1, x: 0, y: 0 });
+    }
+
+    /// Release an action pressed with `press_for_test`
+    pub(crate) fn release_for_test(&mut self, action: GameAction) {
+        let mouse_btn = self.test_mouse_button(action);
+        self.process_event(&sdl2::event::Event::MouseButtonUp { timestamp: 0, window_id: 0, which: 0, mouse_btn, clicks: 1, x: 0, y: 0 });
+    }
+
+    /// Mouse button bound to an action, binding a free one if there is none
+    fn test_mouse_button(&mut self, action: GameAction) -> sdl2::mouse::MouseButton {
+        use sdl2::mouse::MouseButton;
+        let buttons = [MouseButton::Left, MouseButton::Middle, MouseButton::Right, MouseButton::X1, MouseButton::X2];
+        let bound = |bindings: &[InputBinding], button: MouseButton| bindings.contains(&InputBinding::mouse_button(button));
+
+        if let Some(&button) = buttons.iter().find(|&&button| self.config.bindings.get(&action).is_some_and(|b| bound(b, button))) {
+            return button;
+        }
+        let button = *buttons
+            .iter()
+            .find(|&&button| !self.config.bindings.values().any(|b| bound(b, button)))
+            .expect("every test mouse button is taken");
+        self.add_binding(action, InputBinding::mouse_button(button));
+        button
+    }
+}
+
 /// Input utilities
 pub mod utils {
     use super::*;
@@ -587,13 +640,16 @@ pub mod utils {
             GameAction::MoveRight => "Move Right",
             GameAction::Jump => "Jump",
             GameAction::Duck => "Duck",
+            GameAction::Up => "Up",
             GameAction::Run => "Run",
+            GameAction::Action => "Action",
             GameAction::Pause => "Pause",
             GameAction::Menu => "Menu",
             GameAction::Confirm => "Confirm",
             GameAction::Cancel => "Cancel",
             GameAction::ToggleDebug => "Toggle Debug",
             GameAction::Screenshot => "Screenshot",
+            GameAction::ToggleFullscreen => "Toggle Fullscreen",
             GameAction::MenuUp => "Menu Up",
             GameAction::MenuDown => "Menu Down",
             GameAction::MenuLeft => "Menu Left",
@@ -2,24 +2,39 @@
 
 use crate::util::Result;
 use crate::assets::AssetDownloader;
-use sdl2::render::Canvas;
-use sdl2::video::Window;
+use crate::audio::AudioManager;
+use crate::math::{Rect, Vector2};
+use crate::projectile::ProjectileKind;
+use crate::sprite::{AtlasBuilder, TextureAtlas};
+use crate::supertux::checkpoint::{CHECKPOINT_TEXTURE, CHECKPOINT_TEXTURE_PATH};
+use crate::supertux::{PowerUpKind, TuxForm};
+use crate::video::{BlendMode, Color, RenderStats, VideoManager};
 use sdl2::keyboard::Keycode;
 use std::collections::HashMap;
 use std::path::Path;
 
+/// Directory the packed sprite atlas is cached in between runs
+const ATLAS_CACHE_DIR: &str = "assets/atlas-cache";
+
 /// Trait for game states
 pub trait GameState {
     /// Update the game state
     fn update(&mut self, delta_time: f32) -> Result<()>;
-/// Update the game state with input manager
-    fn update_with_input(&mut self, delta_time: f32, input_manager: &crate::control::InputManager) -> Result<()> {
+/// Update the game state with input manager, returning the state to switch to, if any
+    fn update_with_input(&mut self, delta_time: f32, input_manager: &crate::control::InputManager) -> Result<Option<StateId>> {
         let _ = input_manager; // Default implementation ignores input manager
-        self.update(delta_time)
+        self.update(delta_time)?;
+        Ok(None)
+    }
+
+    /// Play the sounds and music requested during the last update
+    fn update_audio(&mut self, audio: &mut AudioManager) -> Result<()> {
+        let _ = audio;
+        Ok(())
     }
 
     /// Render the game state
-    fn render(&self, canvas: &mut Canvas<Window>) -> Result<()>;
+    fn render(&self, video: &mut VideoManager) -> Result<()>;
 
     /// Handle key down events
     fn handle_key_down(&mut self, keycode: Keycode) -> Result<Option<StateId>> {
@@ -43,6 +58,22 @@ pub trait GameState {
         Ok(())
     }
 
+    /// Acquire the textures this state draws with, after `on_enter`
+    fn load_resources(&mut self, video: &mut VideoManager) -> Result<()> {
+        let _ = video;
+        Ok(())
+    }
+
+    /// Release the textures acquired in `load_resources`, after `on_exit`
+    fn unload_resources(&mut self, video: &mut VideoManager) {
+        let _ = video;
+    }
+
+    /// Called when the logical screen size changes
+    fn on_resize(&mut self, output_size: Vector2) {
+        let _ = output_size;
+    }
+
     /// Get the state's name
     fn name(&self) -> &str;
 }
@@ -120,14 +151,15 @@ impl GameStateManager {
         }
     }
 
-    /// Process state transitions
-    pub fn process_transitions(&mut self) -> Result<()> {
+    /// Process state transitions, moving textures from the old state to the new one
+    pub fn process_transitions(&mut self, video: &mut VideoManager) -> Result<()> {
         if let Some(next_state) = self.next_state.take() {
             log::info!("Processing state transition to {:?}", next_state);
             // Exit current state
             if let Some(current_id) = &self.current_state {
                 log::info!("Exiting current state: {:?}", current_id);if let Some(current_state) = self.states.get_mut(current_id) {
                     current_state.on_exit()?;
+                    current_state.unload_resources(video);
                 }
             }
 
@@ -135,6 +167,7 @@ impl GameStateManager {
             log::info!("Entering new state: {:?}", next_state);
             if let Some(new_state) = self.states.get_mut(&next_state) {
                 new_state.on_enter()?;
+                new_state.load_resources(video)?;
             }
 
             self.current_state = Some(next_state);
@@ -206,20 +239,19 @@ impl GameState for MenuState {
         Ok(())
     }
 
-    fn render(&self, canvas: &mut Canvas<Window>) -> Result<()> {
+    fn render(&self, video: &mut VideoManager) -> Result<()> {
         log::debug!("MenuState render called, downloading: {}, complete: {}", self.downloading, self.download_complete);
         // Simple menu rendering - just clear to a different color for now
         if self.downloading {
             // Show downloading status with orange background
-            canvas.set_draw_color(sdl2::pixels::Color::RGB(255, 165, 0));
+            video.clear(Color::rgb(255, 165, 0));
         } else if self.download_complete {
             // Show ready status with green background
-            canvas.set_draw_color(sdl2::pixels::Color::RGB(0, 128, 0));
+            video.clear(Color::rgb(0, 128, 0));
         } else {
             // Default menu color
-            canvas.set_draw_color(sdl2::pixels::Color::RGB(50, 50, 100));
+            video.clear(Color::rgb(50, 50, 100));
         }
-        canvas.clear();
         // TODO: Render actual menu text and options showing download status
         Ok(())
     }
@@ -268,16 +300,41 @@ impl GameState for MenuState {
 /// A simple playing state for testing
 pub struct PlayingState {
     game_world: Option<crate::supertux::GameWorld>,
-    
+    level_name: String,
+    level: Option<crate::level::LevelData>,
+    /// Textures acquired for the current level
+    loaded_textures: Vec<String>,
+    /// Atlas Tux's forms are drawn from, once uploaded
+    atlas: Option<TextureAtlas>,
+    /// Logical screen size the camera frames, once known
+    viewport_size: Option<Vector2>,
     initialized: bool,
+    /// Trigger message on screen and how long it stays up (seconds)
+    message: Option<(String, f32)>,
+    /// Music a trigger asked for, started in `update_audio`
+    pending_music: Option<String>,
+    /// Whether render stats are drawn over the game (toggled with F3)
+    show_render_stats: bool,
 }
 
 impl PlayingState {
     pub fn new() -> Self {
+        Self::with_level("level1")
+    }
+
+    /// Create a playing state for the named level in `levels/`
+    pub fn with_level(level_name: &str) -> Self {
         Self {
             game_world: None,
-            
+            level_name: level_name.to_string(),
+            level: None,
+            loaded_textures: Vec::new(),
+            atlas: None,
+            viewport_size: None,
             initialized: false,
+            message: None,
+            pending_music: None,
+            show_render_stats: false,
         }
     }
     
@@ -297,65 +354,107 @@ impl PlayingState {
             return Ok(());
         }
         
-        let mut game_world = crate::supertux::GameWorld::new();
-        
-        // Create the player at a starting position
-        let player_position = crate::math::Vector2::new(250.0, 368.0); // On Platform1 surface (400-32=368)
-        game_world.create_player(player_position, "tux".to_string());
-        
-        // Create some test platforms
-        self.create_test_level(&mut game_world)?;
+        let level = self.load_level();
+        let tileset = self.load_tileset(&level);
+        let game_world = crate::level::LevelLoader::build_world_with_tileset(&level, tileset)?;
         
         self.game_world = Some(game_world);
+        self.level = Some(level);
         self.initialized = true;
         
-        log::info!("Playing state initialized with test level");
+        log::info!("Playing state initialized with level '{}'", self.level_name);
         Ok(())
     }
     
-    fn create_test_level(&self, game_world: &mut crate::supertux::GameWorld) -> Result<()> {
-            use crate::physics::BodyType;
-            use crate::collision::CollisionLayer;
-            use crate::math::Vector2;
-            
-            // Create platforms manually to avoid borrow checker issues
-            self.create_platform(game_world, "Ground".to_string(), Vector2::new(0.0, 500.0), Vector2::new(800.0, 100.0))?;
-            self.create_platform(game_world, "Platform1".to_string(), Vector2::new(200.0, 400.0), Vector2::new(100.0, 20.0))?;
-            self.create_platform(game_world, "Platform2".to_string(), Vector2::new(400.0, 350.0), Vector2::new(100.0, 20.0))?;
-            
-            Ok(())
+    /// Rebuild the level after Tux died, carrying his progress over and
+    /// putting him at the last checkpoint he reached
+    fn restart_level(&mut self) -> Result<()> {
+        let (Some(level), Some(previous)) = (&self.level, &self.game_world) else {
+            return Ok(());
+        };
+        let tileset = match previous.tilemap() {
+            Some(tilemap) => tilemap.tileset().clone(),
+            None => self.load_tileset(level),
+        };
+        let mut game_world = crate::level::LevelLoader::build_world_with_tileset(level, tileset)?;
+        game_world.resume_from(previous);
+        if let Some(atlas) = &self.atlas {
+            game_world.use_sprite_atlas(atlas);
         }
+        if let Some(viewport_size) = self.viewport_size {
+            game_world.set_viewport_size(viewport_size);
+        }
+
+        log::info!("Restarting level '{}' at {:?}", self.level_name, game_world.respawn_point());
+        self.game_world = Some(game_world);
+        Ok(())
+    }
+
+    /// Throw the level away so the next game starts it afresh
+    fn reset(&mut self) {
+        self.game_world = None;
+        self.level = None;
+        self.initialized = false;
+        self.message = None;
+        self.pending_music = None;
+    }
+
+    /// Load the level file, falling back to the built-in test level
+    fn load_level(&self) -> crate::level::LevelData {
+        let loaded = crate::engine::ResourceManager::new()
+            .and_then(|resource_manager| resource_manager.load_level(&self.level_name));
         
-        fn create_platform(&self, game_world: &mut crate::supertux::GameWorld, name: String, position: crate::math::Vector2, size: crate::math::Vector2) -> Result<()> {use crate::object::{Transform, PhysicsComponent, SpriteComponent};
-                use crate::physics::{PhysicsBody, BodyType};
-                use crate::collision::CollisionLayer;
-                use crate::sprite::Sprite;
-                
-                // Create the object first
-                let object_id = game_world.object_manager_mut().create_object(name.clone());
-                
-                // Create physics body
-                let physics_body = PhysicsBody::new(0, position, size, BodyType::Static);
-                let body_id = game_world.physics_world_mut().add_body(physics_body);
-                
-                // Determine texture based on platform name
-                let texture_name = if name.contains("Ground") {
-                    "ground"
-                } else {
-                    "platform"
-                };
-                
-                // Add components to the object
-                if let Some(object) = game_world.object_manager_mut().get_object_mut(object_id) {
-                    object.add_component(Transform::new(position));
-                    object.add_component(PhysicsComponent::new(body_id, BodyType::Static, CollisionLayer::World));
-                    
-                    // Add sprite component
-                    let sprite = Sprite::new(texture_name.to_string(), crate::math::Vector2::ZERO);
-                    object.add_component(SpriteComponent::new(sprite));
-                }
-                
-                Ok(())}
+        match loaded {
+            Ok(level) => level,
+            Err(e) => {
+                log::warn!("Could not load level '{}': {}. Using built-in test level", self.level_name, e);
+                crate::level::LevelData::test_level()
+            }
+        }
+    }
+
+    /// Draw the sprite render stats in the top-left corner
+    fn render_stats_overlay(video: &mut VideoManager, stats: &RenderStats) -> Result<()> {
+        let text = stats.overlay_text();
+        let size = video.text_size(&text, 16.0);
+        video.set_blend_mode(BlendMode::Blend);
+        video.fill_rect(Rect::new(4.0, 4.0, size.x + 8.0, size.y + 8.0), Color::rgba(0, 0, 0, 160))?;
+        video.set_blend_mode(BlendMode::None);
+        video.draw_text(&text, Vector2::new(8.0, 8.0), 16.0, Color::WHITE)
+    }
+
+    /// Draw a trigger message centered near the top of the screen
+    fn render_message(video: &mut VideoManager, text: &str) -> Result<()> {
+        let size = video.text_size(text, 24.0);
+        let position = Vector2::new((video.output_size().x - size.x) * 0.5, 64.0);
+        video.set_blend_mode(BlendMode::Blend);
+        video.fill_rect(Rect::new(position.x - 8.0, position.y - 8.0, size.x + 16.0, size.y + 16.0), Color::rgba(0, 0, 0, 160))?;
+        video.set_blend_mode(BlendMode::None);
+        video.draw_text(text, position, 24.0, Color::WHITE)
+    }
+
+    /// Load a music file relative to the data directory, unless it is already loaded
+    fn load_music(audio: &mut AudioManager, music: &str) -> Result<()> {
+        if !audio.has_clip(music) {
+            let path = crate::engine::ResourceManager::new()?.get_resource_path(music);
+            audio.load_clip(music, path)?;
+        }
+        Ok(())
+    }
+
+    /// Load the level's tileset, falling back to one built from the level itself
+    fn load_tileset(&self, level: &crate::level::LevelData) -> crate::tilemap::Tileset {
+        let loaded = crate::engine::ResourceManager::new()
+            .and_then(|resource_manager| crate::level::LevelLoader::load_tileset(&resource_manager, level));
+
+        match loaded {
+            Ok(tileset) => tileset,
+            Err(e) => {
+                log::warn!("Could not load tileset for level '{}': {}", self.level_name, e);
+                crate::level::LevelLoader::default_tileset(level)
+            }
+        }
+    }
 }
 
 impl GameState for PlayingState {
@@ -367,64 +466,174 @@ impl GameState for PlayingState {
         Ok(())
     }
 
+    fn load_resources(&mut self, video: &mut VideoManager) -> Result<()> {
+        self.on_resize(video.output_size());
+        let Some(game_world) = &self.game_world else {
+            return Ok(());
+        };
+        if !self.loaded_textures.is_empty() {
+            return Ok(());
+        }
+
+        let tileset_textures = game_world.tilemap().map(|tilemap| &tilemap.tileset().textures);
+        let level_textures = self.level.as_ref().map(|level| &level.textures);
+        for textures in level_textures.into_iter().chain(tileset_textures) {
+            for (name, path) in textures {
+                if self.loaded_textures.contains(name) {
+                    continue;
+                }
+                match video.acquire_texture(name, path) {
+                    Ok(()) => self.loaded_textures.push(name.clone()),
+                    Err(e) => log::warn!("Failed to load {} texture: {}", name, e),
+                }
+            }
+        }
+
+        // Every Tux form, power-up, projectile and the checkpoint bell; forms without their own art borrow big Tux's
+        let form_textures = TuxForm::ALL.map(|form| (form.texture_name(), form.texture_path(), form.is_big()));
+        let power_up_textures = PowerUpKind::ALL.map(|kind| (kind.default_texture(), kind.texture_path(), false));
+        let projectile_textures = ProjectileKind::ALL.map(|kind| (kind.texture_name(), kind.texture_path(), false));
+        let checkpoint_texture = (CHECKPOINT_TEXTURE, CHECKPOINT_TEXTURE_PATH.to_string(), false);
+        let textures = form_textures.into_iter().chain(power_up_textures).chain(projectile_textures).chain([checkpoint_texture]);
+        for (name, path, is_big) in textures {
+            if self.loaded_textures.iter().any(|loaded| loaded == name) {
+                continue;
+            }
+            let loaded = video.acquire_texture(name, &path).or_else(|e| {
+                if is_big {
+                    video.acquire_texture(name, TuxForm::Big.texture_path())
+                } else {
+                    Err(e)
+                }
+            });
+            match loaded {
+                Ok(()) => self.loaded_textures.push(name.to_string()),
+                Err(e) => log::warn!("Failed to load {} texture: {}", name, e),
+            }
+        }
+
+        // Effects fall back to plain squares if their textures are missing
+        for (name, path) in crate::particle::effects::TEXTURES {
+            match video.acquire_texture(name, path) {
+                Ok(()) => self.loaded_textures.push(name.to_string()),
+                Err(e) => log::debug!("No {} particle texture: {}", name, e),
+            }
+        }
+
+        // Tux is drawn from an atlas of his forms' frames, or from the textures above without one
+        let mut builder = AtlasBuilder::new();
+        for form in TuxForm::ALL {
+            builder.add_definition(&form.sprite_definition());
+        }
+        match builder.build_cached(ATLAS_CACHE_DIR).and_then(|atlas| atlas.upload(video).map(|()| atlas)) {
+            Ok(atlas) => {
+                if let Some(game_world) = &mut self.game_world {
+                    game_world.use_sprite_atlas(&atlas);
+                }
+                self.atlas = Some(atlas);
+            }
+            Err(e) => log::warn!("Failed to build the sprite atlas: {}", e),
+        }
+
+        log::info!("Loaded {} textures for level '{}'", self.loaded_textures.len(), self.level_name);
+        Ok(())
+    }
+
+    fn on_resize(&mut self, output_size: Vector2) {
+        self.viewport_size = Some(output_size);
+        if let Some(game_world) = &mut self.game_world {
+            game_world.set_viewport_size(output_size);
+        }
+    }
+
+    fn unload_resources(&mut self, video: &mut VideoManager) {
+        if let Some(atlas) = self.atlas.take() {
+            atlas.unload(video);
+        }
+        for name in self.loaded_textures.drain(..) {
+            video.release_texture(&name);
+        }
+    }
+
     fn update(&mut self, delta_time: f32) -> Result<()> {
         // Default update - does nothing, use update_with_input instead
         Ok(())
     }
 
-    fn update_with_input(&mut self, delta_time: f32, input_manager: &crate::control::InputManager) -> Result<()> {
+    fn update_with_input(&mut self, delta_time: f32, input_manager: &crate::control::InputManager) -> Result<Option<StateId>> {
+        if let Some((_, remaining)) = &mut self.message {
+            *remaining -= delta_time;
+            if *remaining <= 0.0 {
+                self.message = None;
+            }
+        }
+
         // Update game world only if initialized
         if let Some(ref mut game_world) = &mut self.game_world {
             game_world.update(input_manager, delta_time)?;
+
+            if game_world.is_death_finished() {
+                if game_world.get_player_lives() > 0 {
+                    self.restart_level()?;
+                } else {
+                    log::info!("Game over with a score of {}", game_world.get_player_score());
+                    self.reset();
+                    return Ok(Some(StateId::GameOver));
+                }
+                return Ok(None);
+            }
+
+            for action in game_world.take_trigger_actions() {
+                match action {
+                    crate::trigger::TriggerAction::EndSequence => {
+                        log::info!("Level '{}' complete with a score of {}", self.level_name, game_world.get_player_score());
+                        self.reset();
+                        return Ok(Some(StateId::Menu));
+                    }
+                    crate::trigger::TriggerAction::Message { text, duration } => self.message = Some((text, duration)),
+                    crate::trigger::TriggerAction::ChangeMusic { music } => self.pending_music = Some(music),
+                    _ => {}
+                }
+            }
         }
         
+        Ok(None)
+    }
+
+    fn update_audio(&mut self, audio: &mut AudioManager) -> Result<()> {
+        if let Some(music) = self.pending_music.take() {
+            // A missing or broken music file shouldn't stop the game
+            match Self::load_music(audio, &music).and_then(|()| audio.play_music(&music)) {
+                Ok(()) => log::info!("Playing music {}", music),
+                Err(e) => log::warn!("Failed to play music {}: {}", music, e),
+            }
+        }
         Ok(())
     }
 
-    fn render(&self, canvas: &mut Canvas<Window>) -> Result<()> {
+    fn render(&self, video: &mut VideoManager) -> Result<()> {
             log::debug!("PlayingState render called, initialized: {}", self.initialized);
             
             // Only render if the state has been properly initialized
             if !self.initialized {
                 // Clear screen with a simple color to indicate not ready
-                canvas.set_draw_color(sdl2::pixels::Color::RGB(50, 50, 50)); // Dark gray
-                canvas.clear();
+                video.clear(Color::rgb(50, 50, 50)); // Dark gray
                 return Ok(());
             }
             
             // Clear screen with sky blue
-            canvas.set_draw_color(sdl2::pixels::Color::RGB(135, 206, 235));
-            canvas.clear();
+            video.clear(Color::SKY_BLUE);
             
             // Render game world only if it exists and we're initialized
             if let Some(ref game_world) = self.game_world {
-                // Create a temporary texture manager for rendering
-                let texture_creator = canvas.texture_creator();
-                let mut texture_manager = crate::sprite::TextureManager::new(&texture_creator);
-                
-                // Load textures for this frame (in a real game, this would be cached)
-                // Use downloaded SuperTux assets if they exist
-                if std::path::Path::new("assets/sprites/creatures/tux/small/idle-0.png").exists() {
-                    if let Err(e) = texture_manager.load_texture_from_file("tux", "assets/sprites/creatures/tux/small/idle-0.png") {
-                        log::warn!("Failed to load tux texture: {}", e);
-                    } else {
-                        log::info!("Successfully loaded tux texture");
-                    }
-                } else {
-                    log::warn!("Tux texture file does not exist");
+                let stats = game_world.render(video)?;
+                if self.show_render_stats {
+                    Self::render_stats_overlay(video, &stats)?;
                 }
-                if std::path::Path::new("assets/sprites/tiles/blocks/brick0.png").exists() {
-                    if let Err(e) = texture_manager.load_texture_from_file("platform", "assets/sprites/tiles/blocks/brick0.png") {
-                        log::warn!("Failed to load platform texture: {}", e);
-                    }
-                }
-                if std::path::Path::new("assets/sprites/tiles/blocks/bigblock.png").exists() {
-                    if let Err(e) = texture_manager.load_texture_from_file("ground", "assets/sprites/tiles/blocks/bigblock.png") {
-                        log::warn!("Failed to load ground texture: {}", e);
-                    }
-                }
-                
-                game_world.render(canvas, &texture_manager)?;
+            }
+
+            if let Some((text, _)) = &self.message {
+                Self::render_message(video, text)?;
             }
             
             Ok(())
@@ -437,6 +646,9 @@ impl GameState for PlayingState {
                 log::info!("Pausing game");
                 // TODO: Transition to paused state
             }
+            Keycode::F3 => {
+                self.show_render_stats = !self.show_render_stats;
+            }
             _ => {}
         }
         Ok(None)
@@ -452,3 +664,49 @@ impl GameState for PlayingState {
         "Playing"
     }
 }
+
+/// Shown when Tux has run out of lives
+pub struct GameOverState;
+
+impl GameOverState {
+    pub fn new() -> Self {
+        Self
+    }
+
+    /// Draw a line of text centered horizontally on the screen
+    fn draw_centered(video: &mut VideoManager, text: &str, y: f32, size: f32, color: Color) -> Result<()> {
+        let width = video.text_size(text, size).x;
+        let x = (video.output_size().x - width) * 0.5;
+        video.draw_text(text, Vector2::new(x, y), size, color)
+    }
+}
+
+impl Default for GameOverState {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl GameState for GameOverState {
+    fn update(&mut self, _delta_time: f32) -> Result<()> {
+        Ok(())
+    }
+
+    fn render(&self, video: &mut VideoManager) -> Result<()> {
+        video.clear(Color::BLACK);
+        let middle = video.output_size().y * 0.5;
+        Self::draw_centered(video, "GAME OVER", middle - 48.0, 48.0, Color::WHITE)?;
+        Self::draw_centered(video, "Press Enter to return to the menu", middle + 16.0, 16.0, Color::WHITE)
+    }
+
+    fn handle_key_down(&mut self, keycode: Keycode) -> Result<Option<StateId>> {
+        match keycode {
+            Keycode::Return | Keycode::Space => Ok(Some(StateId::Menu)),
+            _ => Ok(None),
+        }
+    }
+
+    fn name(&self) -> &str {
+        "GameOver"
+    }
+}
@@ -3,31 +3,30 @@
 use crate::util::Result;
 use crate::math::Vector2;
 use crate::config::*;
-use crate::sprite::TextureManager;
+use crate::video::{Color, RenderBackendKind, VideoConfig, VideoManager};
 use crate::audio::AudioManager;
-use crate::control::InputManager;
+use crate::control::{GameAction, InputManager};
+use sdl2::event::{Event, WindowEvent};
 use std::time::{Duration, Instant};
 
 pub mod game_state;
 pub mod resource_manager;
 
-pub use game_state::{GameState, GameStateManager, MenuState, PlayingState, StateId};
+pub use game_state::{GameOverState, GameState, GameStateManager, MenuState, PlayingState, StateId};
 pub use resource_manager::ResourceManager;
 
 /// Main game engine that manages the game loop and systems
 pub struct Engine {
     /// SDL2 context
     sdl_context: sdl2::Sdl,
-    /// Video subsystem
-    video_subsystem: sdl2::VideoSubsystem,
+    /// Video subsystem (`None` when rendering headless)
+    video_subsystem: Option<sdl2::VideoSubsystem>,
     /// Audio subsystem
     audio_subsystem: sdl2::AudioSubsystem,
-    /// Canvas for rendering
-    canvas: sdl2::render::Canvas<sdl2::video::Window>,
+    /// Video manager owning the render backend and textures
+    video: VideoManager,
     /// Event pump for handling input
     event_pump: sdl2::EventPump,
-    /// Texture creator for loading textures
-    texture_creator: sdl2::render::TextureCreator<sdl2::video::WindowContext>,
     /// Audio manager for sound and music
     audio_manager: AudioManager,
     /// Input manager for handling controls
@@ -47,35 +46,59 @@ pub struct Engine {
 }
 
 impl Engine {
-    /// Create a new engine instance
+    /// Create a new engine instance drawing to a window
     pub fn new() -> Result<Self> {
+        Self::with_backend(RenderBackendKind::Window)
+    }
+
+    /// Create a new engine instance drawing with the given backend
+    pub fn with_backend(backend: RenderBackendKind) -> Result<Self> {
+        Self::with_video_config(backend, &VideoConfig::default())
+    }
+
+    /// Create a new engine instance with the given backend and display settings
+    pub fn with_video_config(backend: RenderBackendKind, video_config: &VideoConfig) -> Result<Self> {
+        if let RenderBackendKind::Headless { .. } = backend {
+            // Don't require a sound card on machines without a display
+            if std::env::var_os("SDL_AUDIODRIVER").is_none() {
+                std::env::set_var("SDL_AUDIODRIVER", "dummy");
+            }
+        }
+
         // Initialize SDL2
         let sdl_context = sdl2::init().map_err(|e| crate::util::Error::Sdl2(e))?;
-        let video_subsystem = sdl_context.video().map_err(|e| crate::util::Error::Sdl2(e))?;
         let audio_subsystem = sdl_context.audio().map_err(|e| crate::util::Error::Sdl2(e))?;
 
-        // Create window
-        let window = video_subsystem
-            .window("RustUX - SuperTux in Rust", SCREEN_WIDTH, SCREEN_HEIGHT)
-            .position_centered()
-            .resizable()
-            .build()
-            .map_err(|e| crate::util::Error::Sdl2(e.to_string()))?;
-
-        // Create canvas
-        let canvas = window
-            .into_canvas()
-            .accelerated()
-            .present_vsync()
-            .build()
-            .map_err(|e| crate::util::Error::Sdl2(e.to_string()))?;
+        // Create video manager
+        let (video_subsystem, mut video) = match backend {
+            RenderBackendKind::Window => {
+                let video_subsystem = sdl_context.video().map_err(|e| crate::util::Error::Sdl2(e))?;
+
+                // Create window
+                let window = video_subsystem
+                    .window("RustUX - SuperTux in Rust", SCREEN_WIDTH, SCREEN_HEIGHT)
+                    .position_centered()
+                    .resizable()
+                    .build()
+                    .map_err(|e| crate::util::Error::Sdl2(e.to_string()))?;
+
+                // Create canvas
+                let canvas = window
+                    .into_canvas()
+                    .accelerated()
+                    .present_vsync()
+                    .build()
+                    .map_err(|e| crate::util::Error::Sdl2(e.to_string()))?;
+
+                (Some(video_subsystem), VideoManager::new(canvas)?)
+            }
+            RenderBackendKind::Headless { width, height } => (None, VideoManager::headless(width, height)?),
+        };
+        video.apply_config(video_config)?;
 
         // Create event pump
         let event_pump = sdl_context.event_pump().map_err(|e| crate::util::Error::Sdl2(e))?;
 
-        // Create texture creator
-        let texture_creator = canvas.texture_creator();
-
         // Initialize audio manager
         let audio_manager = AudioManager::new(audio_subsystem.clone())
             .map_err(|e| crate::util::Error::Audio(format!("Failed to initialize audio: {}", e)))?;
@@ -94,9 +117,8 @@ impl Engine {
             sdl_context,
             video_subsystem,
             audio_subsystem,
-            canvas,
+            video,
             event_pump,
-            texture_creator,
             audio_manager,
             input_manager,
             resource_manager,
@@ -142,7 +164,11 @@ impl Engine {
 
     /// Handle SDL2 events
     fn handle_events(&mut self) -> Result<()> {
-        for event in self.event_pump.poll_iter() {
+        let events: Vec<Event> = self.event_pump.poll_iter().collect();
+        for event in events {
+            // Game code sees the mouse in logical coordinates
+            let event = self.to_logical(event);
+
             // Process event with input manager
             self.input_manager.process_event(&event);
             
@@ -150,6 +176,12 @@ impl Engine {
                 sdl2::event::Event::Quit { .. } => {
                     self.running = false;
                 }
+                Event::Window { win_event: WindowEvent::SizeChanged(..), .. } => {
+                    self.video.refresh_viewport();
+                    if let Some(state) = self.state_manager.current_state_mut() {
+                        state.on_resize(self.video.output_size());
+                    }
+                }
                 sdl2::event::Event::KeyDown { keycode: Some(keycode), .. } => {
                                     if keycode == sdl2::keyboard::Keycode::Escape {
                                         self.running = false;
@@ -182,21 +214,66 @@ impl Engine {
                 _ => {}
             }
         }
+
+        if self.input_manager.is_action_just_pressed(GameAction::ToggleFullscreen) {
+            let fullscreen = self.video.toggle_fullscreen()?;
+            log::info!("Fullscreen {}", if fullscreen { "on" } else { "off" });
+        }
         Ok(())
     }
 
+    /// Map the window coordinates of mouse events to the logical resolution
+    fn to_logical(&self, event: Event) -> Event {
+        let viewport = self.video.viewport();
+        let map = |x: i32, y: i32| {
+            let position = viewport.to_logical(Vector2::new(x as f32, y as f32));
+            (position.x.floor() as i32, position.y.floor() as i32)
+        };
+
+        match event {
+            Event::MouseMotion { timestamp, window_id, which, mousestate, x, y, xrel, yrel } => {
+                let (x, y) = map(x, y);
+                let relative = Vector2::new(xrel as f32, yrel as f32) / viewport.scale;
+                Event::MouseMotion {
+                    timestamp,
+                    window_id,
+                    which,
+                    mousestate,
+                    x,
+                    y,
+                    xrel: relative.x.round() as i32,
+                    yrel: relative.y.round() as i32,
+                }
+            }
+            Event::MouseButtonDown { timestamp, window_id, which, mouse_btn, clicks, x, y } => {
+                let (x, y) = map(x, y);
+                Event::MouseButtonDown { timestamp, window_id, which, mouse_btn, clicks, x, y }
+            }
+            Event::MouseButtonUp { timestamp, window_id, which, mouse_btn, clicks, x, y } => {
+                let (x, y) = map(x, y);
+                Event::MouseButtonUp { timestamp, window_id, which, mouse_btn, clicks, x, y }
+            }
+            event => event,
+        }
+    }
+
     /// Update game logic
     fn update(&mut self) -> Result<()> {
-        // Update input manager
-        self.input_manager.update();
-        
         // Update current game state
         if let Some(state) = self.state_manager.current_state_mut() {
-            state.update_with_input(self.delta_time, &self.input_manager)?;
+            let next_state = state.update_with_input(self.delta_time, &self.input_manager)?;
+            state.update_audio(&mut self.audio_manager)?;
+            if let Some(next_state) = next_state {
+                log::info!("State transition requested: {:?}", next_state);
+                self.state_manager.set_state(next_state)?;
+            }
         }
 
+        // Forget this frame's presses and releases only after the state has seen them
+        self.input_manager.update();
+
         // Handle state transitions
-        self.state_manager.process_transitions()?;
+        self.state_manager.process_transitions(&mut self.video)?;
 
         Ok(())
     }
@@ -204,16 +281,15 @@ impl Engine {
     /// Render the current frame
     fn render(&mut self) -> Result<()> {
         // Clear screen
-        self.canvas.set_draw_color(sdl2::pixels::Color::RGB(135, 206, 235)); // Sky blue
-        self.canvas.clear();
+        self.video.clear(Color::SKY_BLUE);
 
         // Render current game state
         if let Some(state) = self.state_manager.current_state() {
-            state.render(&mut self.canvas)?;
+            state.render(&mut self.video)?;
         }
 
         // Present frame
-        self.canvas.present();
+        self.video.present();
         Ok(())
     }
 
@@ -247,11 +323,6 @@ impl Engine {
         &mut self.state_manager
     }
 
-    /// Get the texture creator
-    pub fn texture_creator(&self) -> &sdl2::render::TextureCreator<sdl2::video::WindowContext> {
-        &self.texture_creator
-    }
-
     /// Get the audio manager
     pub fn audio_manager(&self) -> &AudioManager {
         &self.audio_manager
@@ -272,20 +343,19 @@ impl Engine {
         &mut self.input_manager
     }
 
-    /// Create a texture manager for the current frame
-    pub fn create_texture_manager(&self) -> TextureManager {
-        TextureManager::new(&self.texture_creator)
+    /// Get the size the game draws at, in logical pixels
+    pub fn canvas_size(&self) -> Vector2 {
+        self.video.output_size()
     }
 
-    /// Get the canvas size
-    pub fn canvas_size(&self) -> Vector2 {
-        let (w, h) = self.canvas.output_size().unwrap_or((SCREEN_WIDTH, SCREEN_HEIGHT));
-        Vector2::new(w as f32, h as f32)
+    /// Get the video manager
+    pub fn video(&self) -> &VideoManager {
+        &self.video
     }
 
-    /// Get the canvas for rendering
-    pub fn canvas(&mut self) -> &mut sdl2::render::Canvas<sdl2::video::Window> {
-        &mut self.canvas
+    /// Get the video manager mutably
+    pub fn video_mut(&mut self) -> &mut VideoManager {
+        &mut self.video
     }
 }
 
@@ -92,6 +92,26 @@ impl ResourceManager {
         Ok(content)
     }
 
+    /// Load and parse a level file
+    pub fn load_level(&self, level_name: &str) -> Result<crate::level::LevelData> {
+        let content = self.load_level_data(level_name)?;
+        crate::level::LevelData::from_json(&content)
+    }
+
+    /// Load and parse a tileset file from `tilesets/`
+    pub fn load_tileset(&self, tileset_name: &str) -> Result<crate::tilemap::Tileset> {
+        let tileset_path = self.get_resource_path(format!("tilesets/{}.json", tileset_name));
+
+        if !tileset_path.exists() {
+            return Err(Error::ResourceNotFound(format!(
+                "Tileset file not found: {}",
+                tileset_path.display()
+            )));
+        }
+
+        crate::tilemap::Tileset::load_from_file(&tileset_path)
+    }
+
     /// Load configuration data
     pub fn load_config<T>(&self, config_name: &str) -> Result<T>
     where
@@ -2,10 +2,9 @@
 
 use crate::util::Result;
 use crate::math::{Vector2, Rect};
-use crate::sprite::{Sprite, TextureManager, SpriteRenderer};
+use crate::sprite::{Sprite, SpriteRenderer};
 use crate::gui::{GuiElement, GuiEvent, GuiTheme, utils};
-use sdl2::render::Canvas;
-use sdl2::video::Window;
+use crate::video::{Color, VideoManager};
 use sdl2::keyboard::Keycode;
 
 /// Button states
@@ -205,18 +204,21 @@ impl GuiElement for Button {
         Ok(())
     }
 
-    fn render(&self, canvas: &mut Canvas<Window>, texture_manager: &TextureManager) -> Result<()> {
+    fn render(&self, video: &mut VideoManager) -> Result<()> {
         if !self.visible {
             return Ok(());
         }
 
         // Render button sprite
         if let Some(sprite) = self.get_current_sprite() {
-            SpriteRenderer::render_sprite(canvas, texture_manager, sprite)?;
+            SpriteRenderer::render_sprite(video, sprite)?;
         }
 
-        // TODO: Render text (would need font rendering system)
-        // For now, we'll just render the sprite
+        let color = match self.state {
+            ButtonState::Hovered | ButtonState::Pressed => self.theme.highlight_color,
+            _ => self.theme.text_color,
+        };
+        video.draw_text(&self.text, self.get_text_position(), self.theme.font_size as f32, Color::from(color))?;
 
         Ok(())
     }
@@ -2,11 +2,10 @@
 
 use crate::util::Result;
 use crate::math::{Vector2, Rect};
-use crate::sprite::{Sprite, TextureManager, SpriteRenderer};
+use crate::sprite::{Sprite, SpriteRenderer};
 use crate::gui::{GuiElement, GuiEvent, GuiTheme, Button, utils};
 use crate::gui::button::ButtonBuilder;
-use sdl2::render::Canvas;
-use sdl2::video::Window;
+use crate::video::{Color, VideoManager};
 use sdl2::keyboard::Keycode;
 use std::collections::HashMap;
 
@@ -294,22 +293,23 @@ impl GuiElement for Menu {
         Ok(())
     }
 
-    fn render(&self, canvas: &mut Canvas<Window>, texture_manager: &TextureManager) -> Result<()> {
+    fn render(&self, video: &mut VideoManager) -> Result<()> {
         if !self.visible {
             return Ok(());
         }
 
         // Render background
         if let Some(ref bg_sprite) = self.background_sprite {
-            SpriteRenderer::render_sprite(canvas, texture_manager, bg_sprite)?;
+            SpriteRenderer::render_sprite(video, bg_sprite)?;
         }
 
         // Render all buttons
         for button in self.buttons.values() {
-            button.render(canvas, texture_manager)?;
+            button.render(video)?;
         }
 
-        // TODO: Render title text (would need font rendering system)
+        let title_size = (self.theme.font_size + 4) as f32;
+        video.draw_text(&self.title, self.get_title_position(), title_size, Color::from(self.theme.text_color))?;
 
         Ok(())
     }
@@ -2,9 +2,8 @@
 
 use crate::util::Result;
 use crate::math::{Vector2, Rect};
-use crate::sprite::{Sprite, TextureManager, SpriteRenderer};
-use sdl2::render::Canvas;
-use sdl2::video::Window;
+use crate::sprite::{Sprite, SpriteRenderer};
+use crate::video::VideoManager;
 use sdl2::keyboard::Keycode;
 use std::collections::HashMap;
 
@@ -22,7 +21,7 @@ pub trait GuiElement {
     fn update(&mut self, delta_time: f32) -> Result<()>;
     
     /// Render the element
-    fn render(&self, canvas: &mut Canvas<Window>, texture_manager: &TextureManager) -> Result<()>;
+    fn render(&self, video: &mut VideoManager) -> Result<()>;
     
     /// Handle mouse input
     fn handle_mouse(&mut self, x: i32, y: i32, pressed: bool) -> Result<bool>;
@@ -96,10 +95,10 @@ impl GuiManager {
     }
 
     /// Render all GUI elements
-    pub fn render(&self, canvas: &mut Canvas<Window>, texture_manager: &TextureManager) -> Result<()> {
+    pub fn render(&self, video: &mut VideoManager) -> Result<()> {
         for element in self.elements.values() {
             if element.is_visible() {
-                element.render(canvas, texture_manager)?;
+                element.render(video)?;
             }
         }
         Ok(())
@@ -315,10 +314,9 @@ pub mod utils {
             && point.y <= rect.y + rect.height
     }
 
-    /// Calculate text size (placeholder - would need actual font rendering)
+    /// Calculate the size of text drawn with the built-in font
     pub fn calculate_text_size(text: &str, font_size: u32) -> Vector2 {
-        // Simplified calculation - in a real implementation, you'd use actual font metrics
-        Vector2::new(text.len() as f32 * font_size as f32 * 0.6, font_size as f32)
+        crate::video::font::text_size(text, font_size as f32)
     }
 
     /// Create a centered rectangle
@@ -2,10 +2,8 @@
 
 use crate::util::Result;
 use crate::math::{Vector2, Rect};
-use crate::sprite::{TextureManager};
 use crate::gui::{GuiElement, GuiEvent, GuiTheme, utils};
-use sdl2::render::Canvas;
-use sdl2::video::Window;
+use crate::video::{Color, VideoManager};
 use sdl2::keyboard::Keycode;
 
 /// Text alignment options
@@ -203,20 +201,16 @@ impl GuiElement for Text {
         Ok(())
     }
 
-    fn render(&self, _canvas: &mut Canvas<Window>, _texture_manager: &TextureManager) -> Result<()> {
+    fn render(&self, video: &mut VideoManager) -> Result<()> {
         if !self.visible {
             return Ok(());
         }
 
-        // TODO: Implement actual text rendering
-        // This would require a font rendering system (like SDL2_ttf)
-        // For now, this is a placeholder
-
-        // The actual implementation would:
-        // 1. Load the font
-        // 2. Create a texture from the text
-        // 3. Render the texture to the canvas
-        // 4. Handle text wrapping and alignment
+        let color = if self.selected { self.theme.highlight_color } else { self.color };
+        for (index, line) in self.wrap_text().iter().enumerate() {
+            let position = self.get_line_position(line, index);
+            video.draw_text(line, position, self.font_size as f32, Color::from(color))?;
+        }
 
         Ok(())
     }
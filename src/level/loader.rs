@@ -0,0 +1,250 @@
+//! Builds a `GameWorld` from level data
+
+use crate::level::{BadguyKind, LevelData, LevelObject, TileLayerData};
+use crate::engine::ResourceManager;
+use crate::object::{factory, PhysicsComponent, SpriteComponent};
+use crate::physics::{BodyType, PhysicsMaterial};
+use crate::collision::{CollisionLayer, CollisionType};
+use crate::sprite::Sprite;
+use crate::supertux::{checkpoint, powerup, GameWorld};
+use crate::trigger::Trigger;
+use crate::tilemap::{TileAttributes, TileDefinition, TileLayer, Tilemap, Tileset};
+use crate::math::{Rect, Vector2};
+use crate::util::Result;
+
+/// Size of collectibles placed by levels
+const COLLECTIBLE_SIZE: f32 = 32.0;
+
+/// Turns `LevelData` into a fully populated `GameWorld`
+pub struct LevelLoader;
+
+impl LevelLoader {
+    /// Load a level by name through the resource manager and build its world
+    pub fn load(resource_manager: &ResourceManager, level_name: &str) -> Result<GameWorld> {
+        let level = resource_manager.load_level(level_name)?;
+        let tileset = Self::load_tileset(resource_manager, &level)?;
+        Self::build_world_with_tileset(&level, tileset)
+    }
+
+    /// Load the tileset named by the level, or build one from its tile textures
+    pub fn load_tileset(resource_manager: &ResourceManager, level: &LevelData) -> Result<Tileset> {
+        match level.tileset {
+            Some(ref name) => resource_manager.load_tileset(name),
+            None => Ok(Self::default_tileset(level)),
+        }
+    }
+
+    /// Build a tileset for levels without a tileset file
+    ///
+    /// Every tile id used in the level gets its `tile_textures` entry (or
+    /// `tile_<id>`) as texture, and is solid if it appears in a solid layer.
+    pub fn default_tileset(level: &LevelData) -> Tileset {
+        let mut tileset = Tileset::new(format!("{} tiles", level.metadata.name));
+
+        for layer in &level.tile_layers {
+            for &id in layer.tiles.iter().filter(|&&id| id != 0) {
+                let solid = layer.solid || tileset.attributes(id).solid;
+                let texture = layer.tile_textures.get(&id).cloned()
+                    .or_else(|| tileset.get(id).map(|tile| tile.texture.clone()))
+                    .unwrap_or_else(|| format!("tile_{}", id));
+                tileset.add_tile(TileDefinition {
+                    id,
+                    texture,
+                    cell: None,
+                    attributes: if solid { TileAttributes::solid() } else { TileAttributes::default() },
+                });
+            }
+        }
+
+        tileset
+    }
+
+    /// Build a game world from level data
+    pub fn build_world(level: &LevelData) -> Result<GameWorld> {
+        Self::build_world_with_tileset(level, Self::default_tileset(level))
+    }
+
+    /// Build a game world from level data using the given tileset
+    pub fn build_world_with_tileset(level: &LevelData, tileset: Tileset) -> Result<GameWorld> {
+        level.validate()?;
+
+        let mut game_world = GameWorld::new();
+        game_world.set_world_bounds(level.world_bounds);
+        game_world.set_spawn_point(level.spawn.position());
+
+        if !level.tile_layers.is_empty() {
+            // Solid tiles are collided against through the tile grid, not as bodies
+            let tilemap = Self::build_tilemap(level, tileset);
+            game_world.set_tilemap(tilemap);
+        }
+
+        for background in &level.backgrounds {
+            game_world.add_background(background.to_layer());
+        }
+
+        for object in &level.objects {
+            Self::build_object(&mut game_world, object);
+        }
+
+        game_world.create_player(level.spawn.position(), "tux".to_string());
+
+        log::info!(
+            "Built level '{}' with {} objects and {} tile layers",
+            level.metadata.name,
+            level.objects.len(),
+            level.tile_layers.len()
+        );
+        Ok(game_world)
+    }
+
+    /// Convert the level's tile layers into a tilemap
+    fn build_tilemap(level: &LevelData, tileset: Tileset) -> Tilemap {
+        let mut tilemap = Tilemap::new(tileset);
+        for layer in &level.tile_layers {
+            tilemap.add_layer(Self::build_tile_layer(layer));
+        }
+        tilemap
+    }
+
+    /// Convert a single tile layer
+    fn build_tile_layer(layer: &TileLayerData) -> TileLayer {
+        TileLayer::from_tiles(layer.name.clone(), layer.width, layer.height, &layer.tiles)
+            .with_solid(layer.solid)
+            .with_z(layer.z)
+    }
+
+    /// Create a single level object
+    pub fn build_object(game_world: &mut GameWorld, object: &LevelObject) {
+        match object {
+            LevelObject::Platform { name, x, y, width, height, texture, one_way, ice, path } => {
+                let position = Vector2::new(*x, *y);
+                let size = Vector2::new(*width, *height);
+                let body_type = if path.is_some() { BodyType::Kinematic } else { BodyType::Static };
+                let (object_manager, physics_world) = game_world.managers_mut();
+                let id = factory::create_physics_object(
+                    object_manager,
+                    physics_world,
+                    name.clone().unwrap_or_else(|| "Platform".to_string()),
+                    position,
+                    size,
+                    body_type,
+                    CollisionLayer::World,
+                );
+                if let Some(object) = object_manager.get_object_mut(id) {
+                    let sprite = Sprite::with_size(texture.clone(), Vector2::ZERO, size);
+                    object.add_component(SpriteComponent::new(sprite));
+
+                    if let Some(physics_comp) = object.get_component::<PhysicsComponent>() {
+                        if *one_way {
+                            physics_world.set_body_collision_type(physics_comp.body_id, CollisionType::Platform);
+                        }
+                        if *ice {
+                            if let Some(body) = physics_world.get_body_mut(physics_comp.body_id) {
+                                body.material = PhysicsMaterial::ice();
+                            }
+                        }
+                        if let Some(path) = path {
+                            physics_world.set_body_path(physics_comp.body_id, path.to_path());
+                        }
+                    }
+                }
+            }
+            LevelObject::Badguy { kind, x, y, texture } => {
+                let position = Vector2::new(*x, *y);
+                let texture = texture.clone().unwrap_or_else(|| kind.default_texture().to_string());
+                let (object_manager, physics_world) = game_world.managers_mut();
+                match kind {
+                    BadguyKind::Goomba => {
+                        crate::badguy::factory::create_goomba(object_manager, physics_world, position, texture);
+                    }
+                    BadguyKind::Spiky => {
+                        crate::badguy::factory::create_spiky(object_manager, physics_world, position, texture);
+                    }
+                    BadguyKind::Flying => {
+                        crate::badguy::factory::create_flying(object_manager, physics_world, position, texture);
+                    }
+                }
+            }
+            LevelObject::Collectible { kind, x, y, value, texture } => {
+                let (object_manager, physics_world) = game_world.managers_mut();
+                factory::create_collectible(
+                    object_manager,
+                    physics_world,
+                    format!("{:?}", kind),
+                    Vector2::new(*x, *y),
+                    texture.clone().unwrap_or_else(|| kind.default_texture().to_string()),
+                    Vector2::new(COLLECTIBLE_SIZE, COLLECTIBLE_SIZE),
+                    value.unwrap_or_else(|| kind.default_value()),
+                );
+            }
+            LevelObject::PowerUp { kind, x, y, texture } => {
+                let (object_manager, physics_world) = game_world.managers_mut();
+                powerup::create_power_up(
+                    object_manager,
+                    physics_world,
+                    *kind,
+                    Vector2::new(*x, *y),
+                    texture.clone().unwrap_or_else(|| kind.default_texture().to_string()),
+                );
+            }
+            LevelObject::Trigger { name, x, y, width, height, mode, repeatable, actions } => {
+                let mut trigger = Trigger::new(Rect::new(*x, *y, *width, *height))
+                    .with_mode(*mode)
+                    .with_repeatable(*repeatable);
+                if let Some(name) = name {
+                    trigger.name = name.clone();
+                }
+                trigger.actions = actions.clone();
+                game_world.add_trigger(trigger);
+            }
+            LevelObject::Water { x, y, width, height } => {
+                game_world.add_water(Rect::new(*x, *y, *width, *height));
+            }
+            LevelObject::Climbable { x, y, width, height } => {
+                game_world.add_climbable(Rect::new(*x, *y, *width, *height));
+            }
+            LevelObject::Checkpoint { x, y, texture } => {
+                let (object_manager, physics_world) = game_world.managers_mut();
+                checkpoint::create_checkpoint(
+                    object_manager,
+                    physics_world,
+                    Vector2::new(*x, *y),
+                    texture.clone().unwrap_or_else(|| checkpoint::CHECKPOINT_TEXTURE.to_string()),
+                );
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::level::CollectibleKind;
+    use std::collections::HashMap;
+
+    #[test]
+    fn builds_a_world_from_level_data() {
+        let mut level = LevelData::test_level();
+        level.tile_layers.push(TileLayerData {
+            name: "main".to_string(),
+            width: 2,
+            height: 1,
+            solid: true,
+            z: 0,
+            tiles: vec![0, 7],
+            tile_textures: HashMap::new(),
+        });
+        level.objects.push(LevelObject::Collectible { kind: CollectibleKind::Coin, x: 10.0, y: 20.0, value: None, texture: None });
+        let world = LevelLoader::build_world(&level).unwrap();
+
+        assert_eq!(world.world_bounds(), level.world_bounds);
+        assert_eq!(world.spawn_point(), level.spawn.position());
+        assert_eq!(world.get_player().unwrap().position(), level.spawn.position());
+        // Three platforms, the coin and Tux; tiles don't get bodies
+        assert_eq!(world.physics_world().body_count(), 5);
+        assert_eq!(world.object_manager().find_objects_by_tag("collectible").len(), 1);
+        assert!(world.physics_world().is_solid_at(Vector2::new(48.0, 16.0)));
+        assert!(!world.physics_world().is_solid_at(Vector2::new(16.0, 16.0)));
+        assert_eq!(world.tilemap().unwrap().tileset().get(7).unwrap().texture, "tile_7");
+    }
+}
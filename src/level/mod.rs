@@ -0,0 +1,543 @@
+//! Level data format for RustUX
+//!
+//! Levels are stored as JSON files under `levels/<name>.json` in the data
+//! directory and describe everything needed to populate a `GameWorld`.
+
+use crate::math::{Rect, Vector2};
+use crate::physics::{self, PathMode};
+use crate::supertux::PowerUpKind;
+use crate::trigger::{ActivationMode, TriggerAction};
+use crate::util::{Error, Result};
+use crate::video::{BackgroundFill, BackgroundLayer};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::Path;
+
+pub mod loader;
+pub mod sexp;
+pub mod stl;
+
+pub use loader::LevelLoader;
+pub use stl::{StlImport, StlImporter, UnknownObject};
+
+/// Current version of the level file format
+pub const LEVEL_FORMAT_VERSION: u32 = 1;
+
+/// Complete description of a level
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LevelData {
+    /// General information about the level
+    pub metadata: LevelMetadata,
+    /// Where the player starts
+    pub spawn: SpawnPoint,
+    /// Playable area of the level
+    pub world_bounds: Rect,
+    /// Texture names used by the level mapped to their file paths
+    #[serde(default)]
+    pub textures: HashMap<String, String>,
+    /// Name of the tileset in `tilesets/` used by the tile layers
+    #[serde(default)]
+    pub tileset: Option<String>,
+    /// Tile layers, drawn in ascending `z` order
+    #[serde(default)]
+    pub tile_layers: Vec<TileLayerData>,
+    /// Objects placed in the level
+    #[serde(default)]
+    pub objects: Vec<LevelObject>,
+    /// Background layers, drawn behind everything else in ascending `z` order
+    #[serde(default)]
+    pub backgrounds: Vec<BackgroundData>,
+}
+
+/// Level metadata
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LevelMetadata {
+    /// Display name of the level
+    pub name: String,
+    /// Level author
+    #[serde(default)]
+    pub author: String,
+    /// Background music to play
+    #[serde(default)]
+    pub music: Option<String>,
+    /// Time limit in seconds (no limit if absent)
+    #[serde(default)]
+    pub time_limit: Option<f32>,
+    /// Level file format version
+    #[serde(default = "default_format_version")]
+    pub format_version: u32,
+}
+
+fn default_format_version() -> u32 {
+    LEVEL_FORMAT_VERSION
+}
+
+/// Player spawn point
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct SpawnPoint {
+    pub x: f32,
+    pub y: f32,
+}
+
+impl SpawnPoint {
+    /// Get the spawn point as a vector
+    pub fn position(&self) -> Vector2 {
+        Vector2::new(self.x, self.y)
+    }
+}
+
+/// A point on a platform path
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Waypoint {
+    pub x: f32,
+    pub y: f32,
+}
+
+/// Path followed by a moving platform
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PlatformPath {
+    pub mode: PathMode,
+    /// Speed along the path (pixels/second)
+    pub speed: f32,
+    /// Waypoints in world coordinates; the platform starts at the first one
+    pub waypoints: Vec<Waypoint>,
+}
+
+impl PlatformPath {
+    /// Convert to a physics path
+    pub fn to_path(&self) -> physics::Path {
+        let waypoints = self.waypoints.iter().map(|point| Vector2::new(point.x, point.y)).collect();
+        physics::Path::new(waypoints, self.mode, self.speed)
+    }
+}
+
+/// A background layer
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BackgroundData {
+    /// Image (a texture name from `textures`) or gradient to draw
+    #[serde(flatten)]
+    pub fill: BackgroundFill,
+    /// How much the layer moves with the camera horizontally (0 = fixed, 1 = with the world)
+    #[serde(default = "default_parallax")]
+    pub parallax_x: f32,
+    /// How much the layer moves with the camera vertically
+    #[serde(default = "default_parallax")]
+    pub parallax_y: f32,
+    /// Position of the image when the camera is at the top-left of the level
+    #[serde(default)]
+    pub offset_x: f32,
+    #[serde(default)]
+    pub offset_y: f32,
+    /// Automatic scrolling (pixels/second)
+    #[serde(default)]
+    pub scroll_x: f32,
+    #[serde(default)]
+    pub scroll_y: f32,
+    /// Rendering order among backgrounds (higher = rendered on top)
+    #[serde(default)]
+    pub z: i32,
+}
+
+fn default_parallax() -> f32 {
+    0.5
+}
+
+impl BackgroundData {
+    /// Convert to a background layer
+    pub fn to_layer(&self) -> BackgroundLayer {
+        BackgroundLayer::new(self.fill.clone())
+            .with_parallax(Vector2::new(self.parallax_x, self.parallax_y))
+            .with_offset(Vector2::new(self.offset_x, self.offset_y))
+            .with_scroll_speed(Vector2::new(self.scroll_x, self.scroll_y))
+            .with_z(self.z)
+    }
+}
+
+/// A grid of tiles stored row by row
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TileLayerData {
+    /// Layer name
+    pub name: String,
+    /// Width in tiles
+    pub width: u32,
+    /// Height in tiles
+    pub height: u32,
+    /// Whether tiles in this layer block movement
+    #[serde(default)]
+    pub solid: bool,
+    /// Rendering layer (higher = rendered on top)
+    #[serde(default)]
+    pub z: i32,
+    /// Tile ids, `width * height` entries, 0 = empty
+    pub tiles: Vec<u32>,
+    /// Texture name for each tile id, used when the level has no tileset
+    #[serde(default)]
+    pub tile_textures: HashMap<u32, String>,
+}
+
+impl TileLayerData {
+    /// Get the tile id at the given tile coordinates
+    pub fn tile_at(&self, x: u32, y: u32) -> u32 {
+        if x >= self.width || y >= self.height {
+            return 0;
+        }
+        self.tiles.get(y as usize * self.width as usize + x as usize).copied().unwrap_or(0)
+    }
+}
+
+/// Badguy types that can be placed in a level
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BadguyKind {
+    Goomba,
+    Spiky,
+    Flying,
+}
+
+impl BadguyKind {
+    /// Texture used when the level doesn't specify one
+    pub fn default_texture(&self) -> &'static str {
+        match self {
+            BadguyKind::Goomba => "goomba",
+            BadguyKind::Spiky => "spiky",
+            BadguyKind::Flying => "flying",
+        }
+    }
+}
+
+/// Collectible types that can be placed in a level
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CollectibleKind {
+    Coin,
+}
+
+impl CollectibleKind {
+    /// Texture used when the level doesn't specify one
+    pub fn default_texture(&self) -> &'static str {
+        match self {
+            CollectibleKind::Coin => "coin",
+        }
+    }
+
+    /// Value used when the level doesn't specify one
+    pub fn default_value(&self) -> i32 {
+        match self {
+            CollectibleKind::Coin => 1,
+        }
+    }
+}
+
+/// An object placed in a level
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum LevelObject {
+    /// Static rectangle, solid or one-way
+    Platform {
+        #[serde(default)]
+        name: Option<String>,
+        x: f32,
+        y: f32,
+        width: f32,
+        height: f32,
+        texture: String,
+        /// Can be jumped through from below
+        #[serde(default)]
+        one_way: bool,
+        /// Slippery surface
+        #[serde(default)]
+        ice: bool,
+        /// Makes the platform move, carrying whoever stands on it
+        #[serde(default)]
+        path: Option<PlatformPath>,
+    },
+    /// Enemy
+    Badguy {
+        kind: BadguyKind,
+        x: f32,
+        y: f32,
+        #[serde(default)]
+        texture: Option<String>,
+    },
+    /// Item the player can pick up
+    Collectible {
+        kind: CollectibleKind,
+        x: f32,
+        y: f32,
+        #[serde(default)]
+        value: Option<i32>,
+        #[serde(default)]
+        texture: Option<String>,
+    },
+    /// Item that changes Tux's form
+    PowerUp {
+        kind: PowerUpKind,
+        x: f32,
+        y: f32,
+        #[serde(default)]
+        texture: Option<String>,
+    },
+    /// Invisible trigger area
+    Trigger {
+        #[serde(default)]
+        name: Option<String>,
+        x: f32,
+        y: f32,
+        width: f32,
+        height: f32,
+        /// When the trigger fires
+        #[serde(default)]
+        mode: ActivationMode,
+        /// Fire every time instead of only once
+        #[serde(default)]
+        repeatable: bool,
+        /// What happens when the trigger fires
+        #[serde(default)]
+        actions: Vec<TriggerAction>,
+    },
+    /// Area filled with water the player can swim in
+    Water {
+        x: f32,
+        y: f32,
+        width: f32,
+        height: f32,
+    },
+    /// Area the player can climb in, such as a vine or ladder
+    Climbable {
+        x: f32,
+        y: f32,
+        width: f32,
+        height: f32,
+    },
+    /// Bell that moves the player's respawn point when rung
+    Checkpoint {
+        x: f32,
+        y: f32,
+        #[serde(default)]
+        texture: Option<String>,
+    },
+}
+
+impl LevelData {
+    /// Parse level data from a JSON string
+    pub fn from_json(json: &str) -> Result<Self> {
+        let level: LevelData = serde_json::from_str(json)
+            .map_err(|e| Error::LevelLoading(format!("Invalid level JSON: {}", e)))?;
+        level.validate()?;
+        Ok(level)
+    }
+
+    /// Load level data from a JSON file
+    pub fn load_from_file<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let content = std::fs::read_to_string(path)?;
+        Self::from_json(&content)
+    }
+
+    /// Save level data to a JSON file
+    pub fn save_to_file<P: AsRef<Path>>(&self, path: P) -> Result<()> {
+        let json = serde_json::to_string_pretty(self)?;
+        std::fs::write(path, json)?;
+        Ok(())
+    }
+
+    /// Check the level for inconsistencies
+    pub fn validate(&self) -> Result<()> {
+        if self.metadata.format_version > LEVEL_FORMAT_VERSION {
+            return Err(Error::LevelLoading(format!(
+                "Level '{}' uses format version {}, but only {} is supported",
+                self.metadata.name, self.metadata.format_version, LEVEL_FORMAT_VERSION
+            )));
+        }
+
+        if self.world_bounds.width <= 0.0 || self.world_bounds.height <= 0.0 {
+            return Err(Error::LevelLoading(format!(
+                "Level '{}' has empty world bounds",
+                self.metadata.name
+            )));
+        }
+
+        for layer in &self.tile_layers {
+            let expected = layer.width as u64 * layer.height as u64;
+            if layer.tiles.len() as u64 != expected {
+                return Err(Error::LevelLoading(format!(
+                    "Tile layer '{}' has {} tiles, expected {} ({}x{})",
+                    layer.name,
+                    layer.tiles.len(),
+                    expected,
+                    layer.width,
+                    layer.height
+                )));
+            }
+        }
+
+        for object in &self.objects {
+            if let LevelObject::Platform { path: Some(path), name, .. } = object {
+                if path.waypoints.len() < 2 || path.speed <= 0.0 {
+                    return Err(Error::LevelLoading(format!(
+                        "Platform '{}' needs at least two waypoints and a positive speed",
+                        name.as_deref().unwrap_or("Platform")
+                    )));
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Built-in test level used when no level file is available
+    pub fn test_level() -> Self {
+        let mut textures = HashMap::new();
+        textures.insert("tux".to_string(), "assets/sprites/creatures/tux/small/idle-0.png".to_string());
+        textures.insert("platform".to_string(), "assets/sprites/tiles/blocks/brick0.png".to_string());
+        textures.insert("ground".to_string(), "assets/sprites/tiles/blocks/bigblock.png".to_string());
+
+        let platform = |name: &str, x: f32, y: f32, width: f32, height: f32, texture: &str| LevelObject::Platform {
+            name: Some(name.to_string()),
+            x,
+            y,
+            width,
+            height,
+            texture: texture.to_string(),
+            one_way: false,
+            ice: false,
+            path: None,
+        };
+
+        Self {
+            metadata: LevelMetadata {
+                name: "Test Level".to_string(),
+                author: "RustUX Team".to_string(),
+                music: None,
+                time_limit: None,
+                format_version: LEVEL_FORMAT_VERSION,
+            },
+            spawn: SpawnPoint { x: 250.0, y: 368.0 }, // On Platform1 surface (400-32=368)
+            world_bounds: Rect::new(0.0, 0.0, 2048.0, 768.0),
+            textures,
+            tileset: None,
+            tile_layers: Vec::new(),
+            backgrounds: Vec::new(),
+            objects: vec![
+                platform("Ground", 0.0, 500.0, 800.0, 100.0, "ground"),
+                platform("Platform1", 200.0, 400.0, 100.0, 20.0, "platform"),
+                platform("Platform2", 400.0, 350.0, 100.0, 20.0, "platform"),
+            ],
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn levels_round_trip_through_json() {
+        let mut level = LevelData::test_level();
+        level.tile_layers.push(TileLayerData {
+            name: "main".to_string(),
+            width: 2,
+            height: 2,
+            solid: true,
+            z: 0,
+            tiles: vec![0, 1, 1, 0],
+            tile_textures: HashMap::new(),
+        });
+        level.objects.push(LevelObject::Collectible {
+            kind: CollectibleKind::Coin,
+            x: 10.0,
+            y: 20.0,
+            value: Some(5),
+            texture: None,
+        });
+
+        let json = serde_json::to_string_pretty(&level).unwrap();
+        let loaded = LevelData::from_json(&json).unwrap();
+        assert_eq!(serde_json::to_value(&loaded).unwrap(), serde_json::to_value(&level).unwrap());
+        assert_eq!(loaded.tile_layers[0].tile_at(0, 1), 1);
+        assert_eq!(loaded.tile_layers[0].tile_at(5, 0), 0);
+    }
+
+    #[test]
+    fn tile_lookups_past_32_bits_do_not_overflow() {
+        let layer = TileLayerData {
+            name: "wide".to_string(),
+            width: 100_000,
+            height: 100_000,
+            solid: false,
+            z: 0,
+            tiles: vec![1; 4],
+            tile_textures: HashMap::new(),
+        };
+        assert_eq!(layer.tile_at(1, 0), 1);
+        assert_eq!(layer.tile_at(99_999, 99_999), 0);
+    }
+
+    #[test]
+    fn missing_fields_take_their_defaults() {
+        let json = r#"{
+            "metadata": { "name": "Tiny" },
+            "spawn": { "x": 1.0, "y": 2.0 },
+            "world_bounds": { "x": 0.0, "y": 0.0, "width": 320.0, "height": 240.0 },
+            "objects": [{ "type": "platform", "x": 0.0, "y": 200.0, "width": 320.0, "height": 40.0, "texture": "ground" }]
+        }"#;
+        let level = LevelData::from_json(json).unwrap();
+        assert_eq!(level.metadata.format_version, LEVEL_FORMAT_VERSION);
+        assert_eq!(level.spawn.position(), Vector2::new(1.0, 2.0));
+        assert!(level.tile_layers.is_empty() && level.backgrounds.is_empty());
+        assert!(matches!(level.objects[0], LevelObject::Platform { one_way: false, ice: false, path: None, .. }));
+    }
+
+    #[test]
+    fn validate_rejects_inconsistent_levels() {
+        assert!(LevelData::test_level().validate().is_ok());
+
+        let mut level = LevelData::test_level();
+        level.metadata.format_version = LEVEL_FORMAT_VERSION + 1;
+        assert!(level.validate().is_err());
+
+        let mut level = LevelData::test_level();
+        level.world_bounds.width = 0.0;
+        assert!(level.validate().is_err());
+
+        let mut level = LevelData::test_level();
+        level.tile_layers.push(TileLayerData {
+            name: "short".to_string(),
+            width: 3,
+            height: 2,
+            solid: false,
+            z: 0,
+            tiles: vec![1; 5],
+            tile_textures: HashMap::new(),
+        });
+        assert!(level.validate().is_err());
+
+        // 65536 * 65536 tiles would wrap around to none in 32 bits
+        let mut level = LevelData::test_level();
+        level.tile_layers.push(TileLayerData {
+            name: "huge".to_string(),
+            width: 65536,
+            height: 65536,
+            solid: false,
+            z: 0,
+            tiles: Vec::new(),
+            tile_textures: HashMap::new(),
+        });
+        assert!(level.validate().is_err());
+
+        let mut level = LevelData::test_level();
+        level.objects.push(LevelObject::Platform {
+            name: None,
+            x: 0.0,
+            y: 0.0,
+            width: 32.0,
+            height: 8.0,
+            texture: "platform".to_string(),
+            one_way: false,
+            ice: false,
+            path: Some(PlatformPath { mode: PathMode::Loop, speed: 50.0, waypoints: vec![Waypoint { x: 0.0, y: 0.0 }] }),
+        });
+        assert!(level.validate().is_err());
+
+        assert!(LevelData::from_json("{ \"metadata\": {} }").is_err());
+    }
+}
@@ -0,0 +1,330 @@
+//! S-expression parser for SuperTux data files
+//!
+//! SuperTux stores levels, worldmaps and sprite files as Lisp-style
+//! S-expressions, e.g. `(spawnpoint (name "main") (x 96) (y 320))`.
+
+use crate::util::{Error, Result};
+
+/// Deepest list nesting accepted; SuperTux files stay far below it
+const MAX_DEPTH: usize = 64;
+
+/// A parsed S-expression value
+#[derive(Debug, Clone, PartialEq)]
+pub enum Sexp {
+    Symbol(String),
+    String(String),
+    Integer(i64),
+    Real(f64),
+    Boolean(bool),
+    List(Vec<Sexp>),
+}
+
+impl Sexp {
+    /// Parse a single top-level expression
+    pub fn parse(source: &str) -> Result<Sexp> {
+        let mut parser = Parser::new(source);
+        let value = parser.parse_value(0)?;
+        parser.skip_whitespace();
+        if parser.peek().is_some() {
+            return Err(parser.error("Unexpected data after top-level expression"));
+        }
+        Ok(value)
+    }
+
+    /// Get the symbol name, if this is a symbol
+    pub fn as_symbol(&self) -> Option<&str> {
+        match self {
+            Sexp::Symbol(s) => Some(s),
+            _ => None,
+        }
+    }
+
+    /// Get the string value, if this is a string or a translated `(_ "...")` string
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            Sexp::String(s) => Some(s),
+            Sexp::List(items) if items.len() == 2 && items[0].as_symbol() == Some("_") => items[1].as_str(),
+            _ => None,
+        }
+    }
+
+    /// Get the value as a float (integers are converted)
+    pub fn as_f32(&self) -> Option<f32> {
+        match self {
+            Sexp::Integer(i) => Some(*i as f32),
+            Sexp::Real(r) => Some(*r as f32),
+            _ => None,
+        }
+    }
+
+    /// Get the value as an integer
+    pub fn as_i64(&self) -> Option<i64> {
+        match self {
+            Sexp::Integer(i) => Some(*i),
+            _ => None,
+        }
+    }
+
+    /// Get the value as a boolean
+    pub fn as_bool(&self) -> Option<bool> {
+        match self {
+            Sexp::Boolean(b) => Some(*b),
+            _ => None,
+        }
+    }
+
+    /// Get the list items, if this is a list
+    pub fn as_list(&self) -> Option<&[Sexp]> {
+        match self {
+            Sexp::List(items) => Some(items),
+            _ => None,
+        }
+    }
+
+    /// Get the head symbol of a list such as `(name ...)`
+    pub fn name(&self) -> Option<&str> {
+        self.as_list().and_then(|items| items.first()).and_then(|head| head.as_symbol())
+    }
+
+    /// Get the items after the head symbol of a list
+    pub fn args(&self) -> &[Sexp] {
+        match self.as_list() {
+            Some(items) if !items.is_empty() => &items[1..],
+            _ => &[],
+        }
+    }
+
+    /// Find the first child list with the given head symbol
+    pub fn get(&self, key: &str) -> Option<&Sexp> {
+        self.args().iter().find(|child| child.name() == Some(key))
+    }
+
+    /// Iterate over all child lists with the given head symbol
+    pub fn get_all<'a>(&'a self, key: &'a str) -> impl Iterator<Item = &'a Sexp> + 'a {
+        self.args().iter().filter(move |child| child.name() == Some(key))
+    }
+
+    /// Get the first value of a `(key value)` child
+    pub fn get_value(&self, key: &str) -> Option<&Sexp> {
+        self.get(key).and_then(|child| child.args().first())
+    }
+
+    /// Get a `(key "string")` property
+    pub fn get_str(&self, key: &str) -> Option<&str> {
+        self.get_value(key).and_then(|value| value.as_str())
+    }
+
+    /// Get a `(key number)` property
+    pub fn get_f32(&self, key: &str) -> Option<f32> {
+        self.get_value(key).and_then(|value| value.as_f32())
+    }
+
+    /// Get a `(key #t)` property
+    pub fn get_bool(&self, key: &str) -> Option<bool> {
+        self.get_value(key).and_then(|value| value.as_bool())
+    }
+}
+
+/// Recursive descent parser over the source text
+struct Parser<'a> {
+    chars: std::iter::Peekable<std::str::Chars<'a>>,
+    line: usize,
+}
+
+impl<'a> Parser<'a> {
+    fn new(source: &'a str) -> Self {
+        Self {
+            chars: source.chars().peekable(),
+            line: 1,
+        }
+    }
+
+    fn error(&self, message: &str) -> Error {
+        Error::LevelLoading(format!("S-expression parse error on line {}: {}", self.line, message))
+    }
+
+    fn peek(&mut self) -> Option<char> {
+        self.chars.peek().copied()
+    }
+
+    fn next(&mut self) -> Option<char> {
+        let c = self.chars.next();
+        if c == Some('\n') {
+            self.line += 1;
+        }
+        c
+    }
+
+    /// Skip whitespace and `;` comments
+    fn skip_whitespace(&mut self) {
+        while let Some(c) = self.peek() {
+            if c == ';' {
+                while let Some(c) = self.next() {
+                    if c == '\n' {
+                        break;
+                    }
+                }
+            } else if c.is_whitespace() {
+                self.next();
+            } else {
+                break;
+            }
+        }
+    }
+
+    /// Parse a value inside `depth` enclosing lists
+    fn parse_value(&mut self, depth: usize) -> Result<Sexp> {
+        self.skip_whitespace();
+        match self.peek() {
+            None => Err(self.error("Unexpected end of input")),
+            Some('(') => {
+                if depth >= MAX_DEPTH {
+                    return Err(self.error("List nested too deeply"));
+                }
+                self.next();
+                let mut items = Vec::new();
+                loop {
+                    self.skip_whitespace();
+                    match self.peek() {
+                        None => return Err(self.error("Unclosed list")),
+                        Some(')') => {
+                            self.next();
+                            return Ok(Sexp::List(items));
+                        }
+                        Some(_) => items.push(self.parse_value(depth + 1)?),
+                    }
+                }
+            }
+            Some(')') => Err(self.error("Unexpected ')'")),
+            Some('"') => self.parse_string(),
+            Some(_) => self.parse_atom(),
+        }
+    }
+
+    fn parse_string(&mut self) -> Result<Sexp> {
+        self.next(); // Opening quote
+        let mut value = String::new();
+        loop {
+            match self.next() {
+                None => return Err(self.error("Unterminated string")),
+                Some('"') => return Ok(Sexp::String(value)),
+                Some('\\') => match self.next() {
+                    Some('n') => value.push('\n'),
+                    Some('t') => value.push('\t'),
+                    Some(c) => value.push(c),
+                    None => return Err(self.error("Unterminated string escape")),
+                },
+                Some(c) => value.push(c),
+            }
+        }
+    }
+
+    fn parse_atom(&mut self) -> Result<Sexp> {
+        let mut token = String::new();
+        while let Some(c) = self.peek() {
+            if c.is_whitespace() || c == '(' || c == ')' || c == '"' || c == ';' {
+                break;
+            }
+            token.push(c);
+            self.next();
+        }
+
+        Ok(match token.as_str() {
+            "#t" => Sexp::Boolean(true),
+            "#f" => Sexp::Boolean(false),
+            _ if !looks_numeric(&token) => Sexp::Symbol(token),
+            _ => {
+                if let Ok(i) = token.parse::<i64>() {
+                    Sexp::Integer(i)
+                } else if let Ok(r) = token.parse::<f64>() {
+                    Sexp::Real(r)
+                } else {
+                    Sexp::Symbol(token)
+                }
+            }
+        })
+    }
+}
+
+/// Check if a token starts like a number, so symbols such as `inf` and
+/// `nan` aren't read as floats
+fn looks_numeric(token: &str) -> bool {
+    let unsigned = token.strip_prefix(['-', '+']).unwrap_or(token);
+    let digits = unsigned.strip_prefix('.').unwrap_or(unsigned);
+    digits.starts_with(|c: char| c.is_ascii_digit())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_strings_with_escapes() {
+        let value = Sexp::parse(r#"(sign (text "a \"quoted\"\nline\t\\ end"))"#).unwrap();
+        assert_eq!(value.get_str("text"), Some("a \"quoted\"\nline\t\\ end"));
+        assert_eq!(Sexp::parse(r#"(_ "translated")"#).unwrap().as_str(), Some("translated"));
+        assert!(Sexp::parse(r#"(text "never closed)"#).is_err());
+    }
+
+    #[test]
+    fn skips_comments() {
+        let value = Sexp::parse("; leading comment\n(level ; trailing comment\n  (x 1)) ; after").unwrap();
+        assert_eq!(value.name(), Some("level"));
+        assert_eq!(value.args().len(), 1);
+        assert_eq!(value.get_f32("x"), Some(1.0));
+    }
+
+    #[test]
+    fn parses_atoms() {
+        let value = Sexp::parse("(atoms #t #f 42 -7 1.5 -0.25 tux)").unwrap();
+        assert_eq!(
+            value.args(),
+            &[
+                Sexp::Boolean(true),
+                Sexp::Boolean(false),
+                Sexp::Integer(42),
+                Sexp::Integer(-7),
+                Sexp::Real(1.5),
+                Sexp::Real(-0.25),
+                Sexp::Symbol("tux".to_string()),
+            ]
+        );
+        assert_eq!(Sexp::parse("(tilemap (solid #t))").unwrap().get_bool("solid"), Some(true));
+        assert_eq!(Sexp::parse("(coin (y 2.5))").unwrap().get_f32("y"), Some(2.5));
+    }
+
+    #[test]
+    fn number_like_words_stay_symbols() {
+        let value = Sexp::parse("(atoms inf nan infinity NaN -inf .5 +3 -.25)").unwrap();
+        assert_eq!(
+            value.args(),
+            &[
+                Sexp::Symbol("inf".to_string()),
+                Sexp::Symbol("nan".to_string()),
+                Sexp::Symbol("infinity".to_string()),
+                Sexp::Symbol("NaN".to_string()),
+                Sexp::Symbol("-inf".to_string()),
+                Sexp::Real(0.5),
+                Sexp::Integer(3),
+                Sexp::Real(-0.25),
+            ]
+        );
+    }
+
+    #[test]
+    fn rejects_malformed_input() {
+        assert!(Sexp::parse("(level (x 1)").is_err());
+        assert!(Sexp::parse(")").is_err());
+        assert!(Sexp::parse("(level) )").is_err());
+        assert!(Sexp::parse("").is_err());
+    }
+
+    #[test]
+    fn rejects_deeply_nested_lists() {
+        let nested = |depth: usize| format!("{}{}", "(".repeat(depth), ")".repeat(depth));
+        assert!(Sexp::parse(&nested(MAX_DEPTH)).is_ok());
+        assert!(Sexp::parse(&nested(MAX_DEPTH + 1)).is_err());
+        // Far too deep to parse recursively
+        assert!(Sexp::parse(&"(".repeat(500_000)).is_err());
+    }
+}
@@ -0,0 +1,383 @@
+//! Importer for original SuperTux `.stl` level files
+//!
+//! Translates a sector of a SuperTux level into `LevelData`, which can then
+//! be turned into a `GameWorld` by the `LevelLoader`. Objects without a
+//! RustUX counterpart are collected as `UnknownObject`s.
+
+use crate::level::sexp::Sexp;
+use crate::level::{
+    BadguyKind, CollectibleKind, LevelData, LevelLoader, LevelMetadata, LevelObject, SpawnPoint, TileLayerData,
+    LEVEL_FORMAT_VERSION,
+};
+use crate::supertux::{GameWorld, PowerUpKind};
+use crate::trigger::{ActivationMode, TriggerAction};
+use crate::math::Rect;
+use crate::config::TILE_SIZE;
+use crate::util::{Error, Result};
+use std::collections::{BTreeMap, HashMap};
+use std::path::Path;
+
+/// Sector entries that are properties rather than objects
+const SECTOR_PROPERTIES: &[&str] = &["name", "music", "gravity", "ambient-light", "init-script"];
+
+/// Largest tilemap accepted, in tiles
+const MAX_TILEMAP_TILES: usize = 1 << 24;
+
+/// An object in the source level that could not be translated
+#[derive(Debug, Clone, PartialEq)]
+pub struct UnknownObject {
+    /// Sector the object was found in
+    pub sector: String,
+    /// SuperTux object name, e.g. `mriceblock`
+    pub kind: String,
+    /// Position of the object, if it had one
+    pub x: Option<f32>,
+    pub y: Option<f32>,
+}
+
+/// Result of importing a SuperTux level
+#[derive(Debug, Clone)]
+pub struct StlImport {
+    /// The translated level
+    pub level: LevelData,
+    /// Objects that were skipped because RustUX doesn't support them yet
+    pub unknown_objects: Vec<UnknownObject>,
+}
+
+impl StlImport {
+    /// Count skipped objects by kind
+    pub fn unknown_object_counts(&self) -> BTreeMap<String, usize> {
+        let mut counts = BTreeMap::new();
+        for object in &self.unknown_objects {
+            *counts.entry(object.kind.clone()).or_insert(0) += 1;
+        }
+        counts
+    }
+
+    /// Build a game world from the imported level
+    pub fn build_world(&self) -> Result<GameWorld> {
+        LevelLoader::build_world(&self.level)
+    }
+}
+
+/// Importer for SuperTux S-expression levels
+pub struct StlImporter;
+
+impl StlImporter {
+    /// Import the `main` sector (or the first sector) of a level file
+    pub fn import_file<P: AsRef<Path>>(path: P) -> Result<StlImport> {
+        let content = std::fs::read_to_string(path)?;
+        Self::import_str(&content, None)
+    }
+
+    /// Import a sector from level source text
+    ///
+    /// If `sector_name` is `None`, the `main` sector is used, or the first
+    /// sector if there is no `main` sector.
+    pub fn import_str(source: &str, sector_name: Option<&str>) -> Result<StlImport> {
+        let root = Sexp::parse(source)?;
+        if root.name() != Some("supertux-level") {
+            return Err(Error::LevelLoading("Not a SuperTux level: missing (supertux-level ...)".to_string()));
+        }
+
+        let version = root.get_value("version").and_then(|v| v.as_i64()).unwrap_or(1);
+        if version < 2 {
+            return Err(Error::LevelLoading(format!(
+                "SuperTux level format version {} is not supported",
+                version
+            )));
+        }
+
+        let sectors: Vec<&Sexp> = root.get_all("sector").collect();
+        let sector = match sector_name {
+            Some(name) => sectors.iter().find(|s| s.get_str("name") == Some(name)).copied(),
+            None => sectors
+                .iter()
+                .find(|s| s.get_str("name") == Some("main"))
+                .or_else(|| sectors.first())
+                .copied(),
+        }
+        .ok_or_else(|| Error::LevelLoading(format!("Sector {} not found", sector_name.unwrap_or("main"))))?;
+
+        let metadata = LevelMetadata {
+            name: root.get_str("name").unwrap_or("Untitled").to_string(),
+            author: root.get_str("author").unwrap_or_default().to_string(),
+            music: sector.get_str("music").map(|m| m.to_string()),
+            time_limit: root.get_f32("target-time"),
+            format_version: LEVEL_FORMAT_VERSION,
+        };
+
+        Self::translate_sector(sector, metadata)
+    }
+
+    /// Translate one sector into level data
+    fn translate_sector(sector: &Sexp, metadata: LevelMetadata) -> Result<StlImport> {
+        let sector_name = sector.get_str("name").unwrap_or("main").to_string();
+        let mut tile_layers = Vec::new();
+        let mut objects = Vec::new();
+        let mut unknown_objects = Vec::new();
+        let mut spawn_points: Vec<(String, SpawnPoint)> = Vec::new();
+
+        for entry in sector.args() {
+            let kind = match entry.name() {
+                Some(kind) => kind,
+                None => continue,
+            };
+
+            if SECTOR_PROPERTIES.contains(&kind) {
+                continue;
+            }
+
+            match kind {
+                "tilemap" => tile_layers.push(Self::translate_tilemap(entry, tile_layers.len())?),
+                "spawnpoint" => {
+                    let name = entry.get_str("name").unwrap_or("main").to_string();
+                    let point = SpawnPoint {
+                        x: entry.get_f32("x").unwrap_or(0.0),
+                        y: entry.get_f32("y").unwrap_or(0.0),
+                    };
+                    spawn_points.push((name, point));
+                }
+                _ => match Self::translate_object(kind, entry) {
+                    Some(object) => objects.push(object),
+                    None => unknown_objects.push(UnknownObject {
+                        sector: sector_name.clone(),
+                        kind: kind.to_string(),
+                        x: entry.get_f32("x"),
+                        y: entry.get_f32("y"),
+                    }),
+                },
+            }
+        }
+
+        let spawn = spawn_points
+            .iter()
+            .find(|(name, _)| name == "main")
+            .or_else(|| spawn_points.first())
+            .map(|(_, point)| *point)
+            .unwrap_or_else(|| {
+                log::warn!("Sector '{}' has no spawnpoint, using the top-left corner", sector_name);
+                SpawnPoint { x: TILE_SIZE as f32, y: TILE_SIZE as f32 }
+            });
+
+        let tile_size = TILE_SIZE as f32;
+        let (width, height) = tile_layers.iter().fold((0u32, 0u32), |(w, h), layer| {
+            (w.max(layer.width), h.max(layer.height))
+        });
+        let world_bounds = if width > 0 && height > 0 {
+            Rect::new(0.0, 0.0, width as f32 * tile_size, height as f32 * tile_size)
+        } else {
+            Rect::new(0.0, 0.0, 2048.0, 768.0)
+        };
+
+        if !unknown_objects.is_empty() {
+            log::info!(
+                "Skipped {} unsupported objects while importing sector '{}'",
+                unknown_objects.len(),
+                sector_name
+            );
+        }
+
+        Ok(StlImport {
+            level: LevelData {
+                metadata,
+                spawn,
+                world_bounds,
+                textures: HashMap::new(),
+                tileset: None,
+                tile_layers,
+                objects,
+                backgrounds: Vec::new(),
+            },
+            unknown_objects,
+        })
+    }
+
+    /// Translate a `(tilemap ...)` entry
+    fn translate_tilemap(tilemap: &Sexp, index: usize) -> Result<TileLayerData> {
+        let width = tilemap.get_f32("width").unwrap_or(0.0).max(0.0) as u32;
+        let height = tilemap.get_f32("height").unwrap_or(0.0).max(0.0) as u32;
+        let mut tiles: Vec<u32> = tilemap
+            .get("tiles")
+            .map(|t| t.args().iter().map(|v| v.as_i64().unwrap_or(0).max(0) as u32).collect())
+            .unwrap_or_default();
+
+        let tile_count = (width as usize)
+            .checked_mul(height as usize)
+            .filter(|&count| count <= MAX_TILEMAP_TILES)
+            .ok_or_else(|| Error::LevelLoading(format!("Tilemap of {}x{} tiles is too large", width, height)))?;
+
+        // Be lenient with truncated or padded tile lists
+        tiles.resize(tile_count, 0);
+
+        Ok(TileLayerData {
+            name: tilemap
+                .get_str("name")
+                .map(|n| n.to_string())
+                .unwrap_or_else(|| format!("tilemap{}", index)),
+            width,
+            height,
+            solid: tilemap.get_bool("solid").unwrap_or(false),
+            z: tilemap.get_f32("z-pos").unwrap_or(0.0) as i32,
+            tiles,
+            tile_textures: HashMap::new(),
+        })
+    }
+
+    /// Translate a SuperTux object into a level object, if it is supported
+    fn translate_object(kind: &str, entry: &Sexp) -> Option<LevelObject> {
+        let x = entry.get_f32("x").unwrap_or(0.0);
+        let y = entry.get_f32("y").unwrap_or(0.0);
+
+        let badguy = |kind: BadguyKind| LevelObject::Badguy { kind, x, y, texture: None };
+
+        match kind {
+            "snowball" | "bouncingsnowball" | "captainsnowball" | "mrbomb" => Some(badguy(BadguyKind::Goomba)),
+            "spiky" | "sspiky" | "hellspiky" => Some(badguy(BadguyKind::Spiky)),
+            "flyingsnowball" | "zeekling" => Some(badguy(BadguyKind::Flying)),
+            "coin" => Some(LevelObject::Collectible {
+                kind: CollectibleKind::Coin,
+                x,
+                y,
+                value: None,
+                texture: None,
+            }),
+            "powerup" => entry.get_str("sprite").and_then(PowerUpKind::from_sprite_path).map(|kind| LevelObject::PowerUp {
+                kind,
+                x,
+                y,
+                texture: None,
+            }),
+            "firefly" => Some(LevelObject::Checkpoint { x, y, texture: None }),
+            "climbable" => Some(LevelObject::Climbable {
+                x,
+                y,
+                width: entry.get_f32("width").unwrap_or(TILE_SIZE as f32),
+                height: entry.get_f32("height").unwrap_or(TILE_SIZE as f32),
+            }),
+            "sequencetrigger" | "scripttrigger" | "secretarea" => Some(LevelObject::Trigger {
+                name: entry.get_str("name").map(|n| n.to_string()).or_else(|| Some(kind.to_string())),
+                x,
+                y,
+                width: entry.get_f32("width").unwrap_or(TILE_SIZE as f32),
+                height: entry.get_f32("height").unwrap_or(TILE_SIZE as f32),
+                mode: ActivationMode::Enter,
+                repeatable: false,
+                actions: Self::translate_trigger_actions(kind, entry),
+            }),
+            _ => None,
+        }
+    }
+
+    /// Translate what a SuperTux trigger does; scripts are not supported
+    fn translate_trigger_actions(kind: &str, entry: &Sexp) -> Vec<TriggerAction> {
+        match kind {
+            "sequencetrigger" => match entry.get_str("sequence") {
+                Some("endsequence") | Some("stoptux") => vec![TriggerAction::EndSequence],
+                _ => Vec::new(),
+            },
+            "secretarea" => vec![TriggerAction::Message {
+                text: entry.get_str("message").unwrap_or("You found a secret area!").to_string(),
+                duration: 3.0,
+            }],
+            _ => Vec::new(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const LEVEL: &str = r#"
+        (supertux-level
+          (version 3)
+          (name (_ "Icy Start"))
+          (author "Someone")
+          (sector
+            (name "secret")
+            (spawnpoint (name "main") (x 10) (y 20)))
+          (sector
+            (name "main")
+            (music "music/chipdisko.ogg")
+            (tilemap (name "interactive") (solid #t) (z-pos 0) (width 3) (height 2)
+              (tiles 1 2 3 4 5))
+            (spawnpoint (name "other") (x 1) (y 2))
+            (spawnpoint (name "main") (x 64) (y 128))
+            (snowball (x 100) (y 200))
+            (mriceblock (x 300) (y 400))
+            (mriceblock (x 500) (y 400))
+            (coin (x 32) (y 32))))
+    "#;
+
+    #[test]
+    fn imports_the_main_sector_and_spawnpoint() {
+        let import = StlImporter::import_str(LEVEL, None).unwrap();
+        assert_eq!(import.level.metadata.name, "Icy Start");
+        assert_eq!(import.level.metadata.music.as_deref(), Some("music/chipdisko.ogg"));
+        assert_eq!(import.level.spawn, SpawnPoint { x: 64.0, y: 128.0 });
+        assert_eq!(import.level.objects.len(), 2);
+        assert!(matches!(
+            import.level.objects[0],
+            LevelObject::Badguy { kind: BadguyKind::Goomba, x, y, .. } if x == 100.0 && y == 200.0
+        ));
+
+        let secret = StlImporter::import_str(LEVEL, Some("secret")).unwrap();
+        assert_eq!(secret.level.spawn, SpawnPoint { x: 10.0, y: 20.0 });
+        assert!(StlImporter::import_str(LEVEL, Some("missing")).is_err());
+    }
+
+    #[test]
+    fn falls_back_to_the_first_sector_and_spawnpoint() {
+        let source = r#"(supertux-level (version 2)
+            (sector (name "one") (spawnpoint (name "a") (x 5) (y 6)) (spawnpoint (name "b") (x 7) (y 8)))
+            (sector (name "two")))"#;
+        let import = StlImporter::import_str(source, None).unwrap();
+        assert_eq!(import.level.spawn, SpawnPoint { x: 5.0, y: 6.0 });
+
+        let empty = StlImporter::import_str(source, Some("two")).unwrap();
+        assert_eq!(empty.level.spawn, SpawnPoint { x: TILE_SIZE as f32, y: TILE_SIZE as f32 });
+    }
+
+    #[test]
+    fn translates_tilemaps() {
+        let import = StlImporter::import_str(LEVEL, None).unwrap();
+        let layer = &import.level.tile_layers[0];
+        assert_eq!(layer.name, "interactive");
+        assert_eq!((layer.width, layer.height), (3, 2));
+        assert!(layer.solid);
+        // The truncated tile list is padded with empty tiles
+        assert_eq!(layer.tiles, vec![1, 2, 3, 4, 5, 0]);
+        let tile_size = TILE_SIZE as f32;
+        assert_eq!(import.level.world_bounds, Rect::new(0.0, 0.0, 3.0 * tile_size, 2.0 * tile_size));
+    }
+
+    #[test]
+    fn rejects_oversized_tilemaps() {
+        let source = r#"(supertux-level (version 2)
+            (sector (name "main") (tilemap (width 100000) (height 100000) (tiles))))"#;
+        assert!(StlImporter::import_str(source, None).is_err());
+    }
+
+    #[test]
+    fn reports_unknown_objects() {
+        let import = StlImporter::import_str(LEVEL, None).unwrap();
+        assert_eq!(import.unknown_objects.len(), 2);
+        assert_eq!(
+            import.unknown_objects[0],
+            UnknownObject {
+                sector: "main".to_string(),
+                kind: "mriceblock".to_string(),
+                x: Some(300.0),
+                y: Some(400.0),
+            }
+        );
+        assert_eq!(import.unknown_object_counts().get("mriceblock"), Some(&2));
+    }
+
+    #[test]
+    fn rejects_other_files_and_old_versions() {
+        assert!(StlImporter::import_str("(supertux-worldmap (version 2))", None).is_err());
+        assert!(StlImporter::import_str("(supertux-level (version 1) (sector))", None).is_err());
+    }
+}
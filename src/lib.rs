@@ -10,11 +10,15 @@ pub mod collision;
 pub mod control;
 pub mod engine;
 pub mod gui;
+pub mod level;
 pub mod math;
 pub mod object;
+pub mod particle;
 pub mod physics;
+pub mod projectile;
 pub mod sprite;
 pub mod supertux;
+pub mod tilemap;
 pub mod trigger;
 pub mod util;
 pub mod video;
@@ -1,6 +1,6 @@
 //! RustUX - A SuperTux remake written in Rust
 
-use rustux::engine::{Engine, GameStateManager, MenuState, PlayingState, StateId};
+use rustux::engine::{Engine, GameOverState, GameStateManager, MenuState, PlayingState, StateId};
 use rustux::util::Result;
 
 fn main() -> Result<()> {
@@ -16,6 +16,7 @@ fn main() -> Result<()> {
     let mut state_manager = GameStateManager::new();
     state_manager.add_state(StateId::Menu, Box::new(MenuState::new()));
     state_manager.add_state(StateId::Playing, Box::new(PlayingState::new()));
+    state_manager.add_state(StateId::GameOver, Box::new(GameOverState::new()));
     
     // Start with the menu state
     log::info!("Setting initial state to Menu");
@@ -2,11 +2,10 @@
 
 use crate::util::Result;
 use crate::math::{Vector2, Rect};
-use crate::sprite::{Sprite, TextureManager, SpriteRenderer};
+use crate::sprite::Sprite;
 use crate::physics::{PhysicsBody, BodyType};
-use crate::collision::CollisionLayer;
-use sdl2::render::Canvas;
-use sdl2::video::Window;
+use crate::collision::{CollisionLayer, CollisionType};
+use crate::video::{Camera, RenderQueue, RenderStats, VideoManager};
 use std::collections::HashMap;
 use std::any::{Any, TypeId};
 
@@ -344,14 +343,14 @@ impl SpriteRenderSystem {
         Self
     }
 
+    /// Draw every visible sprite through a render queue, returning the frame's stats
     pub fn render(
         &self,
         objects: &HashMap<ObjectId, GameObject>,
-        canvas: &mut Canvas<Window>,
-        texture_manager: &TextureManager,
-    ) -> Result<()> {
-        // Collect all visible sprites with their rendering layers
-        let mut sprites_to_render: Vec<(i32, &SpriteComponent, &Transform)> = Vec::new();
+        video: &mut VideoManager,
+        camera: &Camera,
+    ) -> Result<RenderStats> {
+        let mut queue = RenderQueue::new();
 
         for object in objects.values() {
             if !object.active {
@@ -363,25 +362,16 @@ impl SpriteRenderSystem {
                 object.get_component::<Transform>(),
             ) {
                 if sprite_comp.visible {
-                    sprites_to_render.push((sprite_comp.layer, sprite_comp, transform));
+                    let mut sprite = sprite_comp.sprite.clone();
+                    sprite.position = transform.position;
+                    sprite.scale *= transform.scale;
+                    sprite.rotation += transform.rotation as f64;
+                    queue.push_world_sprite(&sprite, sprite_comp.layer, camera);
                 }
             }
         }
 
-        // Sort by layer (lower layers rendered first)
-        sprites_to_render.sort_by_key(|(layer, _, _)| *layer);
-
-        // Render sprites
-        for (_, sprite_comp, transform) in sprites_to_render {
-            let mut sprite = sprite_comp.sprite.clone();
-            sprite.position = transform.position;
-            sprite.scale = transform.scale;
-            sprite.rotation = transform.rotation as f64;
-
-            SpriteRenderer::render_sprite(canvas, texture_manager, &sprite)?;
-        }
-
-        Ok(())
+        queue.flush(video)
     }
 }
 
@@ -501,6 +491,8 @@ impl System for HealthSystem {
 /// Game object manager that handles all game objects and systems
 pub struct GameObjectManager {
     objects: HashMap<ObjectId, GameObject>,
+    /// Owner of each physics body, for looking up what a contact touched
+    body_owners: HashMap<u32, ObjectId>,
     next_id: ObjectId,
     systems: Vec<Box<dyn System>>,
     sprite_render_system: SpriteRenderSystem,
@@ -511,6 +503,7 @@ impl GameObjectManager {
     pub fn new() -> Self {
         let mut manager = Self {
             objects: HashMap::new(),
+            body_owners: HashMap::new(),
             next_id: 1,
             systems: Vec::new(),
             sprite_render_system: SpriteRenderSystem::new(),
@@ -538,13 +531,35 @@ impl GameObjectManager {
     /// Add an existing game object
     pub fn add_object(&mut self, object: GameObject) -> ObjectId {
         let id = object.id;
+        if let Some(physics) = object.get_component::<PhysicsComponent>() {
+            self.body_owners.insert(physics.body_id, id);
+        }
         self.objects.insert(id, object);
         id
     }
 
     /// Remove a game object
     pub fn remove_object(&mut self, id: ObjectId) -> Option<GameObject> {
-        self.objects.remove(&id)
+        let object = self.objects.remove(&id)?;
+        if let Some(physics) = object.get_component::<PhysicsComponent>() {
+            self.body_owners.remove(&physics.body_id);
+        }
+        Some(object)
+    }
+
+    /// Give an object its physics body
+    ///
+    /// Physics components have to be added through the manager so that
+    /// `object_for_body` can find their owner.
+    pub fn add_physics_component(&mut self, id: ObjectId, physics: PhysicsComponent) {
+        let Some(object) = self.objects.get_mut(&id) else {
+            return;
+        };
+        if let Some(old) = object.get_component::<PhysicsComponent>() {
+            self.body_owners.remove(&old.body_id);
+        }
+        self.body_owners.insert(physics.body_id, id);
+        object.add_component(physics);
     }
 
     /// Get a game object by ID
@@ -575,6 +590,11 @@ impl GameObjectManager {
             .collect()
     }
 
+    /// Find the object owning a physics body
+    pub fn object_for_body(&self, body_id: u32) -> Option<ObjectId> {
+        self.body_owners.get(&body_id).copied()
+    }
+
     /// Add a system to the manager
     pub fn add_system(&mut self, system: Box<dyn System>) {
         self.systems.push(system);
@@ -597,13 +617,13 @@ impl GameObjectManager {
         Ok(())
     }
 
-    /// Render all objects
+    /// Render all objects as seen by the camera
     pub fn render(
         &self,
-        canvas: &mut Canvas<Window>,
-        texture_manager: &TextureManager,
-    ) -> Result<()> {
-        self.sprite_render_system.render(&self.objects, canvas, texture_manager)
+        video: &mut VideoManager,
+        camera: &Camera,
+    ) -> Result<RenderStats> {
+        self.sprite_render_system.render(&self.objects, video, camera)
     }
 
     /// Synchronize object positions from physics world
@@ -629,6 +649,7 @@ impl GameObjectManager {
     /// Clear all objects
     pub fn clear(&mut self) {
         self.objects.clear();
+        self.body_owners.clear();
     }
 
     /// Get objects in a rectangular area
@@ -676,17 +697,39 @@ pub mod factory {
         id
     }
 
-    /// Create a collectible item
+    /// Create a sprite object with a static sensor body on the item layer
+    ///
+    /// Nothing is blocked by the item; the player touching it shows up as a contact.
+    pub fn create_sensor_item(
+        manager: &mut GameObjectManager,
+        physics_world: &mut crate::physics::PhysicsWorld,
+        name: String,
+        position: Vector2,
+        size: Vector2,
+        texture_name: String,
+    ) -> ObjectId {
+        let id = create_static_sprite(manager, name, position, texture_name, size);
+
+        let mut physics_body = crate::physics::PhysicsBody::new(0, position, size, BodyType::Static)
+            .with_collision_type(CollisionType::Sensor);
+        physics_body.collision_layer = CollisionLayer::Item;
+        let body_id = physics_world.add_body(physics_body);
+
+        manager.add_physics_component(id, PhysicsComponent::new(body_id, BodyType::Static, CollisionLayer::Item));
+        id
+    }
+
+    /// Create a collectible item with a sensor body the player can touch
     pub fn create_collectible(
         manager: &mut GameObjectManager,
+        physics_world: &mut crate::physics::PhysicsWorld,
         name: String,
         position: Vector2,
         texture_name: String,
         size: Vector2,
         value: i32,
     ) -> ObjectId {
-        let id = create_static_sprite(manager, name, position, texture_name, size);
-        
+        let id = create_sensor_item(manager, physics_world, name, position, size, texture_name);
         if let Some(object) = manager.get_object_mut(id) {
             object.add_component(Collectible::new(value));object.tag = "collectible".to_string();
         }
@@ -707,13 +750,14 @@ pub mod factory {
         let id = manager.create_object(name);
         
         // Create physics body
-        let physics_body = crate::physics::PhysicsBody::new(0, position, size, body_type);
+        let mut physics_body = crate::physics::PhysicsBody::new(0, position, size, body_type);
+        physics_body.collision_layer = collision_layer;
         let body_id = physics_world.add_body(physics_body);
         
         if let Some(object) = manager.get_object_mut(id) {
             object.add_component(Transform::new(position));
-            object.add_component(PhysicsComponent::new(body_id, body_type, collision_layer));
         }
+        manager.add_physics_component(id, PhysicsComponent::new(body_id, body_type, collision_layer));
         
         id
     }
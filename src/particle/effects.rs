@@ -0,0 +1,58 @@
+//! Standard particle effects for RustUX
+
+use super::{Curve, ParticleConfig, ParticleEmitter};
+use crate::math::Vector2;
+use crate::video::{BlendMode, Color};
+use std::rc::Rc;
+
+/// Textures used by the standard effects, as `(name, path)` pairs
+pub const TEXTURES: &[(&str, &str)] = &[
+    ("particle-sparkle", "assets/sprites/particles/sparkle.png"),
+    ("particle-star", "assets/sprites/particles/star.png"),
+    ("particle-smoke", "assets/sprites/particles/smoke.png"),
+];
+
+/// Layer the effects are drawn on, above sprites
+const EFFECT_LAYER: i32 = 10;
+
+/// Golden sparkles flying up from a collected coin
+pub fn coin_sparkle(position: Vector2) -> ParticleEmitter {
+    let config = ParticleConfig::new(Vector2::splat(8.0))
+        .with_frames(&["particle-sparkle"])
+        .with_lifetime(0.3, 0.6)
+        .with_velocity(Vector2::new(-60.0, -140.0), Vector2::new(60.0, -40.0))
+        .with_gravity(Vector2::new(0.0, 300.0))
+        .with_spawn_area(Vector2::splat(16.0))
+        .with_alpha(Curve::linear(1.0, 0.0))
+        .with_scale(Curve::linear(1.0, 0.3))
+        .with_color(Color::rgb(255, 230, 110))
+        .with_blend(BlendMode::Add)
+        .with_layer(EFFECT_LAYER);
+    ParticleEmitter::burst(Rc::new(config), position, 10)
+}
+
+/// Stars bursting out of a stomped badguy
+pub fn stomp_stars(position: Vector2) -> ParticleEmitter {
+    let config = ParticleConfig::new(Vector2::splat(10.0))
+        .with_frames(&["particle-star"])
+        .with_lifetime(0.4, 0.7)
+        .with_velocity(Vector2::new(-120.0, -160.0), Vector2::new(120.0, -60.0))
+        .with_gravity(Vector2::new(0.0, 500.0))
+        .with_alpha(Curve::from_points(vec![(0.0, 1.0), (0.6, 1.0), (1.0, 0.0)]))
+        .with_layer(EFFECT_LAYER);
+    ParticleEmitter::burst(Rc::new(config), position, 6)
+}
+
+/// Dust kicked up at the player's feet when landing
+pub fn landing_dust(position: Vector2) -> ParticleEmitter {
+    let config = ParticleConfig::new(Vector2::splat(12.0))
+        .with_frames(&["particle-smoke"])
+        .with_lifetime(0.25, 0.45)
+        .with_velocity(Vector2::new(-70.0, -25.0), Vector2::new(70.0, -5.0))
+        .with_spawn_area(Vector2::new(24.0, 0.0))
+        .with_alpha(Curve::linear(0.7, 0.0))
+        .with_scale(Curve::linear(0.5, 1.4))
+        .with_color(Color::rgb(220, 210, 190))
+        .with_layer(EFFECT_LAYER);
+    ParticleEmitter::burst(Rc::new(config), position, 6)
+}
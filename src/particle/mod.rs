@@ -0,0 +1,549 @@
+//! Particle system for RustUX
+//!
+//! An emitter spawns short-lived particles from a shared configuration,
+//! either all at once (a burst) or at a steady rate. Every random choice
+//! (lifetime, velocity, spawn offset) comes from the system's RNG, so a
+//! seeded system produces the same particles every run.
+
+use crate::math::{Rect, Vector2};
+use crate::util::Result;
+use crate::video::{BlendMode, Camera, Color, DrawParams, RenderCommand, RenderQueue, RenderStats, VideoManager};
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use std::rc::Rc;
+
+pub mod effects;
+
+/// A value that changes over a particle's life, linear between points
+#[derive(Debug, Clone, PartialEq)]
+pub struct Curve {
+    /// `(t, value)` pairs sorted by `t`, with `t` going from 0 (birth) to 1 (death)
+    points: Vec<(f32, f32)>,
+}
+
+impl Curve {
+    /// A value that never changes
+    pub fn constant(value: f32) -> Self {
+        Self { points: vec![(0.0, value)] }
+    }
+
+    /// A value going from `start` at birth to `end` at death
+    pub fn linear(start: f32, end: f32) -> Self {
+        Self { points: vec![(0.0, start), (1.0, end)] }
+    }
+
+    /// A curve through the given points, in any order
+    pub fn from_points(mut points: Vec<(f32, f32)>) -> Self {
+        points.sort_by(|a, b| a.0.total_cmp(&b.0));
+        if points.is_empty() {
+            points.push((0.0, 1.0));
+        }
+        Self { points }
+    }
+
+    /// Value at `t` (clamped to the first and last point)
+    pub fn sample(&self, t: f32) -> f32 {
+        let index = self.points.partition_point(|&(point_t, _)| point_t <= t);
+        if index == 0 {
+            return self.points[0].1;
+        }
+        let (t0, v0) = self.points[index - 1];
+        match self.points.get(index) {
+            Some(&(t1, v1)) => v0 + (v1 - v0) * (t - t0) / (t1 - t0),
+            None => v0,
+        }
+    }
+}
+
+/// Everything the particles of an emitter have in common
+#[derive(Debug, Clone, PartialEq)]
+pub struct ParticleConfig {
+    /// Textures played in order over a particle's life (no textures = plain squares)
+    pub frames: Vec<String>,
+    /// Size of a particle at scale 1
+    pub size: Vector2,
+    /// Lifetime range in seconds
+    pub lifetime: (f32, f32),
+    /// Lower corner of the initial velocity range
+    pub velocity_min: Vector2,
+    /// Upper corner of the initial velocity range
+    pub velocity_max: Vector2,
+    /// Acceleration applied to every particle
+    pub gravity: Vector2,
+    /// Particles spawn anywhere in a box of this size around the emitter
+    pub spawn_area: Vector2,
+    /// Opacity over the particle's life (0 to 1)
+    pub alpha: Curve,
+    /// Size multiplier over the particle's life
+    pub scale: Curve,
+    pub color: Color,
+    pub blend: BlendMode,
+    /// Rendering layer (higher = drawn on top)
+    pub layer: i32,
+}
+
+impl ParticleConfig {
+    /// Create a config for white, one-second particles that stay put
+    pub fn new(size: Vector2) -> Self {
+        Self {
+            frames: Vec::new(),
+            size,
+            lifetime: (1.0, 1.0),
+            velocity_min: Vector2::ZERO,
+            velocity_max: Vector2::ZERO,
+            gravity: Vector2::ZERO,
+            spawn_area: Vector2::ZERO,
+            alpha: Curve::constant(1.0),
+            scale: Curve::constant(1.0),
+            color: Color::WHITE,
+            blend: BlendMode::Blend,
+            layer: 0,
+        }
+    }
+
+    pub fn with_frames(mut self, frames: &[&str]) -> Self {
+        self.frames = frames.iter().map(|frame| frame.to_string()).collect();
+        self
+    }
+
+    pub fn with_lifetime(mut self, min: f32, max: f32) -> Self {
+        self.lifetime = (min, max);
+        self
+    }
+
+    pub fn with_velocity(mut self, min: Vector2, max: Vector2) -> Self {
+        self.velocity_min = min;
+        self.velocity_max = max;
+        self
+    }
+
+    pub fn with_gravity(mut self, gravity: Vector2) -> Self {
+        self.gravity = gravity;
+        self
+    }
+
+    pub fn with_spawn_area(mut self, spawn_area: Vector2) -> Self {
+        self.spawn_area = spawn_area;
+        self
+    }
+
+    pub fn with_alpha(mut self, alpha: Curve) -> Self {
+        self.alpha = alpha;
+        self
+    }
+
+    pub fn with_scale(mut self, scale: Curve) -> Self {
+        self.scale = scale;
+        self
+    }
+
+    pub fn with_color(mut self, color: Color) -> Self {
+        self.color = color;
+        self
+    }
+
+    pub fn with_blend(mut self, blend: BlendMode) -> Self {
+        self.blend = blend;
+        self
+    }
+
+    pub fn with_layer(mut self, layer: i32) -> Self {
+        self.layer = layer;
+        self
+    }
+
+    /// Create a particle at `origin` with randomized lifetime, offset and velocity
+    fn spawn(&self, origin: Vector2, rng: &mut StdRng) -> Particle {
+        let offset = Vector2::new(
+            random_between(rng, -self.spawn_area.x, self.spawn_area.x) * 0.5,
+            random_between(rng, -self.spawn_area.y, self.spawn_area.y) * 0.5,
+        );
+        Particle {
+            position: origin + offset,
+            velocity: Vector2::new(
+                random_between(rng, self.velocity_min.x, self.velocity_max.x),
+                random_between(rng, self.velocity_min.y, self.velocity_max.y),
+            ),
+            age: 0.0,
+            lifetime: random_between(rng, self.lifetime.0, self.lifetime.1).max(f32::EPSILON),
+        }
+    }
+}
+
+/// Uniform random value in `min..=max`, accepting empty and reversed ranges
+fn random_between(rng: &mut StdRng, min: f32, max: f32) -> f32 {
+    if min == max {
+        return min;
+    }
+    rng.gen_range(min.min(max)..=min.max(max))
+}
+
+/// A single live particle
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Particle {
+    /// Center of the particle in world space
+    pub position: Vector2,
+    pub velocity: Vector2,
+    /// Seconds since the particle spawned
+    pub age: f32,
+    pub lifetime: f32,
+}
+
+impl Particle {
+    /// How far through its life the particle is, from 0 to 1
+    pub fn progress(&self) -> f32 {
+        (self.age / self.lifetime).min(1.0)
+    }
+}
+
+/// How an emitter spawns particles
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum EmitterMode {
+    /// Spawn this many particles on the first update
+    Burst(u32),
+    /// Spawn particles per second, for a duration (`None` = until stopped)
+    Continuous { rate: f32, duration: Option<f32> },
+}
+
+/// Spawns and simulates particles at a position
+#[derive(Debug, Clone)]
+pub struct ParticleEmitter {
+    config: Rc<ParticleConfig>,
+    mode: EmitterMode,
+    pub position: Vector2,
+    particles: Vec<Particle>,
+    /// Seconds the emitter has been running
+    elapsed: f32,
+    /// Fractional particles owed by a continuous emitter
+    pending: f32,
+    emitting: bool,
+}
+
+impl ParticleEmitter {
+    /// Create an emitter
+    pub fn new(config: Rc<ParticleConfig>, mode: EmitterMode, position: Vector2) -> Self {
+        Self {
+            config,
+            mode,
+            position,
+            particles: Vec::new(),
+            elapsed: 0.0,
+            pending: 0.0,
+            emitting: true,
+        }
+    }
+
+    /// Create an emitter spawning `count` particles at once
+    pub fn burst(config: Rc<ParticleConfig>, position: Vector2, count: u32) -> Self {
+        Self::new(config, EmitterMode::Burst(count), position)
+    }
+
+    /// Create an emitter spawning `rate` particles per second until stopped
+    pub fn continuous(config: Rc<ParticleConfig>, position: Vector2, rate: f32) -> Self {
+        Self::new(config, EmitterMode::Continuous { rate, duration: None }, position)
+    }
+
+    /// Stop a continuous emitter after `duration` seconds
+    pub fn with_duration(mut self, duration: f32) -> Self {
+        if let EmitterMode::Continuous { rate, .. } = self.mode {
+            self.mode = EmitterMode::Continuous { rate, duration: Some(duration) };
+        }
+        self
+    }
+
+    pub fn config(&self) -> &ParticleConfig {
+        &self.config
+    }
+
+    pub fn mode(&self) -> EmitterMode {
+        self.mode
+    }
+
+    /// Get the live particles
+    pub fn particles(&self) -> &[Particle] {
+        &self.particles
+    }
+
+    /// Stop spawning; live particles play out
+    pub fn stop(&mut self) {
+        self.emitting = false;
+    }
+
+    /// Check if the emitter stopped spawning and all its particles died
+    pub fn is_finished(&self) -> bool {
+        !self.emitting && self.particles.is_empty()
+    }
+
+    /// Spawn new particles, then age and move the live ones
+    pub fn update(&mut self, delta_time: f32, rng: &mut StdRng) {
+        for particle in &mut self.particles {
+            particle.age += delta_time;
+            particle.velocity += self.config.gravity * delta_time;
+            particle.position += particle.velocity * delta_time;
+        }
+        self.particles.retain(|particle| particle.age < particle.lifetime);
+
+        if self.emitting {
+            self.emit(delta_time, rng);
+        }
+    }
+
+    fn emit(&mut self, delta_time: f32, rng: &mut StdRng) {
+        let count = match self.mode {
+            EmitterMode::Burst(count) => {
+                self.emitting = false;
+                count
+            }
+            EmitterMode::Continuous { rate, duration } => {
+                let mut active_time = delta_time;
+                if let Some(duration) = duration {
+                    active_time = active_time.min(duration - self.elapsed).max(0.0);
+                    if self.elapsed + delta_time >= duration {
+                        self.emitting = false;
+                    }
+                }
+                self.pending += rate * active_time;
+                let count = self.pending.floor();
+                self.pending -= count;
+                count as u32
+            }
+        };
+        self.elapsed += delta_time;
+
+        for _ in 0..count {
+            let particle = self.config.spawn(self.position, rng);
+            self.particles.push(particle);
+        }
+    }
+
+    /// Queue the visible particles for drawing, returning how many were culled
+    fn queue(&self, queue: &mut RenderQueue, camera: &Camera) -> u32 {
+        let mut culled = 0;
+        for particle in &self.particles {
+            let t = particle.progress();
+            let size = self.config.size * self.config.scale.sample(t).max(0.0);
+            let world_rect = Rect::new(particle.position.x - size.x * 0.5, particle.position.y - size.y * 0.5, size.x, size.y);
+            if !camera.is_visible(&world_rect) {
+                culled += 1;
+                continue;
+            }
+
+            let frame = ((t * self.config.frames.len() as f32) as usize).min(self.config.frames.len() - 1);
+            let alpha = (self.config.alpha.sample(t).clamp(0.0, 1.0) * self.config.color.a as f32).round() as u8;
+            queue.push(RenderCommand {
+                layer: self.config.layer,
+                texture_name: self.config.frames[frame].clone(),
+                src: None,
+                dst: camera.world_rect_to_screen(&world_rect),
+                params: DrawParams {
+                    color: self.config.color.with_alpha(alpha),
+                    blend: self.config.blend,
+                    ..DrawParams::default()
+                },
+            });
+        }
+        culled
+    }
+
+    /// Draw the particles as plain squares, for configs without textures
+    fn render_untextured(&self, video: &mut VideoManager, camera: &Camera) -> Result<()> {
+        video.set_blend_mode(self.config.blend);
+        for particle in &self.particles {
+            let t = particle.progress();
+            let size = self.config.size * self.config.scale.sample(t).max(0.0);
+            let world_rect = Rect::new(particle.position.x - size.x * 0.5, particle.position.y - size.y * 0.5, size.x, size.y);
+            if !camera.is_visible(&world_rect) {
+                continue;
+            }
+            let alpha = (self.config.alpha.sample(t).clamp(0.0, 1.0) * self.config.color.a as f32).round() as u8;
+            video.fill_rect(camera.world_rect_to_screen(&world_rect), self.config.color.with_alpha(alpha))?;
+        }
+        video.set_blend_mode(BlendMode::None);
+        Ok(())
+    }
+}
+
+/// Owns the running emitters and the RNG they draw from
+#[derive(Debug)]
+pub struct ParticleSystem {
+    emitters: Vec<ParticleEmitter>,
+    rng: StdRng,
+}
+
+impl ParticleSystem {
+    /// Create a system seeded from the OS
+    pub fn new() -> Self {
+        Self::from_rng(StdRng::from_entropy())
+    }
+
+    /// Create a system that produces the same particles every run
+    pub fn with_seed(seed: u64) -> Self {
+        Self::from_rng(StdRng::seed_from_u64(seed))
+    }
+
+    fn from_rng(rng: StdRng) -> Self {
+        Self {
+            emitters: Vec::new(),
+            rng,
+        }
+    }
+
+    /// Start an emitter; it is removed once it has finished
+    pub fn add(&mut self, emitter: ParticleEmitter) {
+        self.emitters.push(emitter);
+    }
+
+    /// Get the running emitters
+    pub fn emitters(&self) -> &[ParticleEmitter] {
+        &self.emitters
+    }
+
+    /// Get the running emitters (mutable)
+    pub fn emitters_mut(&mut self) -> &mut [ParticleEmitter] {
+        &mut self.emitters
+    }
+
+    /// Get the number of live particles across all emitters
+    pub fn particle_count(&self) -> usize {
+        self.emitters.iter().map(|emitter| emitter.particles.len()).sum()
+    }
+
+    /// Remove every emitter and particle
+    pub fn clear(&mut self) {
+        self.emitters.clear();
+    }
+
+    /// Update every emitter and drop the finished ones
+    pub fn update(&mut self, delta_time: f32) {
+        for emitter in &mut self.emitters {
+            emitter.update(delta_time, &mut self.rng);
+        }
+        self.emitters.retain(|emitter| !emitter.is_finished());
+    }
+
+    /// Draw the particles seen by the camera, returning the frame's stats
+    pub fn render(&self, video: &mut VideoManager, camera: &Camera) -> Result<RenderStats> {
+        let mut queue = RenderQueue::new();
+        let mut culled = 0;
+        for emitter in &self.emitters {
+            // Missing textures fall back to squares, so effects work without their assets
+            if !emitter.config.frames.is_empty() && emitter.config.frames.iter().all(|frame| video.has_texture(frame)) {
+                culled += emitter.queue(&mut queue, camera);
+            } else {
+                emitter.render_untextured(video, camera)?;
+            }
+        }
+
+        let mut stats = queue.flush(video)?;
+        stats.submitted += culled;
+        stats.culled += culled;
+        Ok(stats)
+    }
+}
+
+impl Default for ParticleSystem {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config() -> Rc<ParticleConfig> {
+        Rc::new(
+            ParticleConfig::new(Vector2::splat(2.0))
+                .with_lifetime(0.5, 1.0)
+                .with_velocity(Vector2::new(-50.0, -100.0), Vector2::new(50.0, -50.0))
+                .with_gravity(Vector2::new(0.0, 200.0)),
+        )
+    }
+
+    fn positions(system: &ParticleSystem) -> Vec<Vector2> {
+        system.emitters().iter().flat_map(|e| e.particles().iter().map(|p| p.position)).collect()
+    }
+
+    #[test]
+    fn curves_interpolate_between_points() {
+        let curve = Curve::from_points(vec![(1.0, 0.0), (0.0, 1.0), (0.5, 2.0)]);
+        assert_eq!(curve.sample(-1.0), 1.0);
+        assert_eq!(curve.sample(0.25), 1.5);
+        assert_eq!(curve.sample(0.75), 1.0);
+        assert_eq!(curve.sample(2.0), 0.0);
+        assert_eq!(Curve::constant(3.0).sample(0.5), 3.0);
+    }
+
+    #[test]
+    fn seeded_systems_are_deterministic() {
+        let run = |seed| {
+            let mut system = ParticleSystem::with_seed(seed);
+            system.add(ParticleEmitter::burst(config(), Vector2::ZERO, 8));
+            system.add(ParticleEmitter::continuous(config(), Vector2::new(10.0, 0.0), 30.0));
+            for _ in 0..20 {
+                system.update(1.0 / 60.0);
+            }
+            positions(&system)
+        };
+
+        assert_eq!(run(7), run(7));
+        assert_ne!(run(7), run(8));
+    }
+
+    #[test]
+    fn bursts_spawn_once_and_finish_when_their_particles_die() {
+        let mut system = ParticleSystem::with_seed(1);
+        system.add(ParticleEmitter::burst(config(), Vector2::ZERO, 5));
+        system.update(0.1);
+        assert_eq!(system.particle_count(), 5);
+        for particle in system.emitters()[0].particles() {
+            assert!((0.5..=1.0).contains(&particle.lifetime));
+            // Launched upwards, pulled down by gravity
+            assert!(particle.velocity.y >= -100.0 && particle.velocity.y <= -50.0);
+        }
+
+        system.update(0.1);
+        assert_eq!(system.particle_count(), 5);
+        for _ in 0..10 {
+            system.update(0.1);
+        }
+        assert_eq!(system.particle_count(), 0);
+        assert!(system.emitters().is_empty());
+    }
+
+    #[test]
+    fn continuous_emitters_spawn_at_their_rate_for_their_duration() {
+        let long_lived = Rc::new(ParticleConfig::new(Vector2::ONE).with_lifetime(10.0, 10.0));
+        let mut system = ParticleSystem::with_seed(1);
+        system.add(ParticleEmitter::continuous(long_lived, Vector2::ZERO, 20.0).with_duration(0.5));
+
+        for _ in 0..4 {
+            system.update(0.25);
+        }
+        assert_eq!(system.particle_count(), 10);
+        assert!(!system.emitters()[0].emitting);
+    }
+
+    #[test]
+    fn particles_fade_out_while_they_render() {
+        let mut video = VideoManager::headless(8, 8).unwrap();
+        video.load_texture_rgba("dot", 1, 1, vec![255, 255, 255, 255]).unwrap();
+        let camera = Camera::new(Vector2::new(8.0, 8.0));
+        let config = ParticleConfig::new(Vector2::splat(2.0))
+            .with_frames(&["dot"])
+            .with_alpha(Curve::linear(1.0, 0.0))
+            .with_color(Color::rgb(255, 0, 0));
+
+        let mut system = ParticleSystem::with_seed(1);
+        system.add(ParticleEmitter::burst(Rc::new(config), Vector2::splat(4.0), 1));
+        system.add(ParticleEmitter::burst(Rc::new(ParticleConfig::new(Vector2::ONE)), Vector2::splat(20.0), 1));
+        system.update(0.0);
+        system.update(0.5);
+
+        let stats = system.render(&mut video, &camera).unwrap();
+        assert_eq!(stats.draw_calls, 1);
+        let pixel = video.screenshot().unwrap().get_pixel(4, 4).0;
+        assert!((120..=135).contains(&pixel[0]), "half faded red, got {:?}", pixel);
+        assert_eq!(pixel[1], 0);
+    }
+}
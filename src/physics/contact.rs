@@ -0,0 +1,115 @@
+//! Contact events between physics bodies for RustUX
+
+use crate::collision::{CollisionLayer, CollisionType};
+use crate::math::{Rect, Vector2};
+
+/// How far apart two bodies may be and still count as touching
+pub const CONTACT_MARGIN: f32 = 0.5;
+
+/// Stage of a contact between two bodies
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ContactPhase {
+    /// The bodies started touching this step
+    Begin,
+    /// The bodies were already touching and still are
+    Stay,
+    /// The bodies stopped touching (or one of them was removed)
+    End,
+}
+
+/// A contact between two bodies, reported once per physics step
+///
+/// `body_a` always has the lower id of the two.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ContactEvent {
+    pub phase: ContactPhase,
+    pub body_a: u32,
+    pub body_b: u32,
+    pub layer_a: CollisionLayer,
+    pub layer_b: CollisionLayer,
+    /// Unit normal pointing from `body_b` towards `body_a`
+    pub normal: Vector2,
+    /// Either body is a sensor or trigger, so neither was blocked
+    pub sensor: bool,
+}
+
+impl ContactEvent {
+    /// Build a contact between two bodies, ordering them by id
+    pub(crate) fn between(
+        phase: ContactPhase,
+        (id_a, rect_a, layer_a, type_a): (u32, Rect, CollisionLayer, CollisionType),
+        (id_b, rect_b, layer_b, type_b): (u32, Rect, CollisionLayer, CollisionType),
+    ) -> Self {
+        if id_b < id_a {
+            return Self::between(phase, (id_b, rect_b, layer_b, type_b), (id_a, rect_a, layer_a, type_a));
+        }
+
+        let is_sensor = |collision_type| matches!(collision_type, CollisionType::Sensor | CollisionType::Trigger);
+
+        Self {
+            phase,
+            body_a: id_a,
+            body_b: id_b,
+            layer_a,
+            layer_b,
+            normal: separation_normal(&rect_a, &rect_b),
+            sensor: is_sensor(type_a) || is_sensor(type_b),
+        }
+    }
+
+    /// Check if a body takes part in the contact
+    pub fn involves(&self, body_id: u32) -> bool {
+        self.body_a == body_id || self.body_b == body_id
+    }
+
+    /// Get the body touching the given one
+    pub fn other(&self, body_id: u32) -> Option<u32> {
+        if self.body_a == body_id {
+            Some(self.body_b)
+        } else if self.body_b == body_id {
+            Some(self.body_a)
+        } else {
+            None
+        }
+    }
+
+    /// Get the layer of a body taking part in the contact
+    pub fn layer_of(&self, body_id: u32) -> Option<CollisionLayer> {
+        if self.body_a == body_id {
+            Some(self.layer_a)
+        } else if self.body_b == body_id {
+            Some(self.layer_b)
+        } else {
+            None
+        }
+    }
+
+    /// Get the contact normal pointing towards the given body
+    ///
+    /// For a body standing on another this is `(0, -1)`.
+    pub fn normal_for(&self, body_id: u32) -> Vector2 {
+        if body_id == self.body_b {
+            -self.normal
+        } else {
+            self.normal
+        }
+    }
+
+    /// Get the key identifying the pair of bodies
+    pub(crate) fn pair(&self) -> (u32, u32) {
+        (self.body_a, self.body_b)
+    }
+}
+
+/// Normal along the axis of least overlap, pointing from `b` towards `a`
+fn separation_normal(a: &Rect, b: &Rect) -> Vector2 {
+    let overlap_x = a.right().min(b.right()) - a.left().max(b.left());
+    let overlap_y = a.bottom().min(b.bottom()) - a.top().max(b.top());
+    let offset = a.center() - b.center();
+
+    if overlap_x < overlap_y {
+        Vector2::new(if offset.x < 0.0 { -1.0 } else { 1.0 }, 0.0)
+    } else {
+        Vector2::new(0.0, if offset.y < 0.0 { -1.0 } else { 1.0 })
+    }
+}
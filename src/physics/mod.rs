@@ -1,10 +1,20 @@
 //! Physics system for RustUX
 
 use crate::math::{Vector2, Rect};
-use crate::collision::{CollisionSystem, CollisionLayer, CollisionType, CollisionResult};
+use crate::collision::{CollisionSystem, CollisionLayer, CollisionType, CollisionResult, TILE_OBJECT_ID};
+use crate::tilemap::{SlopeDirection, SlopeType, TileAttributes, TileGrid};
 use crate::config::{GRAVITY, TERMINAL_VELOCITY, FIXED_TIMESTEP};
 use crate::util::Result;
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
+
+pub mod contact;
+pub mod path;
+
+pub use contact::{ContactEvent, ContactPhase, CONTACT_MARGIN};
+pub use path::{Path, PathFollower, PathMode};
+
+/// How far a body may sink into a one-way platform and still land on it
+const PLATFORM_TOLERANCE: f32 = 0.5;
 
 /// Physics body type
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -38,6 +48,16 @@ impl Default for PhysicsMaterial {
     }
 }
 
+impl PhysicsMaterial {
+    /// Slippery material for ice
+    pub fn ice() -> Self {
+        Self {
+            friction: 0.05,
+            ..Self::default()
+        }
+    }
+}
+
 /// Physics body component
 #[derive(Debug, Clone)]
 pub struct PhysicsBody {
@@ -59,10 +79,20 @@ pub struct PhysicsBody {
     pub material: PhysicsMaterial,
     /// Collision layer
     pub collision_layer: CollisionLayer,
+    /// How other bodies collide with this one
+    pub collision_type: CollisionType,
     /// Whether the body is affected by gravity
     pub use_gravity: bool,
     /// Whether the body is on the ground
     pub on_ground: bool,
+    /// Whether the ground the body stands on is a one-way platform
+    pub on_platform: bool,
+    /// How much of the body is under water, from 0.0 to 1.0
+    pub submerged: f32,
+    /// Body the body is standing on, if it isn't standing on tiles
+    pub ground_body: Option<u32>,
+    /// Time left during which one-way platforms are ignored
+    pub drop_through_time: f32,
     /// Whether the body is active
     pub active: bool,
     /// Maximum velocity
@@ -90,14 +120,25 @@ impl PhysicsBody {
             mass,
             material: PhysicsMaterial::default(),
             collision_layer: CollisionLayer::World,
+            collision_type: CollisionType::Solid,
             use_gravity: matches!(body_type, BodyType::Dynamic),
             on_ground: false,
+            on_platform: false,
+            submerged: 0.0,
+            ground_body: None,
+            drop_through_time: 0.0,
             active: true,
             max_velocity: Vector2::new(400.0, TERMINAL_VELOCITY),
             linear_damping: 0.98,
         }
     }
 
+    /// Set how other bodies collide with this one
+    pub fn with_collision_type(mut self, collision_type: CollisionType) -> Self {
+        self.collision_type = collision_type;
+        self
+    }
+
     /// Get the collision rectangle for this body
     pub fn get_rect(&self) -> Rect {Rect::new(self.position.x, self.position.y, self.size.x, self.size.y)
     }
@@ -139,6 +180,11 @@ impl PhysicsBody {
     pub fn is_moving(&self) -> bool {
         self.velocity.length_squared() > 0.01
     }
+
+    /// Check if the body is mostly under water
+    pub fn in_water(&self) -> bool {
+        self.submerged >= 0.5
+    }
 }
 
 /// Physics world that manages all physics bodies and simulation
@@ -147,6 +193,18 @@ pub struct PhysicsWorld {
     bodies: HashMap<u32, PhysicsBody>,
     /// Collision system
     collision_system: CollisionSystem,
+    /// Solid tiles of the level, if it is tile-based
+    tile_grid: Option<TileGrid>,
+    /// Paths driving kinematic bodies, keyed by body ID
+    paths: HashMap<u32, PathFollower>,
+    /// Areas filled with water
+    water_volumes: Vec<Rect>,
+    /// Pairs of bodies touching after the last step
+    contacts: BTreeMap<(u32, u32), ContactEvent>,
+    /// Pairs where the second body stopped the first one's move this step
+    blocked: Vec<(u32, u32)>,
+    /// Contact events not yet taken by the game
+    contact_events: Vec<ContactEvent>,
     /// Next available body ID
     next_id: u32,
     /// Accumulated time for fixed timestep
@@ -163,6 +221,12 @@ impl PhysicsWorld {
         Self {
             bodies: HashMap::new(),
             collision_system: CollisionSystem::new(),
+            tile_grid: None,
+            paths: HashMap::new(),
+            water_volumes: Vec::new(),
+            contacts: BTreeMap::new(),
+            blocked: Vec::new(),
+            contact_events: Vec::new(),
             next_id: 1,
             accumulator: 0.0,
             gravity: Vector2::new(0.0, GRAVITY),
@@ -178,13 +242,7 @@ impl PhysicsWorld {
         body.id = id;
         
         // Add collision object
-        let collision_type = match body.body_type {
-            BodyType::Static => CollisionType::Solid,
-            BodyType::Kinematic => CollisionType::Solid,
-            BodyType::Dynamic => CollisionType::Solid,
-        };
-        
-        self.collision_system.add_object(body.get_rect(), body.collision_layer, collision_type);
+        self.collision_system.add_object(body.get_rect(), body.collision_layer, body.collision_type);
         self.bodies.insert(id, body);
         
         id
@@ -194,6 +252,8 @@ impl PhysicsWorld {
     pub fn remove_body(&mut self, id: u32) -> Option<PhysicsBody> {
         if let Some(body) = self.bodies.remove(&id) {
             self.collision_system.remove_object(id);
+            self.paths.remove(&id);
+            self.end_contacts_of(id);
             Some(body)
         } else {
             None
@@ -227,8 +287,19 @@ impl PhysicsWorld {
 
     /// Perform one physics simulation step
     fn step(&mut self, dt: f32) {
-        // Collect body IDs to avoid borrowing issues
-        let body_ids: Vec<u32> = self.bodies.keys().copied().collect();
+        // Collect body IDs to avoid borrowing issues; bodies move in id
+        // order so a step plays out the same way every time
+        let mut body_ids: Vec<u32> = self.bodies.keys().copied().collect();
+        body_ids.sort_unstable();
+
+        // Drive kinematic bodies along their paths
+        for (&body_id, follower) in self.paths.iter_mut() {
+            if let Some(body) = self.bodies.get_mut(&body_id) {
+                if body.active {
+                    body.velocity = (follower.advance(dt) - body.position) / dt;
+                }
+            }
+        }
 
         for &body_id in &body_ids {
             if let Some(body) = self.bodies.get_mut(&body_id) {
@@ -236,17 +307,20 @@ impl PhysicsWorld {
                     continue;
                 }
 
-                // Apply gravity
+                body.submerged = submerged_fraction(&self.water_volumes, &body.get_rect());
+
+                // Apply gravity, with water pushing back up on the submerged part
                 if body.use_gravity && body.body_type == BodyType::Dynamic {
-                    body.acceleration += self.gravity;
+                    body.acceleration += self.gravity * (1.0 - constants::WATER_BUOYANCY * body.submerged);
                 }
 
                 // Integrate velocity
                 if body.body_type == BodyType::Dynamic {
                     body.velocity += body.acceleration * dt;
                     
-                    // Apply damping
+                    // Apply damping, and drag from the water
                     body.velocity *= body.linear_damping;
+                    body.velocity *= 1.0 - (1.0 - constants::WATER_DRAG) * body.submerged;
                 
                     // Clamp velocity to maximum
                     body.velocity.x = body.velocity.x.clamp(-body.max_velocity.x, body.max_velocity.x);
@@ -255,101 +329,466 @@ impl PhysicsWorld {
 
                 // Reset acceleration for next frame
                 body.acceleration = Vector2::ZERO;
+
+                body.drop_through_time = (body.drop_through_time - dt).max(0.0);
             }
         }
 
-        // Move bodies and resolve collisions
+        // Move kinematic bodies first; nothing blocks them
+        let mut displacements = HashMap::new();
         for &body_id in &body_ids {
             if let Some(body) = self.bodies.get_mut(&body_id) {
-                if !body.active || body.body_type == BodyType::Static {
+                if !body.active || body.body_type != BodyType::Kinematic {
+                    continue;
+                }
+
+                let displacement = body.velocity * dt;
+                body.position += displacement;
+                displacements.insert(body_id, displacement);
+                self.collision_system.update_object(body_id, body.get_rect());
+            }
+        }
+
+        // Carry bodies standing on kinematic bodies along with them
+        for &body_id in &body_ids {
+            let (position, ground_body, on_ground, on_platform) = match self.bodies.get(&body_id) {
+                Some(body) if body.active && body.body_type == BodyType::Dynamic => {
+                    (body.position, body.ground_body, body.on_ground, body.on_platform)
+                }
+                _ => continue,
+            };
+            let carrier = match ground_body {
+                Some(carrier) => carrier,
+                None => continue,
+            };
+
+            if let Some(&displacement) = displacements.get(&carrier) {
+                if displacement != Vector2::ZERO {
+                    self.move_body_excluding(body_id, position + displacement, Some(carrier));
+
+                    // Being carried doesn't count as leaving the ground
+                    if let Some(body) = self.bodies.get_mut(&body_id) {
+                        body.on_ground = on_ground;
+                        body.on_platform = on_platform;
+                        body.ground_body = ground_body;
+                    }
+                }
+            }
+        }
+
+        // Move dynamic bodies and resolve collisions
+        let mut landings = Vec::new();
+        for &body_id in &body_ids {
+            if let Some(body) = self.bodies.get_mut(&body_id) {
+                if !body.active || body.body_type != BodyType::Dynamic {
                     continue;
                 }
 
                 // Calculate new position
                 let old_position = body.position;
+                let old_ground_body = body.ground_body;
                 let new_position = old_position + body.velocity * dt;
                 
-                // Move and check for collisions
-                self.move_body_with_collision(body_id, new_position);}
+                // Move and check for collisions (this also updates the collision system)
+                self.move_body_with_collision(body_id, new_position);
+
+                if let Some(ground_body) = self.bodies.get(&body_id).and_then(|body| body.ground_body) {
+                    if old_ground_body != Some(ground_body) {
+                        landings.push(ground_body);
+                    }
+                }
+            }
+        }
+
+        // Triggered paths start when something lands on them
+        for body_id in landings {
+            self.trigger_body_path(body_id);
         }
 
-        // Update collision system with new positions
+        self.update_contacts();
+    }
+
+    /// Find touching bodies and emit begin/stay/end events
+    fn update_contacts(&mut self) {
+        let mut current = BTreeMap::new();
+
         for body in self.bodies.values() {
-            self.collision_system.update_object(body.id, body.get_rect());
+            // Static bodies never move into anything, so only moving bodies search
+            if !body.active || body.body_type == BodyType::Static {
+                continue;
+            }
+
+            let area = body.get_rect().expand(CONTACT_MARGIN);
+            for collision in self.collision_system.check_collisions(&area, body.collision_layer) {
+                let other = match self.bodies.get(&collision.object.id) {
+                    Some(other) if other.id != body.id && other.active => other,
+                    _ => continue,
+                };
+
+                let contact = ContactEvent::between(
+                    ContactPhase::Stay,
+                    (body.id, body.get_rect(), body.collision_layer, body.collision_type),
+                    (other.id, other.get_rect(), other.collision_layer, other.collision_type),
+                );
+                current.entry(contact.pair()).or_insert(contact);
+            }
+        }
+
+        // A body that stopped another one moved on before the step ended; they
+        // still touched, even if it is now a little further than the margin
+        for (body_id, other_id) in std::mem::take(&mut self.blocked) {
+            let (body, other) = match (self.bodies.get(&body_id), self.bodies.get(&other_id)) {
+                (Some(body), Some(other)) if body.active && other.active => (body, other),
+                _ => continue,
+            };
+
+            let contact = ContactEvent::between(
+                ContactPhase::Stay,
+                (body.id, body.get_rect(), body.collision_layer, body.collision_type),
+                (other.id, other.get_rect(), other.collision_layer, other.collision_type),
+            );
+            current.entry(contact.pair()).or_insert(contact);
         }
+
+        for (pair, contact) in &current {
+            let phase = if self.contacts.contains_key(pair) { ContactPhase::Stay } else { ContactPhase::Begin };
+            self.contact_events.push(ContactEvent { phase, ..contact.clone() });
+        }
+        for (pair, contact) in &self.contacts {
+            if !current.contains_key(pair) {
+                self.contact_events.push(ContactEvent { phase: ContactPhase::End, ..contact.clone() });
+            }
+        }
+
+        self.contacts = current;
+    }
+
+    /// End all contacts of a body that is leaving the world
+    fn end_contacts_of(&mut self, body_id: u32) {
+        let ended: Vec<(u32, u32)> = self.contacts.keys().copied().filter(|&(a, b)| a == body_id || b == body_id).collect();
+        for pair in ended {
+            if let Some(contact) = self.contacts.remove(&pair) {
+                self.contact_events.push(ContactEvent { phase: ContactPhase::End, ..contact });
+            }
+        }
+    }
+
+    /// Get contact events emitted since they were last taken
+    pub fn contact_events(&self) -> &[ContactEvent] {
+        &self.contact_events
+    }
+
+    /// Take all pending contact events
+    ///
+    /// Events accumulate over physics steps until taken, so a game should
+    /// take them once per update.
+    pub fn take_contact_events(&mut self) -> Vec<ContactEvent> {
+        std::mem::take(&mut self.contact_events)
+    }
+
+    /// Check if two bodies were touching after the last step
+    pub fn are_touching(&self, body_a: u32, body_b: u32) -> bool {
+        self.contacts.contains_key(&(body_a.min(body_b), body_a.max(body_b)))
     }
 
     /// Move a body to a new position, resolving collisions
     fn move_body_with_collision(&mut self, body_id: u32, target_position: Vector2) {
-        let body = match self.bodies.get_mut(&body_id) {
-            Some(body) => body,
-            None => return,
+        self.move_body_excluding(body_id, target_position, None);
+    }
+
+    /// Move a body to a new position, resolving collisions against everything but `ignored`
+    ///
+    /// Movement is resolved one axis at a time against solid tiles from the
+    /// tile grid and against bodies in the collision system. Slope tiles are
+    /// resolved afterwards, and bodies that were on the ground are snapped
+    /// back onto it when walking down a slope.
+    fn move_body_excluding(&mut self, body_id: u32, target_position: Vector2, ignored: Option<u32>) {
+        let (old_position, size, layer, mut velocity, dropping_through, was_on_ground) = match self.bodies.get(&body_id) {
+            Some(body) if body.body_type != BodyType::Static => (
+                body.position,
+                body.size,
+                body.collision_layer,
+                body.velocity,
+                body.drop_through_time > 0.0,
+                body.on_ground,
+            ),
+            _ => return,
         };
 
-        if body.body_type == BodyType::Static {
-            return;
-        }
+        let mut position = old_position;
+        let mut hit_ground = false;
+        let mut on_platform = false;
+        let mut ground_body = None;
 
-        let old_position = body.position;
-        body.position = target_position;
-        let new_rect = body.get_rect();
-        
-        // Check for collisions
-        let collisions = self.collision_system.check_collisions(&new_rect, body.collision_layer);
-        
-        if collisions.is_empty() {
-            body.on_ground = false;
-            return;
-        }
+        // Horizontal pass
+        let dx = target_position.x - old_position.x;
+        if dx != 0.0 {
+            position.x = target_position.x;
 
-        // Resolve collisions
-        let mut resolved_position = target_position;
-        let mut hit_ground = false;
+            // Walk up floor slopes first, so the tile at the top of a slope
+            // isn't mistaken for a wall
+            if was_on_ground {
+                if let Some(surface) = self.floor_slope_surface(&Rect::from_pos_size(position, size), layer, 0.0, 0.0) {
+                    position.y = position.y.min(surface - size.y);
+                }
+            }
 
-        for collision in collisions {
-            // Skip if it's the same object
-            if collision.object.id == body_id {
-                continue;
+            // Sweep the whole move so fast bodies can't skip past thin walls;
+            // the nearest wall along the way wins
+            let rect = swept_rect(Vector2::new(old_position.x, position.y), position, size);
+            let mut blocker = None;
+            for collision in self.blocking_collisions(body_id, &rect, layer, ignored) {
+                if collision.object.collision_type != CollisionType::Solid {
+                    continue;
+                }
+
+                let other = collision.object.rect;
+                let stop = if dx > 0.0 { other.left() - size.x } else { other.right() };
+                if (dx > 0.0 && stop <= position.x) || (dx < 0.0 && stop >= position.x) {
+                    position.x = stop;
+                    blocker = Some(collision.object.id);
+                }
+                velocity.x = 0.0;
+            }
+            if let Some(other_id) = blocker.filter(|&id| id != TILE_OBJECT_ID) {
+                self.blocked.push((body_id, other_id));
             }
 
-            match collision.object.collision_type {
-                CollisionType::Solid => {
-                    // Resolve solid collision
-                    let resolved_rect = self.collision_system.resolve_collision(&new_rect, &collision);
-                    resolved_position = Vector2::new(resolved_rect.x, resolved_rect.y);
-                    
-                    // Update velocity based on collision direction
-                    match collision.direction {
-                        crate::math::Direction::Left | crate::math::Direction::Right => {
-                            body.velocity.x = 0.0;
-                        }
-                        crate::math::Direction::Up => {
-                            body.velocity.y = 0.0;
+            // The tall side of a slope is a wall when it's too high to step onto
+            let rect = swept_rect(Vector2::new(old_position.x, position.y), position, size);
+            for (cell, slope) in self.slope_cells(&rect, layer) {
+                if slope_penetration(&slope, &cell, &rect) <= size.y * 0.5 {
+                    continue;
+                }
+
+                let tall_right = slope.direction == SlopeDirection::RisingRight;
+                if dx < 0.0 && tall_right && old_position.x >= cell.right() - PLATFORM_TOLERANCE {
+                    position.x = position.x.max(cell.right());
+                    velocity.x = 0.0;
+                } else if dx > 0.0 && !tall_right && old_position.x + size.x <= cell.left() + PLATFORM_TOLERANCE {
+                    position.x = position.x.min(cell.left() - size.x);
+                    velocity.x = 0.0;
+                }
+            }
+        }
+
+        // Vertical pass
+        let dy = target_position.y - old_position.y;
+        if dy != 0.0 {
+            position.y = target_position.y;
+            let swept = swept_rect(Vector2::new(position.x, old_position.y), position, size);
+
+            for collision in self.blocking_collisions(body_id, &swept, layer, ignored) {
+                let other = collision.object.rect;
+                let other_id = collision.object.id;
+                match collision.object.collision_type {
+                    CollisionType::Solid => {
+                        if dy > 0.0 {
+                            // Only the nearest ground along the sweep is stood on
+                            let landing = other.top() - size.y;
+                            if landing <= position.y {
+                                position.y = landing;
+                                hit_ground = true;
+                                on_platform = false;
+                                ground_body = (other_id != TILE_OBJECT_ID).then_some(other_id);
+                            }
+                        } else {
+                            position.y = position.y.max(other.bottom());
                         }
-                        crate::math::Direction::Down => {
-                            body.velocity.y = 0.0;
-                hit_ground = true;
+                        velocity.y = 0.0;
+                    }
+                    CollisionType::Platform => {
+                        // One-way platform - only block bodies falling onto it from above
+                        let was_above = old_position.y + size.y <= other.top() + PLATFORM_TOLERANCE;
+                        let landing = other.top() - size.y;
+                        let nearer = landing < position.y || (!hit_ground && landing <= position.y);
+                        if dy > 0.0 && was_above && !dropping_through && nearer {
+                            position.y = landing;
+                            velocity.y = 0.0;
+                            on_platform = true;
+                            hit_ground = true;
+                            ground_body = (other_id != TILE_OBJECT_ID).then_some(other_id);
                         }
                     }
+                    CollisionType::Trigger | CollisionType::Sensor => {
+                        // Don't resolve position for triggers/sensors
+                        // These are handled by the game logic
+                    }
                 }
-                CollisionType::Platform => {
-                    // One-way platform - only collide from above
-                    if collision.direction == crate::math::Direction::Down && body.velocity.y >= 0.0 {
-                        let resolved_rect = self.collision_system.resolve_collision(&new_rect, &collision);
-                        resolved_position.y = resolved_rect.y;
-                        body.velocity.y = 0.0;
+            }
+
+            // The flat sides of slope tiles: the underside of floor slopes
+            // and the top of ceiling slopes
+            let rect = swept_rect(Vector2::new(position.x, old_position.y), position, size);
+            for (cell, slope) in self.slope_cells(&rect, layer) {
+                if dy < 0.0 && !slope.ceiling && old_position.y >= cell.bottom() - PLATFORM_TOLERANCE {
+                    position.y = position.y.max(cell.bottom());
+                    velocity.y = 0.0;
+                } else if dy > 0.0 && slope.ceiling && old_position.y + size.y <= cell.top() + PLATFORM_TOLERANCE {
+                    let landing = cell.top() - size.y;
+                    if landing <= position.y {
+                        position.y = landing;
+                        velocity.y = 0.0;
                         hit_ground = true;
+                        on_platform = false;
+                        ground_body = None;
                     }
                 }
-                CollisionType::Trigger | CollisionType::Sensor => {
-                    // Don't resolve position for triggers/sensors
-                    // These are handled by the game logic
+            }
+        }
+
+        // Slope surfaces, including ones passed through during a fast move
+        let travel = (position.y - old_position.y).abs();
+        if dy >= 0.0 {
+            let rect = Rect::from_pos_size(position, size);
+            if let Some(surface) = self.floor_slope_surface(&rect, layer, 0.0, travel) {
+                position.y = position.y.min(surface - size.y);
+                velocity.y = velocity.y.min(0.0);
+                hit_ground = true;
+                on_platform = false;
+                ground_body = None;
+            } else if was_on_ground && !hit_ground && !dropping_through {
+                if let Some(ground) = self.ground_below(&rect, layer, constants::GROUND_SNAP_DISTANCE) {
+                    position.y = ground - size.y;
+                    velocity.y = velocity.y.min(0.0);
+                    hit_ground = true;
                 }
             }
+        } else if let Some(surface) = self.ceiling_slope_surface(&Rect::from_pos_size(position, size), layer, travel) {
+            position.y = position.y.max(surface);
+            velocity.y = 0.0;
         }
 
-        body.position = resolved_position;
-        body.on_ground = hit_ground;
+        if let Some(body) = self.bodies.get_mut(&body_id) {
+            body.position = position;
+            body.velocity = velocity;
+            body.on_ground = hit_ground;
+            body.on_platform = on_platform;
+            body.ground_body = ground_body;
+            self.collision_system.update_object(body_id, body.get_rect());
+        }
+    }
+
+    /// Get the slope tiles overlapping a rectangle, if the layer collides with the world
+    fn slope_cells(&self, rect: &Rect, layer: CollisionLayer) -> Vec<(Rect, SlopeType)> {
+        match &self.tile_grid {
+            Some(grid) if self.collision_system.layers_interact(layer, CollisionLayer::World) => {
+                grid.slope_cells_in_rect(rect)
+            }
+            _ => Vec::new(),
+        }
+    }
+
+    /// Get the highest floor slope surface a rectangle stands on
+    ///
+    /// Surfaces up to `snap_distance` below the rectangle count as well.
+    /// Surfaces above the middle of the rectangle are ignored, so bodies
+    /// are only pushed up, never through a slope. `travel` is how far the
+    /// rectangle just fell, so surfaces it passed through still count.
+    fn floor_slope_surface(&self, rect: &Rect, layer: CollisionLayer, snap_distance: f32, travel: f32) -> Option<f32> {
+        let area = Rect::new(rect.x, rect.y - travel, rect.width, rect.height + travel + snap_distance);
+        self.slope_cells(&area, layer)
+            .into_iter()
+            .filter(|(_, slope)| !slope.ceiling)
+            .map(|(cell, slope)| (slope_penetration(&slope, &cell, rect), slope.surface_y(&cell, slope.contact_x(&cell, rect))))
+            .filter(|&(penetration, _)| penetration >= -snap_distance && penetration <= rect.height * 0.5 + travel)
+            .map(|(_, surface)| surface)
+            .reduce(f32::min)
+    }
+
+    /// Get the lowest ceiling slope surface a rectangle has moved into
+    ///
+    /// `travel` is how far the rectangle just rose.
+    fn ceiling_slope_surface(&self, rect: &Rect, layer: CollisionLayer, travel: f32) -> Option<f32> {
+        let area = Rect::new(rect.x, rect.y, rect.width, rect.height + travel);
+        self.slope_cells(&area, layer)
+            .into_iter()
+            .filter(|(_, slope)| slope.ceiling)
+            .map(|(cell, slope)| (slope_penetration(&slope, &cell, rect), slope.surface_y(&cell, slope.contact_x(&cell, rect))))
+            .filter(|&(penetration, _)| penetration > 0.0 && penetration <= rect.height * 0.5 + travel)
+            .map(|(_, surface)| surface)
+            .reduce(f32::max)
+    }
+
+    /// Find the nearest ground within `max_distance` below a rectangle
+    fn ground_below(&self, rect: &Rect, layer: CollisionLayer, max_distance: f32) -> Option<f32> {
+        let slope = self.floor_slope_surface(rect, layer, max_distance, 0.0);
+
+        let probe = Rect::new(rect.x, rect.bottom(), rect.width, max_distance);
+        let flat = match &self.tile_grid {
+            Some(grid) if self.collision_system.layers_interact(layer, CollisionLayer::World) => grid
+                .collidable_cells_in_rect(&probe)
+                .into_iter()
+                .filter(|(_, _, attributes)| attributes.solid || attributes.one_way)
+                .map(|(x, y, _)| grid.cell_rect(x, y).top())
+                .filter(|&top| top >= rect.bottom() - PLATFORM_TOLERANCE)
+                .reduce(f32::min),
+            _ => None,
+        };
+
+        match (slope, flat) {
+            (Some(a), Some(b)) => Some(a.min(b)),
+            (a, b) => a.or(b),
+        }
+    }
+
+    /// Get tile and object collisions for a body's rectangle, excluding the body itself
+    fn blocking_collisions(&self, body_id: u32, rect: &Rect, layer: CollisionLayer, ignored: Option<u32>) -> Vec<CollisionResult> {
+        let mut collisions = self.collision_system.check_collisions_with_tiles(rect, layer, self.tile_grid.as_ref());
+        collisions.retain(|collision| collision.object.id != body_id && Some(collision.object.id) != ignored);
+        collisions
+    }
+
+    /// Let a body fall through the one-way platform it is standing on
+    ///
+    /// Returns false if the body isn't standing on a one-way platform.
+    pub fn drop_through(&mut self, body_id: u32) -> bool {
+        match self.bodies.get_mut(&body_id) {
+            Some(body) if body.on_ground && body.on_platform => {
+                body.drop_through_time = constants::DROP_THROUGH_TIME;
+                body.on_ground = false;
+                body.on_platform = false;
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Make a kinematic body follow a path, starting at its first waypoint
+    pub fn set_body_path(&mut self, body_id: u32, path: Path) {
+        let follower = PathFollower::new(path);
+        if let Some(body) = self.bodies.get_mut(&body_id) {
+            body.position = follower.position();
+            body.velocity = Vector2::ZERO;
+            self.collision_system.update_object(body_id, body.get_rect());
+            self.paths.insert(body_id, follower);
+        }
+    }
+
+    /// Get the path a body is following
+    pub fn body_path(&self, body_id: u32) -> Option<&PathFollower> {
+        self.paths.get(&body_id)
+    }
+
+    /// Stop a body from following its path
+    pub fn clear_body_path(&mut self, body_id: u32) -> Option<PathFollower> {
+        if let Some(body) = self.bodies.get_mut(&body_id) {
+            body.velocity = Vector2::ZERO;
+        }
+        self.paths.remove(&body_id)
+    }
+
+    /// Start a body on a triggered path moving towards its other end
+    pub fn trigger_body_path(&mut self, body_id: u32) -> bool {
+        self.paths.get_mut(&body_id).is_some_and(|follower| follower.trigger())
+    }
+
+    /// Change how other bodies collide with a body
+    pub fn set_body_collision_type(&mut self, body_id: u32, collision_type: CollisionType) {
+        if let Some(body) = self.bodies.get_mut(&body_id) {
+            body.collision_type = collision_type;
+            self.collision_system.set_object_collision_type(body_id, collision_type);
+        }
     }
 
     /// Apply a force to a body
@@ -381,11 +820,75 @@ impl PhysicsWorld {
         }
     }
 
+    /// Resize a body, keeping its top-left corner in place
+    pub fn set_body_size(&mut self, body_id: u32, size: Vector2) {
+        if let Some(body) = self.bodies.get_mut(&body_id) {
+            body.size = size;
+            self.collision_system.update_object(body_id, body.get_rect());
+        }
+    }
+
     /// Perform a raycast in the physics world
     pub fn raycast(&self, start: Vector2, direction: Vector2, max_distance: f32, layer: CollisionLayer) -> Option<CollisionResult> {
         self.collision_system.raycast(start, direction, max_distance, layer)
     }
 
+    /// Set the tile grid used for level geometry
+    pub fn set_tile_grid(&mut self, tile_grid: TileGrid) {
+        self.tile_grid = Some(tile_grid);
+    }
+
+    /// Get the tile grid
+    pub fn tile_grid(&self) -> Option<&TileGrid> {
+        self.tile_grid.as_ref()
+    }
+
+    /// Get the tile grid (mutable)
+    pub fn tile_grid_mut(&mut self) -> Option<&mut TileGrid> {
+        self.tile_grid.as_mut()
+    }
+
+    /// Check if there is a solid tile at a world position
+    pub fn is_solid_at(&self, point: Vector2) -> bool {
+        self.tile_grid.as_ref().map(|grid| grid.is_solid_at(point)).unwrap_or(false)
+    }
+
+    /// Get the tile attributes at a world position
+    pub fn tile_attributes_at(&self, point: Vector2) -> TileAttributes {
+        self.tile_grid.as_ref().map(|grid| grid.attributes_at(point)).unwrap_or_default()
+    }
+
+    /// Check if any solid tile overlaps a rectangle
+    pub fn is_solid_in_rect(&self, rect: &Rect) -> bool {
+        self.tile_grid.as_ref().map(|grid| grid.is_solid_in_rect(rect)).unwrap_or(false)
+    }
+
+    /// Fill an area with water
+    pub fn add_water_volume(&mut self, area: Rect) {
+        self.water_volumes.push(area);
+    }
+
+    /// Get the areas filled with water
+    pub fn water_volumes(&self) -> &[Rect] {
+        &self.water_volumes
+    }
+
+    /// Get the friction of the ground a body stands on, or `None` if it is in the air
+    ///
+    /// Bodies use their own material; tiles use ice or the default material
+    /// depending on their attributes.
+    pub fn ground_friction(&self, body_id: u32) -> Option<f32> {
+        let body = self.bodies.get(&body_id).filter(|body| body.on_ground)?;
+        if let Some(ground) = body.ground_body.and_then(|ground| self.bodies.get(&ground)) {
+            return Some(ground.material.friction);
+        }
+
+        // Look just below the middle of the body's feet
+        let feet = Vector2::new(body.get_center().x, body.position.y + body.size.y + 1.0);
+        let material = if self.tile_attributes_at(feet).ice { PhysicsMaterial::ice() } else { PhysicsMaterial::default() };
+        Some(material.friction)
+    }
+
     /// Get all bodies in a rectangular area
     pub fn query_area(&self, rect: &Rect) -> Vec<u32> {
         let mut body_ids = Vec::new();
@@ -423,6 +926,12 @@ impl PhysicsWorld {
     pub fn clear(&mut self) {
         self.bodies.clear();
         self.collision_system.clear();
+        self.tile_grid = None;
+        self.paths.clear();
+        self.water_volumes.clear();
+        self.contacts.clear();
+        self.blocked.clear();
+        self.contact_events.clear();
     }
 
     /// Get the number of bodies in the world
@@ -452,6 +961,38 @@ impl Default for PhysicsWorld {
     }
 }
 
+/// Rectangle covering a body's whole move from `from` to `to`
+fn swept_rect(from: Vector2, to: Vector2, size: Vector2) -> Rect {
+    let min = from.min(to);
+    Rect::from_pos_size(min, (to - from).abs() + size)
+}
+
+/// Share of a rectangle's area covered by water volumes
+fn submerged_fraction(volumes: &[Rect], rect: &Rect) -> f32 {
+    let area = rect.width * rect.height;
+    if area <= 0.0 {
+        return 0.0;
+    }
+    let covered: f32 = volumes
+        .iter()
+        .filter_map(|volume| volume.intersection(rect))
+        .map(|overlap| overlap.width * overlap.height)
+        .sum();
+    (covered / area).min(1.0)
+}
+
+/// How far a rectangle reaches into the solid part of a slope tile
+///
+/// Negative values mean the rectangle is clear of the surface.
+fn slope_penetration(slope: &SlopeType, cell: &Rect, rect: &Rect) -> f32 {
+    let surface = slope.surface_y(cell, slope.contact_x(cell, rect));
+    if slope.ceiling {
+        surface - rect.top()
+    } else {
+        rect.bottom() - surface
+    }
+}
+
 /// Physics utilities
 pub mod utils {
     use super::*;
@@ -511,4 +1052,516 @@ pub mod constants {
     
     /// Air resistance
     pub const AIR_RESISTANCE: f32 = 0.99;
-}
\ No newline at end of file
+
+    /// How long one-way platforms are ignored after dropping through (seconds)
+    pub const DROP_THROUGH_TIME: f32 = 0.2;
+
+    /// How far a grounded body is pulled down to stay on a slope (pixels)
+    pub const GROUND_SNAP_DISTANCE: f32 = 8.0;
+
+    /// Share of gravity water cancels on a fully submerged body
+    pub const WATER_BUOYANCY: f32 = 0.9;
+
+    /// Velocity kept per physics step by a fully submerged body
+    pub const WATER_DRAG: f32 = 0.95;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tilemap::SlopeGradient;
+    use approx::assert_relative_eq;
+
+    fn slope_tile(slope: SlopeType) -> TileAttributes {
+        TileAttributes {
+            slope: Some(slope),
+            ..TileAttributes::default()
+        }
+    }
+
+    /// World with a solid floor along row 5 (y = 160) and the given extra tiles
+    fn world_with_tiles(tiles: &[(i32, i32, TileAttributes)]) -> PhysicsWorld {
+        let mut grid = TileGrid::new();
+        for x in 0..10 {
+            grid.set_attributes(x, 5, TileAttributes::solid());
+        }
+        for &(x, y, attributes) in tiles {
+            grid.set_attributes(x, y, attributes);
+        }
+        let mut world = PhysicsWorld::new();
+        world.set_tile_grid(grid);
+        world
+    }
+
+    fn add_box(world: &mut PhysicsWorld, x: f32, y: f32) -> u32 {
+        let mut body = PhysicsBody::new(0, Vector2::new(x, y), Vector2::new(16.0, 16.0), BodyType::Dynamic);
+        body.collision_layer = CollisionLayer::Player;
+        world.add_body(body)
+    }
+
+    fn run(world: &mut PhysicsWorld, steps: usize) {
+        for _ in 0..steps {
+            world.step(FIXED_TIMESTEP);
+        }
+    }
+
+    #[test]
+    fn rests_on_and_is_blocked_by_grid_tiles() {
+        let wall = TileAttributes::solid();
+        let mut world = world_with_tiles(&[(8, 4, wall), (8, 3, wall)]);
+        let id = add_box(&mut world, 100.0, 40.0);
+        run(&mut world, 60);
+
+        // The tiles are collided against directly, the box is the only body
+        assert_eq!(world.body_count(), 1);
+        let body = world.get_body(id).unwrap();
+        assert_eq!(body.position.y, 144.0);
+        assert!(body.on_ground);
+        assert_eq!(body.ground_body, None);
+
+        walk(&mut world, id, 200.0, 60);
+        assert_eq!(world.get_body(id).unwrap().position.x, 240.0);
+
+        // A tile overhead stops a jump
+        let mut world = world_with_tiles(&[(3, 2, wall)]);
+        let id = add_box(&mut world, 100.0, 144.0);
+        run(&mut world, 2);
+        world.set_body_velocity(id, Vector2::new(0.0, -600.0));
+        let mut highest = f32::MAX;
+        for _ in 0..30 {
+            world.step(FIXED_TIMESTEP);
+            highest = highest.min(world.get_body(id).unwrap().position.y);
+        }
+        assert_eq!(highest, 96.0);
+    }
+
+    /// Drop a box onto a floor slope in cell (4, 4) and return where it rests
+    fn rest_on(slope: SlopeType) -> (f32, bool) {
+        let mut world = world_with_tiles(&[(4, 4, slope_tile(slope))]);
+        let id = add_box(&mut world, 136.0, 60.0);
+        run(&mut world, 120);
+        let body = world.get_body(id).unwrap();
+        assert_eq!(body.velocity.y, 0.0);
+        (body.position.y, body.on_ground)
+    }
+
+    /// Push a box up into a ceiling slope in cell (4, 2) and return where it stops
+    fn stop_under(slope: SlopeType) -> (f32, bool) {
+        let mut world = world_with_tiles(&[(4, 2, slope_tile(slope))]);
+        let id = add_box(&mut world, 136.0, 120.0);
+        world.get_body_mut(id).unwrap().use_gravity = false;
+        for _ in 0..60 {
+            world.set_body_velocity(id, Vector2::new(0.0, -200.0));
+            world.step(FIXED_TIMESTEP);
+        }
+        let body = world.get_body(id).unwrap();
+        (body.position.y, body.on_ground)
+    }
+
+    #[test]
+    fn rests_on_steep_floor_slopes() {
+        // The box spans x 136..152; it rests on whichever edge is higher up the slope
+        assert_eq!(rest_on(SlopeType::floor(SlopeDirection::RisingRight, SlopeGradient::Steep)), (120.0, true));
+        assert_eq!(rest_on(SlopeType::floor(SlopeDirection::RisingLeft, SlopeGradient::Steep)), (120.0, true));
+    }
+
+    #[test]
+    fn rests_on_gentle_floor_slopes() {
+        assert_eq!(rest_on(SlopeType::floor(SlopeDirection::RisingRight, SlopeGradient::GentleLower)), (132.0, true));
+        assert_eq!(rest_on(SlopeType::floor(SlopeDirection::RisingRight, SlopeGradient::GentleUpper)), (116.0, true));
+        assert_eq!(rest_on(SlopeType::floor(SlopeDirection::RisingLeft, SlopeGradient::GentleLower)), (132.0, true));
+        assert_eq!(rest_on(SlopeType::floor(SlopeDirection::RisingLeft, SlopeGradient::GentleUpper)), (116.0, true));
+    }
+
+    #[test]
+    fn stops_under_steep_ceiling_slopes() {
+        assert_eq!(stop_under(SlopeType::ceiling(SlopeDirection::RisingRight, SlopeGradient::Steep)), (88.0, false));
+        assert_eq!(stop_under(SlopeType::ceiling(SlopeDirection::RisingLeft, SlopeGradient::Steep)), (88.0, false));
+    }
+
+    #[test]
+    fn stops_under_gentle_ceiling_slopes() {
+        assert_eq!(stop_under(SlopeType::ceiling(SlopeDirection::RisingRight, SlopeGradient::GentleLower)), (76.0, false));
+        assert_eq!(stop_under(SlopeType::ceiling(SlopeDirection::RisingRight, SlopeGradient::GentleUpper)), (92.0, false));
+        assert_eq!(stop_under(SlopeType::ceiling(SlopeDirection::RisingLeft, SlopeGradient::GentleLower)), (76.0, false));
+        assert_eq!(stop_under(SlopeType::ceiling(SlopeDirection::RisingLeft, SlopeGradient::GentleUpper)), (92.0, false));
+    }
+
+    #[test]
+    fn floor_slope_underside_is_solid() {
+        let slope = SlopeType::floor(SlopeDirection::RisingRight, SlopeGradient::Steep);
+        let mut world = world_with_tiles(&[(4, 2, slope_tile(slope))]);
+        let id = add_box(&mut world, 136.0, 120.0);
+        world.get_body_mut(id).unwrap().use_gravity = false;
+        for _ in 0..60 {
+            world.set_body_velocity(id, Vector2::new(0.0, -200.0));
+            world.step(FIXED_TIMESTEP);
+        }
+        assert_eq!(world.get_body(id).unwrap().position.y, 96.0);
+    }
+
+    #[test]
+    fn water_slows_a_fall_and_marks_the_body_submerged() {
+        let mut dry = world_with_tiles(&[]);
+        let mut wet = world_with_tiles(&[]);
+        wet.add_water_volume(Rect::new(0.0, 0.0, 320.0, 160.0));
+        let dry_id = add_box(&mut dry, 64.0, 16.0);
+        let wet_id = add_box(&mut wet, 64.0, 16.0);
+        run(&mut dry, 30);
+        run(&mut wet, 30);
+
+        let dry_body = dry.get_body(dry_id).unwrap();
+        let wet_body = wet.get_body(wet_id).unwrap();
+        assert_eq!(dry_body.submerged, 0.0);
+        assert!(!dry_body.in_water());
+        assert_relative_eq!(wet_body.submerged, 1.0, epsilon = 1e-4);
+        assert!(wet_body.in_water());
+        assert!(wet_body.position.y < 40.0, "sank to {}", wet_body.position.y);
+        assert!(dry_body.position.y > 100.0);
+    }
+
+    #[test]
+    fn ground_friction_comes_from_the_surface() {
+        let ice = TileAttributes { ice: true, ..TileAttributes::solid() };
+        let mut world = world_with_tiles(&[(2, 5, ice)]);
+        let on_ice = add_box(&mut world, 64.0, 140.0);
+        let on_rock = add_box(&mut world, 200.0, 140.0);
+        let airborne = add_box(&mut world, 120.0, 0.0);
+        run(&mut world, 10);
+
+        assert_eq!(world.ground_friction(on_ice), Some(PhysicsMaterial::ice().friction));
+        assert_eq!(world.ground_friction(on_rock), Some(PhysicsMaterial::default().friction));
+        assert_eq!(world.ground_friction(airborne), None);
+    }
+
+    /// A 45° hill descending to the right from a plateau at y = 64 to the floor at y = 160
+    fn hill() -> PhysicsWorld {
+        let slope = slope_tile(SlopeType::floor(SlopeDirection::RisingLeft, SlopeGradient::Steep));
+        let mut tiles = vec![(3, 2, slope), (4, 3, slope), (5, 4, slope)];
+        for x in 0..3 {
+            for y in 2..5 {
+                tiles.push((x, y, TileAttributes::solid()));
+            }
+        }
+        tiles.extend([(3, 3), (3, 4), (4, 4)].map(|(x, y)| (x, y, TileAttributes::solid())));
+        world_with_tiles(&tiles)
+    }
+
+    fn walk(world: &mut PhysicsWorld, id: u32, speed: f32, steps: usize) -> bool {
+        let mut always_grounded = true;
+        for _ in 0..steps {
+            let velocity = world.get_body(id).unwrap().velocity;
+            world.set_body_velocity(id, Vector2::new(speed, velocity.y));
+            world.step(FIXED_TIMESTEP);
+            always_grounded &= world.get_body(id).unwrap().on_ground;
+        }
+        always_grounded
+    }
+
+    #[test]
+    fn snaps_to_ground_walking_down_a_slope() {
+        let mut world = hill();
+        let id = add_box(&mut world, 40.0, 48.0);
+        run(&mut world, 2);
+        assert!(world.get_body(id).unwrap().on_ground);
+
+        assert!(walk(&mut world, id, 150.0, 80));
+        let body = world.get_body(id).unwrap();
+        assert!(body.position.x > 200.0);
+        assert_eq!(body.position.y, 144.0);
+    }
+
+    #[test]
+    fn walks_up_a_slope_onto_the_plateau() {
+        let mut world = hill();
+        let id = add_box(&mut world, 230.0, 144.0);
+        run(&mut world, 2);
+
+        assert!(walk(&mut world, id, -150.0, 80));
+        let body = world.get_body(id).unwrap();
+        assert!(body.position.x < 90.0);
+        assert_eq!(body.position.y, 48.0);
+    }
+
+    #[test]
+    fn tall_side_of_a_slope_is_a_wall() {
+        let slope = SlopeType::floor(SlopeDirection::RisingRight, SlopeGradient::Steep);
+        let mut world = world_with_tiles(&[(4, 4, slope_tile(slope))]);
+        let id = add_box(&mut world, 200.0, 144.0);
+        run(&mut world, 2);
+
+        walk(&mut world, id, -150.0, 60);
+        assert_eq!(world.get_body(id).unwrap().position.x, 160.0);
+    }
+
+    /// World with the solid floor and a one-way platform spanning x 96..224 at y = 100
+    fn world_with_one_way_platform() -> PhysicsWorld {
+        let mut world = world_with_tiles(&[]);
+        let platform = PhysicsBody::new(0, Vector2::new(96.0, 100.0), Vector2::new(128.0, 8.0), BodyType::Static)
+            .with_collision_type(CollisionType::Platform);
+        world.add_body(platform);
+        world
+    }
+
+    #[test]
+    fn lands_on_one_way_platforms_from_above() {
+        let mut world = world_with_one_way_platform();
+        let id = add_box(&mut world, 136.0, 20.0);
+        run(&mut world, 60);
+
+        let body = world.get_body(id).unwrap();
+        assert_eq!(body.position.y, 84.0);
+        assert!(body.on_ground && body.on_platform);
+    }
+
+    #[test]
+    fn jumps_up_through_one_way_platforms() {
+        let mut world = world_with_one_way_platform();
+        let id = add_box(&mut world, 136.0, 144.0);
+        run(&mut world, 2);
+        world.set_body_velocity(id, Vector2::new(0.0, -600.0));
+
+        let mut highest = f32::MAX;
+        for _ in 0..90 {
+            world.step(FIXED_TIMESTEP);
+            highest = highest.min(world.get_body(id).unwrap().position.y);
+        }
+        // Passes the platform on the way up and lands on it on the way down
+        assert!(highest < 84.0, "only got up to {}", highest);
+        let body = world.get_body(id).unwrap();
+        assert_eq!(body.position.y, 84.0);
+        assert!(body.on_platform);
+    }
+
+    #[test]
+    fn drops_through_one_way_platforms_for_a_moment() {
+        let mut world = world_with_one_way_platform();
+        let id = add_box(&mut world, 136.0, 84.0);
+        run(&mut world, 2);
+        assert!(world.get_body(id).unwrap().on_platform);
+
+        assert!(world.drop_through(id));
+        assert_eq!(world.get_body(id).unwrap().drop_through_time, constants::DROP_THROUGH_TIME);
+        run(&mut world, 60);
+        let body = world.get_body(id).unwrap();
+        assert_eq!(body.position.y, 144.0);
+        assert!(body.on_ground && !body.on_platform);
+        assert_eq!(body.drop_through_time, 0.0);
+
+        // Solid ground can't be dropped through
+        assert!(!world.drop_through(id));
+
+        // Once the time is up the platform holds again
+        world.set_body_position(id, Vector2::new(136.0, 60.0));
+        run(&mut world, 60);
+        assert_eq!(world.get_body(id).unwrap().position.y, 84.0);
+    }
+
+    fn add_platform(world: &mut PhysicsWorld, path: Path) -> u32 {
+        let body = PhysicsBody::new(0, Vector2::ZERO, Vector2::new(64.0, 16.0), BodyType::Kinematic);
+        let id = world.add_body(body);
+        world.set_body_path(id, path);
+        id
+    }
+
+    /// Offset of the rider from the platform after each of `steps` steps
+    fn ride(world: &mut PhysicsWorld, rider: u32, platform: u32, steps: usize) -> Vec<(Vector2, bool)> {
+        (0..steps)
+            .map(|_| {
+                world.step(FIXED_TIMESTEP);
+                let rider = world.get_body(rider).unwrap();
+                let platform = world.get_body(platform).unwrap();
+                (rider.position - platform.position, rider.on_ground)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn riders_move_with_horizontal_platforms() {
+        let mut world = PhysicsWorld::new();
+        let path = Path::new(vec![Vector2::new(100.0, 100.0), Vector2::new(300.0, 100.0)], PathMode::PingPong, 90.0);
+        let platform = add_platform(&mut world, path);
+        let rider = add_box(&mut world, 120.0, 84.0);
+        world.step(FIXED_TIMESTEP);
+
+        let start = world.get_body(rider).unwrap().position - world.get_body(platform).unwrap().position;
+        for (offset, on_ground) in ride(&mut world, rider, platform, 300) {
+            assert!(on_ground);
+            assert_relative_eq!(offset.x, start.x, epsilon = 1e-3);
+            assert_relative_eq!(offset.y, -16.0, epsilon = 1e-3);
+        }
+    }
+
+    #[test]
+    fn riders_move_with_vertical_platforms() {
+        let mut world = PhysicsWorld::new();
+        let path = Path::new(vec![Vector2::new(100.0, 200.0), Vector2::new(100.0, 100.0)], PathMode::PingPong, 120.0);
+        let platform = add_platform(&mut world, path);
+        let rider = add_box(&mut world, 120.0, 184.0);
+        world.step(FIXED_TIMESTEP);
+
+        for (offset, on_ground) in ride(&mut world, rider, platform, 200) {
+            assert!(on_ground);
+            assert_relative_eq!(offset.y, -16.0, epsilon = 1e-3);
+        }
+    }
+
+    #[test]
+    fn triggered_platforms_start_when_landed_on() {
+        let mut world = PhysicsWorld::new();
+        let path = Path::new(vec![Vector2::new(100.0, 100.0), Vector2::new(100.0, 0.0)], PathMode::Triggered, 60.0);
+        let platform = add_platform(&mut world, path);
+        run(&mut world, 10);
+        assert_eq!(world.get_body(platform).unwrap().position, Vector2::new(100.0, 100.0));
+
+        let rider = add_box(&mut world, 120.0, 60.0);
+        run(&mut world, 30);
+        assert!(world.body_path(platform).unwrap().is_running());
+        assert!(world.get_body(platform).unwrap().position.y < 100.0);
+        assert!(world.get_body(rider).unwrap().on_ground);
+    }
+
+    fn phases(events: &[ContactEvent]) -> Vec<ContactPhase> {
+        events.iter().map(|event| event.phase).collect()
+    }
+
+    #[test]
+    fn contacts_begin_stay_and_end() {
+        let mut world = PhysicsWorld::new();
+        let ground = world.add_body(PhysicsBody::new(0, Vector2::new(0.0, 100.0), Vector2::new(200.0, 20.0), BodyType::Static));
+        let faller = add_box(&mut world, 50.0, 84.0);
+
+        run(&mut world, 3);
+        let events = world.take_contact_events();
+        assert_eq!(phases(&events), [ContactPhase::Begin, ContactPhase::Stay, ContactPhase::Stay]);
+        assert_eq!(events[0].other(faller), Some(ground));
+        assert_eq!(events[0].layer_of(ground), Some(CollisionLayer::World));
+        assert_eq!(events[0].normal_for(faller), Vector2::new(0.0, -1.0));
+        assert_eq!(events[0].normal_for(ground), Vector2::new(0.0, 1.0));
+        assert!(!events[0].sensor);
+        assert!(world.are_touching(ground, faller));
+
+        world.remove_body(ground);
+        assert_eq!(phases(&world.take_contact_events()), [ContactPhase::End]);
+    }
+
+    #[test]
+    fn bodies_stopped_by_a_moving_body_stay_in_contact() {
+        let mut world = PhysicsWorld::new();
+        let chaser = add_box(&mut world, 80.0, 0.0);
+        let mut leader = PhysicsBody::new(0, Vector2::new(100.0, 0.0), Vector2::new(16.0, 16.0), BodyType::Dynamic);
+        leader.collision_layer = CollisionLayer::Enemy;
+        leader.use_gravity = false;
+        leader.linear_damping = 1.0;
+        leader.velocity = Vector2::new(120.0, 0.0);
+        let leader = world.add_body(leader);
+
+        // The chaser moves first, so the leader is a little ahead again by
+        // the end of each step
+        for _ in 0..5 {
+            if let Some(body) = world.get_body_mut(chaser) {
+                body.use_gravity = false;
+                body.velocity = Vector2::new(600.0, 0.0);
+            }
+            world.step(FIXED_TIMESTEP);
+            assert!(world.are_touching(chaser, leader));
+        }
+    }
+
+    #[test]
+    fn sensors_report_contacts_without_blocking() {
+        let mut world = PhysicsWorld::new();
+        let mut sensor = PhysicsBody::new(0, Vector2::new(40.0, 100.0), Vector2::new(40.0, 20.0), BodyType::Static)
+            .with_collision_type(CollisionType::Sensor);
+        sensor.collision_layer = CollisionLayer::Item;
+        let sensor = world.add_body(sensor);
+        let faller = add_box(&mut world, 50.0, 60.0);
+
+        run(&mut world, 60);
+        let events = world.take_contact_events();
+        assert_eq!(events.first().map(|event| event.phase), Some(ContactPhase::Begin));
+        assert_eq!(events.last().map(|event| event.phase), Some(ContactPhase::End));
+        assert!(events.iter().all(|event| event.sensor && event.involves(sensor)));
+        assert!(world.get_body(faller).unwrap().position.y > 120.0);
+    }
+
+    /// Drop a box at `speed` onto a 20px thick platform body at y = 300 and return where it ends up
+    fn fall_onto(collision_type: CollisionType, speed: f32) -> (f32, bool) {
+        let mut world = PhysicsWorld::new();
+        let platform = PhysicsBody::new(0, Vector2::new(0.0, 300.0), Vector2::new(200.0, 20.0), BodyType::Static)
+            .with_collision_type(collision_type);
+        world.add_body(platform);
+
+        let id = add_box(&mut world, 50.0, 0.0);
+        let body = world.get_body_mut(id).unwrap();
+        body.max_velocity.y = speed;
+        body.velocity.y = speed;
+        run(&mut world, 60);
+
+        let body = world.get_body(id).unwrap();
+        (body.position.y, body.on_ground)
+    }
+
+    const FAST_SPEEDS: [f32; 4] = [TERMINAL_VELOCITY, TERMINAL_VELOCITY * 2.0, TERMINAL_VELOCITY * 5.0, TERMINAL_VELOCITY * 20.0];
+
+    #[test]
+    fn fast_bodies_land_on_thin_solid_platforms() {
+        for speed in FAST_SPEEDS {
+            assert_eq!(fall_onto(CollisionType::Solid, speed), (284.0, true), "speed {speed}");
+        }
+    }
+
+    #[test]
+    fn fast_bodies_land_on_thin_one_way_platforms() {
+        for speed in FAST_SPEEDS {
+            assert_eq!(fall_onto(CollisionType::Platform, speed), (284.0, true), "speed {speed}");
+        }
+    }
+
+    #[test]
+    fn fast_bodies_stop_at_thin_walls() {
+        for speed in FAST_SPEEDS {
+            let mut world = PhysicsWorld::new();
+            world.add_body(PhysicsBody::new(0, Vector2::new(300.0, 0.0), Vector2::new(20.0, 200.0), BodyType::Static));
+            let id = add_box(&mut world, 0.0, 50.0);
+            let body = world.get_body_mut(id).unwrap();
+            body.use_gravity = false;
+            body.max_velocity.x = speed;
+            for _ in 0..60 {
+                world.set_body_velocity(id, Vector2::new(speed, 0.0));
+                world.step(FIXED_TIMESTEP);
+            }
+            assert_eq!(world.get_body(id).unwrap().position.x, 284.0, "speed {speed}");
+        }
+    }
+
+    #[test]
+    fn fast_bodies_land_on_the_nearest_platform() {
+        let mut world = PhysicsWorld::new();
+        let upper = world.add_body(PhysicsBody::new(0, Vector2::new(0.0, 100.0), Vector2::new(200.0, 20.0), BodyType::Static));
+        world.add_body(PhysicsBody::new(0, Vector2::new(0.0, 130.0), Vector2::new(200.0, 20.0), BodyType::Static));
+        let id = add_box(&mut world, 50.0, 0.0);
+        let body = world.get_body_mut(id).unwrap();
+        body.max_velocity.y = 12000.0;
+        body.velocity.y = 12000.0;
+        world.step(FIXED_TIMESTEP);
+
+        let body = world.get_body(id).unwrap();
+        assert_eq!(body.position.y, 84.0);
+        assert_eq!(body.ground_body, Some(upper));
+    }
+
+    #[test]
+    fn fast_bodies_land_on_floor_slopes() {
+        let slope = SlopeType::floor(SlopeDirection::RisingRight, SlopeGradient::Steep);
+        let mut world = world_with_tiles(&[(4, 2, slope_tile(slope))]);
+        let id = add_box(&mut world, 136.0, -100.0);
+        let body = world.get_body_mut(id).unwrap();
+        body.max_velocity.y = TERMINAL_VELOCITY * 20.0;
+        body.velocity.y = TERMINAL_VELOCITY * 20.0;
+        run(&mut world, 10);
+
+        let body = world.get_body(id).unwrap();
+        assert_eq!((body.position.y, body.on_ground), (56.0, true));
+    }
+}
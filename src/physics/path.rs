@@ -0,0 +1,256 @@
+//! Waypoint paths for kinematic bodies in RustUX
+
+use crate::math::Vector2;
+use serde::{Deserialize, Serialize};
+
+/// How a body moves along its waypoints
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PathMode {
+    /// Travel from the first to the last waypoint once, then stop
+    Linear,
+    /// Travel the waypoints as a closed loop, last back to first
+    Loop,
+    /// Travel to the last waypoint, then back to the first, forever
+    PingPong,
+    /// Orbit the first waypoint, starting at the second one
+    Circular,
+    /// Wait at an end until triggered, then travel to the other end
+    Triggered,
+}
+
+/// Waypoints and movement settings for a kinematic body
+#[derive(Debug, Clone, PartialEq)]
+pub struct Path {
+    pub waypoints: Vec<Vector2>,
+    pub mode: PathMode,
+    /// Speed along the path (pixels/second)
+    pub speed: f32,
+}
+
+impl Path {
+    /// Create a new path
+    pub fn new(waypoints: Vec<Vector2>, mode: PathMode, speed: f32) -> Self {
+        Self { waypoints, mode, speed }
+    }
+
+    /// Create a circular path around `center`, starting at `start`
+    pub fn circle(center: Vector2, start: Vector2, speed: f32) -> Self {
+        Self::new(vec![center, start], PathMode::Circular, speed)
+    }
+}
+
+/// Current position of a body along its path
+#[derive(Debug, Clone)]
+pub struct PathFollower {
+    path: Path,
+    /// Index of the last waypoint passed
+    node: usize,
+    /// Distance travelled since the last waypoint
+    travelled: f32,
+    forward: bool,
+    running: bool,
+    /// Current angle around the center, for circular paths
+    angle: f32,
+}
+
+impl PathFollower {
+    /// Start following a path from its first waypoint
+    pub fn new(path: Path) -> Self {
+        let angle = match path.waypoints.as_slice() {
+            [center, start, ..] => (*start - *center).to_angle(),
+            _ => 0.0,
+        };
+        let running = path.mode != PathMode::Triggered;
+
+        Self {
+            path,
+            node: 0,
+            travelled: 0.0,
+            forward: true,
+            running,
+            angle,
+        }
+    }
+
+    /// Get the path being followed
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Check if the follower is currently moving
+    pub fn is_running(&self) -> bool {
+        self.running
+    }
+
+    /// Start moving a triggered path towards its other end
+    ///
+    /// Returns false if the path is already moving or isn't triggered.
+    pub fn trigger(&mut self) -> bool {
+        if self.path.mode != PathMode::Triggered || self.running {
+            return false;
+        }
+        self.running = true;
+        true
+    }
+
+    /// Get the current position on the path
+    pub fn position(&self) -> Vector2 {
+        let waypoints = &self.path.waypoints;
+        if self.path.mode == PathMode::Circular {
+            return match waypoints.as_slice() {
+                [center, start, ..] => *center + Vector2::from_angle(self.angle) * start.distance(*center),
+                [center] => *center,
+                [] => Vector2::ZERO,
+            };
+        }
+
+        let from = match waypoints.get(self.node) {
+            Some(from) => *from,
+            None => return Vector2::ZERO,
+        };
+        match self.next_node() {
+            Some(next) => {
+                let to = waypoints[next];
+                let length = from.distance(to);
+                if length > 0.0 {
+                    from.lerp(to, self.travelled / length)
+                } else {
+                    from
+                }
+            }
+            None => from,
+        }
+    }
+
+    /// Advance along the path and return the new position
+    pub fn advance(&mut self, delta_time: f32) -> Vector2 {
+        if !self.running || self.path.waypoints.len() < 2 {
+            return self.position();
+        }
+
+        if self.path.mode == PathMode::Circular {
+            let radius = self.path.waypoints[1].distance(self.path.waypoints[0]);
+            if radius > 0.0 {
+                self.angle = (self.angle + self.path.speed * delta_time / radius) % std::f32::consts::TAU;
+            }
+            return self.position();
+        }
+
+        let mut remaining = self.path.speed * delta_time;
+        // Bounded so paths made of identical waypoints can't spin forever
+        for _ in 0..=self.path.waypoints.len() * 2 {
+            let next = match self.next_node() {
+                Some(next) => next,
+                None => break,
+            };
+
+            let length = self.path.waypoints[self.node].distance(self.path.waypoints[next]);
+            if self.travelled + remaining < length {
+                self.travelled += remaining;
+                break;
+            }
+
+            remaining -= length - self.travelled;
+            self.travelled = 0.0;
+            self.node = next;
+
+            if self.next_node().is_none() {
+                match self.path.mode {
+                    PathMode::PingPong => self.forward = !self.forward,
+                    PathMode::Triggered => {
+                        self.forward = !self.forward;
+                        self.running = false;
+                        break;
+                    }
+                    _ => {
+                        self.running = false;
+                        break;
+                    }
+                }
+            }
+        }
+
+        self.position()
+    }
+
+    /// Get the waypoint being travelled towards
+    fn next_node(&self) -> Option<usize> {
+        let count = self.path.waypoints.len();
+        if self.forward {
+            if self.node + 1 < count {
+                Some(self.node + 1)
+            } else if self.path.mode == PathMode::Loop && count > 1 {
+                Some(0)
+            } else {
+                None
+            }
+        } else {
+            self.node.checked_sub(1)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use approx::assert_relative_eq;
+
+    fn line(mode: PathMode) -> PathFollower {
+        PathFollower::new(Path::new(vec![Vector2::new(0.0, 0.0), Vector2::new(100.0, 0.0)], mode, 50.0))
+    }
+
+    #[test]
+    fn linear_stops_at_the_end() {
+        let mut follower = line(PathMode::Linear);
+        assert_eq!(follower.advance(1.0), Vector2::new(50.0, 0.0));
+        assert_eq!(follower.advance(3.0), Vector2::new(100.0, 0.0));
+        assert!(!follower.is_running());
+    }
+
+    #[test]
+    fn ping_pong_turns_around() {
+        let mut follower = line(PathMode::PingPong);
+        assert_eq!(follower.advance(2.5), Vector2::new(75.0, 0.0));
+        assert_eq!(follower.advance(2.0), Vector2::new(25.0, 0.0));
+        assert!(follower.is_running());
+    }
+
+    #[test]
+    fn loop_returns_to_the_first_waypoint() {
+        let square = vec![
+            Vector2::new(0.0, 0.0),
+            Vector2::new(10.0, 0.0),
+            Vector2::new(10.0, 10.0),
+            Vector2::new(0.0, 10.0),
+        ];
+        let mut follower = PathFollower::new(Path::new(square, PathMode::Loop, 10.0));
+        assert_eq!(follower.advance(3.5), Vector2::new(0.0, 5.0));
+        assert_eq!(follower.advance(1.0), Vector2::new(5.0, 0.0));
+    }
+
+    #[test]
+    fn circular_orbits_the_center() {
+        let mut follower = PathFollower::new(Path::circle(Vector2::ZERO, Vector2::new(10.0, 0.0), 10.0 * std::f32::consts::FRAC_PI_2));
+        assert_eq!(follower.position(), Vector2::new(10.0, 0.0));
+        let position = follower.advance(1.0);
+        assert_relative_eq!(position.x, 0.0, epsilon = 1e-4);
+        assert_relative_eq!(position.y, 10.0, epsilon = 1e-4);
+    }
+
+    #[test]
+    fn triggered_waits_and_alternates_ends() {
+        let mut follower = line(PathMode::Triggered);
+        assert_eq!(follower.advance(1.0), Vector2::ZERO);
+
+        assert!(follower.trigger());
+        assert!(!follower.trigger());
+        assert_eq!(follower.advance(3.0), Vector2::new(100.0, 0.0));
+        assert!(!follower.is_running());
+
+        assert!(follower.trigger());
+        assert_eq!(follower.advance(1.0), Vector2::new(50.0, 0.0));
+        assert_eq!(follower.advance(1.0), Vector2::new(0.0, 0.0));
+        assert!(!follower.is_running());
+    }
+}
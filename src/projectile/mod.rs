@@ -0,0 +1,477 @@
+//! Player projectiles for RustUX
+//!
+//! Fire Tux throws fireballs that bounce along the ground; Ice Tux throws
+//! iceballs that fly straight and freeze what they hit. Only a few can be
+//! in the air at once, and each one is gone when it hits a wall, runs out
+//! of time or leaves the camera.
+
+use crate::collision::{CollisionLayer, CollisionType};
+use crate::math::{Rect, Vector2};
+use crate::object::{factory, Component, GameObjectManager, ObjectId, PhysicsComponent, SpriteComponent};
+use crate::physics::{BodyType, ContactEvent, ContactPhase, PhysicsBody, PhysicsWorld, CONTACT_MARGIN};
+use crate::sprite::Sprite;
+use serde::{Deserialize, Serialize};
+use std::any::Any;
+
+/// Size of a projectile
+pub const PROJECTILE_SIZE: f32 = 16.0;
+
+/// How many projectiles the player can have in the air by default
+pub const MAX_PROJECTILES: usize = 2;
+
+/// How far outside the camera view a projectile may go before it is removed
+const OFFSCREEN_MARGIN: f32 = 64.0;
+
+/// Kinds of projectile the player can throw
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ProjectileKind {
+    Fireball,
+    Iceball,
+}
+
+impl ProjectileKind {
+    pub const ALL: [ProjectileKind; 2] = [ProjectileKind::Fireball, ProjectileKind::Iceball];
+
+    /// Texture the projectile draws with
+    pub fn texture_name(self) -> &'static str {
+        match self {
+            ProjectileKind::Fireball => "fireball",
+            ProjectileKind::Iceball => "iceball",
+        }
+    }
+
+    /// Image file of the texture
+    pub fn texture_path(self) -> String {
+        format!("assets/sprites/objects/bullets/{}.png", self.texture_name())
+    }
+
+    /// Horizontal speed
+    pub fn speed(self) -> f32 {
+        match self {
+            ProjectileKind::Fireball => 350.0,
+            ProjectileKind::Iceball => 300.0,
+        }
+    }
+
+    /// Upward speed after touching the ground, or `None` for projectiles that fly straight
+    pub fn bounce_speed(self) -> Option<f32> {
+        match self {
+            ProjectileKind::Fireball => Some(200.0),
+            ProjectileKind::Iceball => None,
+        }
+    }
+
+    /// Seconds before the projectile burns out
+    pub fn lifetime(self) -> f32 {
+        match self {
+            ProjectileKind::Fireball => 2.0,
+            ProjectileKind::Iceball => 1.5,
+        }
+    }
+}
+
+/// Projectile component
+#[derive(Debug, Clone)]
+pub struct Projectile {
+    pub kind: ProjectileKind,
+    /// -1.0 when flying left, 1.0 when flying right
+    pub direction: f32,
+    /// Seconds left before the projectile disappears
+    pub time_left: f32,
+}
+
+impl Projectile {
+    pub fn new(kind: ProjectileKind, direction: f32) -> Self {
+        Self {
+            kind,
+            direction: direction.signum(),
+            time_left: kind.lifetime(),
+        }
+    }
+}
+
+impl Component for Projectile {
+    fn as_any(&self) -> &dyn Any { self }
+    fn as_any_mut(&mut self) -> &mut dyn Any { self }
+    fn clone_component(&self) -> Box<dyn Component> { Box::new(self.clone()) }
+}
+
+/// Projectile system that spawns, moves and removes projectiles
+pub struct ProjectileSystem {
+    projectiles: Vec<ObjectId>,
+    max_on_screen: usize,
+}
+
+impl ProjectileSystem {
+    pub fn new() -> Self {
+        Self {
+            projectiles: Vec::new(),
+            max_on_screen: MAX_PROJECTILES,
+        }
+    }
+
+    pub fn with_max_on_screen(mut self, max_on_screen: usize) -> Self {
+        self.max_on_screen = max_on_screen;
+        self
+    }
+
+    /// Get the number of projectiles in the air
+    pub fn count(&self) -> usize {
+        self.projectiles.len()
+    }
+
+    /// Get the projectiles in the air
+    pub fn projectiles(&self) -> &[ObjectId] {
+        &self.projectiles
+    }
+
+    /// Throw a projectile centered on `position`
+    ///
+    /// Returns `None` when the limit of projectiles in the air is reached.
+    pub fn spawn(
+        &mut self,
+        object_manager: &mut GameObjectManager,
+        physics_world: &mut PhysicsWorld,
+        kind: ProjectileKind,
+        position: Vector2,
+        facing_right: bool,
+    ) -> Option<ObjectId> {
+        if self.projectiles.len() >= self.max_on_screen {
+            return None;
+        }
+
+        let size = Vector2::splat(PROJECTILE_SIZE);
+        let top_left = position - size * 0.5;
+        let direction = if facing_right { 1.0 } else { -1.0 };
+        let id = factory::create_physics_object(
+            object_manager,
+            physics_world,
+            format!("{:?}", kind),
+            top_left,
+            size,
+            BodyType::Dynamic,
+            CollisionLayer::Projectile,
+        );
+
+        // Nothing is blocked by a projectile, but projectiles are blocked by walls
+        let body_id = object_manager.get_object(id)?.get_component::<PhysicsComponent>()?.body_id;
+        physics_world.set_body_collision_type(body_id, CollisionType::Sensor);
+        if let Some(body) = physics_world.get_body_mut(body_id) {
+            body.use_gravity = kind.bounce_speed().is_some();
+            body.linear_damping = 1.0;
+            body.velocity = Vector2::new(direction * kind.speed(), 0.0);
+        }
+
+        if let Some(object) = object_manager.get_object_mut(id) {
+            let sprite = Sprite::with_size(kind.texture_name().to_string(), Vector2::ZERO, size);
+            object.add_component(SpriteComponent::new(sprite));
+            object.add_component(Projectile::new(kind, direction));
+            object.tag = "projectile".to_string();
+        }
+
+        self.projectiles.push(id);
+        Some(id)
+    }
+
+    /// Find the enemy body each projectile touched in this step's contacts
+    ///
+    /// Enemies are solid, so a projectile flying into one is stopped just
+    /// short of it; the physics world still reports the two as touching.
+    pub fn hits(&self, object_manager: &GameObjectManager, contacts: &[ContactEvent]) -> Vec<(ObjectId, u32)> {
+        let mut hits = Vec::new();
+        for &id in &self.projectiles {
+            let body_id = match object_manager.get_object(id).and_then(|object| object.get_component::<PhysicsComponent>()) {
+                Some(physics) => physics.body_id,
+                None => continue,
+            };
+
+            let enemy = contacts
+                .iter()
+                .filter(|event| event.phase != ContactPhase::End)
+                .filter_map(|event| {
+                    event
+                        .other(body_id)
+                        .filter(|&other| event.layer_of(other) == Some(CollisionLayer::Enemy))
+                })
+                .min();
+            if let Some(enemy) = enemy {
+                hits.push((id, enemy));
+            }
+        }
+        hits
+    }
+
+    /// Check if a projectile has run into a wall
+    ///
+    /// Solid bodies touching it from the side report a contact; tiles have
+    /// no bodies, so a projectile stopped with a solid tile just ahead of it
+    /// has hit a wall too. Enemies stop projectiles as well but are hits, not walls.
+    fn hit_wall(physics_world: &PhysicsWorld, body: &PhysicsBody, direction: f32, contacts: &[ContactEvent]) -> bool {
+        let against_body = contacts.iter().filter(|event| event.phase != ContactPhase::End).any(|event| {
+            event.other(body.id).is_some_and(|other| {
+                !matches!(event.layer_of(other), Some(CollisionLayer::Enemy | CollisionLayer::Player))
+                    && physics_world.get_body(other).is_some_and(|other| other.collision_type == CollisionType::Solid)
+                    && event.normal_for(body.id).x != 0.0
+            })
+        });
+
+        let rect = body.get_rect();
+        let ahead = Rect::new(
+            if direction > 0.0 { rect.right() } else { rect.left() - CONTACT_MARGIN },
+            rect.top() + CONTACT_MARGIN,
+            CONTACT_MARGIN,
+            rect.height - CONTACT_MARGIN * 2.0,
+        );
+        let against_tile = body.velocity.x == 0.0 && physics_world.is_solid_in_rect(&ahead);
+
+        against_body || against_tile
+    }
+
+    /// Move projectiles along and remove those that are spent
+    ///
+    /// `contacts` are the contact events of this step's physics update and
+    /// `view` is the world area on screen; projectiles well outside it are removed.
+    pub fn update(
+        &mut self,
+        object_manager: &mut GameObjectManager,
+        physics_world: &mut PhysicsWorld,
+        contacts: &[ContactEvent],
+        view: Rect,
+        delta_time: f32,
+    ) {
+        let bounds = view.expand(OFFSCREEN_MARGIN);
+        let mut spent = Vec::new();
+
+        for &id in &self.projectiles {
+            let (projectile, body_id) = match object_manager.get_object_mut(id) {
+                Some(object) => {
+                    let body_id = object.get_component::<PhysicsComponent>().map(|physics| physics.body_id);
+                    match (object.get_component_mut::<Projectile>(), body_id) {
+                        (Some(projectile), Some(body_id)) => (projectile, body_id),
+                        _ => {
+                            spent.push(id);
+                            continue;
+                        }
+                    }
+                }
+                None => {
+                    spent.push(id);
+                    continue;
+                }
+            };
+
+            projectile.time_left -= delta_time;
+
+            // A wall puts the projectile out
+            let hit_wall = match physics_world.get_body(body_id) {
+                Some(body) => Self::hit_wall(physics_world, body, projectile.direction, contacts),
+                None => false,
+            };
+            let body = match physics_world.get_body_mut(body_id) {
+                Some(body) => body,
+                None => {
+                    spent.push(id);
+                    continue;
+                }
+            };
+
+            if projectile.time_left <= 0.0 || hit_wall || !bounds.intersects(&body.get_rect()) {
+                spent.push(id);
+                continue;
+            }
+
+            body.velocity.x = projectile.direction * projectile.kind.speed();
+            if let Some(bounce_speed) = projectile.kind.bounce_speed() {
+                if body.on_ground {
+                    body.velocity.y = -bounce_speed;
+                }
+            }
+        }
+
+        for id in spent {
+            self.despawn(object_manager, physics_world, id);
+        }
+    }
+
+    /// Remove a projectile and its body from the world
+    pub fn despawn(&mut self, object_manager: &mut GameObjectManager, physics_world: &mut PhysicsWorld, id: ObjectId) {
+        self.projectiles.retain(|&projectile| projectile != id);
+        if let Some(object) = object_manager.remove_object(id) {
+            if let Some(physics) = object.get_component::<PhysicsComponent>() {
+                physics_world.remove_body(physics.body_id);
+            }
+        }
+    }
+
+    /// Remove every projectile
+    pub fn clear(&mut self, object_manager: &mut GameObjectManager, physics_world: &mut PhysicsWorld) {
+        for id in std::mem::take(&mut self.projectiles) {
+            self.despawn(object_manager, physics_world, id);
+        }
+    }
+}
+
+impl Default for ProjectileSystem {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::badguy::{self, BadguyAI, BadguyState};
+    use crate::control::{GameAction, InputManager};
+    use crate::physics::PhysicsBody;
+    use crate::supertux::{GameWorld, PlayerController, TuxForm};
+
+    const DT: f32 = 1.0 / 60.0;
+
+    fn add_ground(physics_world: &mut PhysicsWorld) {
+        physics_world.add_body(PhysicsBody::new(0, Vector2::new(0.0, 200.0), Vector2::new(2000.0, 32.0), BodyType::Static));
+    }
+
+    /// Press and release Action for one update
+    fn throw(world: &mut GameWorld, input: &mut InputManager) {
+        input.press_for_test(GameAction::Action);
+        world.update(input, DT).unwrap();
+        input.update();
+        input.release_for_test(GameAction::Action);
+    }
+
+    fn fly(world: &mut GameWorld, input: &InputManager, frames: usize) {
+        for _ in 0..frames {
+            world.update(input, DT).unwrap();
+        }
+    }
+
+    #[test]
+    fn projectiles_are_limited_and_burn_out() {
+        let mut object_manager = GameObjectManager::new();
+        let mut physics_world = PhysicsWorld::new();
+        let mut system = ProjectileSystem::new();
+        let view = Rect::new(0.0, 0.0, 1000.0, 1000.0);
+
+        for _ in 0..MAX_PROJECTILES {
+            assert!(system.spawn(&mut object_manager, &mut physics_world, ProjectileKind::Iceball, Vector2::new(100.0, 100.0), true).is_some());
+        }
+        assert!(system.spawn(&mut object_manager, &mut physics_world, ProjectileKind::Iceball, Vector2::new(100.0, 100.0), true).is_none());
+
+        system.update(&mut object_manager, &mut physics_world, &[], view, ProjectileKind::Iceball.lifetime() + DT);
+        assert_eq!(system.count(), 0);
+        assert_eq!(physics_world.body_count(), 0);
+
+        // Leaving the camera view removes a projectile too
+        system.spawn(&mut object_manager, &mut physics_world, ProjectileKind::Iceball, Vector2::new(100.0, 100.0), false);
+        system.update(&mut object_manager, &mut physics_world, &[], Rect::new(500.0, 0.0, 100.0, 100.0), DT);
+        assert_eq!(system.count(), 0);
+    }
+
+    #[test]
+    fn fireballs_bounce_along_the_ground() {
+        let mut object_manager = GameObjectManager::new();
+        let mut physics_world = PhysicsWorld::new();
+        add_ground(&mut physics_world);
+        let mut system = ProjectileSystem::new();
+        let view = Rect::new(0.0, 0.0, 2000.0, 1000.0);
+
+        let id = system.spawn(&mut object_manager, &mut physics_world, ProjectileKind::Fireball, Vector2::new(100.0, 150.0), true).unwrap();
+        let body_id = object_manager.get_object(id).unwrap().get_component::<PhysicsComponent>().unwrap().body_id;
+
+        let mut bounces = 0;
+        let mut was_rising = false;
+        for _ in 0..60 {
+            physics_world.update(DT);
+            system.update(&mut object_manager, &mut physics_world, &[], view, DT);
+            let body = physics_world.get_body(body_id).unwrap();
+            assert!(body.get_rect().bottom() <= 200.0 + 0.01);
+            let rising = body.velocity.y < 0.0;
+            if rising && !was_rising {
+                bounces += 1;
+            }
+            was_rising = rising;
+        }
+
+        assert!(bounces >= 2, "bounced {} times", bounces);
+        let body = physics_world.get_body(body_id).unwrap();
+        assert!((body.position.x - (100.0 + ProjectileKind::Fireball.speed() - PROJECTILE_SIZE * 0.5)).abs() < 10.0);
+    }
+
+    #[test]
+    fn only_walls_put_projectiles_out() {
+        let mut object_manager = GameObjectManager::new();
+        let mut physics_world = PhysicsWorld::new();
+        let mut system = ProjectileSystem::new();
+        let view = Rect::new(0.0, 0.0, 1000.0, 1000.0);
+
+        // An enemy stops the iceball, which is a hit rather than a wall
+        let mut enemy = PhysicsBody::new(0, Vector2::new(130.0, 80.0), Vector2::new(32.0, 32.0), BodyType::Static);
+        enemy.collision_layer = CollisionLayer::Enemy;
+        let enemy = physics_world.add_body(enemy);
+        let id = system.spawn(&mut object_manager, &mut physics_world, ProjectileKind::Iceball, Vector2::new(100.0, 100.0), true).unwrap();
+        let mut hits = Vec::new();
+        for _ in 0..10 {
+            physics_world.update(DT);
+            let contacts = physics_world.take_contact_events();
+            hits = system.hits(&object_manager, &contacts);
+            system.update(&mut object_manager, &mut physics_world, &contacts, view, DT);
+        }
+        assert_eq!(hits, vec![(id, enemy)]);
+        assert_eq!(system.count(), 1);
+
+        // A solid wall puts it out
+        physics_world.remove_body(enemy);
+        physics_world.add_body(PhysicsBody::new(0, Vector2::new(140.0, 0.0), Vector2::new(32.0, 200.0), BodyType::Static));
+        for _ in 0..5 {
+            physics_world.update(DT);
+            let contacts = physics_world.take_contact_events();
+            assert!(system.hits(&object_manager, &contacts).is_empty());
+            system.update(&mut object_manager, &mut physics_world, &contacts, view, DT);
+        }
+        assert_eq!(system.count(), 0);
+    }
+
+    #[test]
+    fn projectiles_respect_badguy_immunities() {
+        let mut world = GameWorld::new();
+        world.create_player(Vector2::new(100.0, 168.0), "tux".to_string());
+        let (object_manager, physics_world) = world.managers_mut();
+        add_ground(physics_world);
+        let goomba = badguy::factory::create_goomba(object_manager, physics_world, Vector2::new(250.0, 168.0), "goomba".to_string());
+
+        let mut input = InputManager::new().unwrap();
+        fly(&mut world, &input, 2);
+
+        // Small Tux has nothing to throw
+        throw(&mut world, &mut input);
+        assert_eq!(world.projectile_system().count(), 0);
+
+        // Fire Tux burns the goomba
+        world.set_player_form(TuxForm::Fire);
+        throw(&mut world, &mut input);
+        assert_eq!(world.projectile_system().count(), 1);
+        fly(&mut world, &input, 30);
+        assert!(!world.object_manager().get_object(goomba).unwrap().active);
+        assert_eq!(world.get_player().and_then(|p| p.get_component::<PlayerController>()).unwrap().score, 100);
+        assert_eq!(world.projectile_system().count(), 0);
+
+        // Ice Tux freezes a goomba but not a flying badguy
+        let (object_manager, physics_world) = world.managers_mut();
+        let goomba = badguy::factory::create_goomba(object_manager, physics_world, Vector2::new(250.0, 168.0), "goomba".to_string());
+        world.set_player_form(TuxForm::Ice);
+        throw(&mut world, &mut input);
+        fly(&mut world, &input, 40);
+        let state = |world: &GameWorld, id| world.object_manager().get_object(id).and_then(|o| o.get_component::<BadguyAI>()).unwrap().state;
+        assert_eq!(state(&world, goomba), BadguyState::Frozen);
+        assert!(world.object_manager().get_object(goomba).unwrap().active);
+
+        let (object_manager, physics_world) = world.managers_mut();
+        physics_world.remove_body(object_manager.get_object(goomba).unwrap().get_component::<PhysicsComponent>().unwrap().body_id);
+        let flying = badguy::factory::create_flying(object_manager, physics_world, Vector2::new(250.0, 160.0), "flying".to_string());
+        throw(&mut world, &mut input);
+        fly(&mut world, &input, 60);
+        assert_ne!(state(&world, flying), BadguyState::Frozen);
+        assert_eq!(world.projectile_system().count(), 0);
+    }
+}
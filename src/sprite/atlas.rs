@@ -0,0 +1,595 @@
+//! Texture atlas packing for RustUX
+//!
+//! Packs the frame images of sprite definitions into a few large textures
+//! ("pages"). All frames of one definition share a page, so a sprite keeps a
+//! single texture and its animation frames only differ in source rectangle.
+//! The packed pages and layout can be saved to a cache directory and are
+//! reused as long as the frame images on disk don't change.
+
+use crate::assets::SpriteDefinition;
+use crate::math::{Rect, Vector2};
+use crate::sprite::{Animation, AnimationFrame, Sprite};
+use crate::util::{Error, Result};
+use crate::video::VideoManager;
+use image::RgbaImage;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::{Path, PathBuf};
+
+/// Default width and height of an atlas page
+pub const DEFAULT_PAGE_SIZE: u32 = 1024;
+
+/// Prefix of the texture names atlas pages are loaded under
+pub const ATLAS_TEXTURE_PREFIX: &str = "__atlas_";
+
+/// File the layout is stored in inside a cache directory
+const LAYOUT_FILE: &str = "atlas.json";
+
+/// Where a frame image was packed
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct AtlasEntry {
+    /// Index of the page holding the frame
+    pub page: usize,
+    /// Pixel rectangle of the frame on its page
+    pub rect: Rect,
+}
+
+/// Size and modification time of a frame image, to notice stale caches
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SourceStamp {
+    pub len: u64,
+    pub modified: u64,
+}
+
+impl SourceStamp {
+    fn of(path: &Path) -> Option<Self> {
+        let metadata = std::fs::metadata(path).ok()?;
+        let modified = metadata.modified().ok()?
+            .duration_since(std::time::UNIX_EPOCH).ok()?
+            .as_secs();
+        Some(Self {
+            len: metadata.len(),
+            modified,
+        })
+    }
+}
+
+/// Result of packing: page sizes and the placement of every frame
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct AtlasLayout {
+    pub page_size: u32,
+    pub padding: u32,
+    /// Used size of each page in pixels
+    pub pages: Vec<(u32, u32)>,
+    /// Frame placements, keyed by the frame's path relative to the asset root
+    pub frames: BTreeMap<String, AtlasEntry>,
+    /// Stamps of the frame images the layout was built from
+    #[serde(default)]
+    pub sources: BTreeMap<String, SourceStamp>,
+}
+
+impl AtlasLayout {
+    /// Pack groups of (key, width, height) frames, keeping each group on one page
+    ///
+    /// A frame shared with an earlier group is packed once, so the rest of a
+    /// group sharing it has to fit on that frame's page.
+    pub fn pack(groups: &[Vec<(String, u32, u32)>], page_size: u32, padding: u32) -> Result<Self> {
+        let mut layout = Self {
+            page_size,
+            padding,
+            ..Self::default()
+        };
+        let mut packers: Vec<ShelfPacker> = Vec::new();
+
+        for group in groups {
+            let mut frames: Vec<&(String, u32, u32)> = Vec::new();
+            for frame in group {
+                if !layout.frames.contains_key(&frame.0) && !frames.iter().any(|other| other.0 == frame.0) {
+                    frames.push(frame);
+                }
+            }
+            // Tallest first, so shelves waste less space
+            frames.sort_by_key(|(_, _, height)| std::cmp::Reverse(*height));
+
+            let shared_pages: BTreeSet<usize> = group.iter()
+                .filter_map(|(key, _, _)| layout.frames.get(key))
+                .map(|entry| entry.page)
+                .collect();
+            if shared_pages.len() > 1 {
+                return Err(Error::SpriteLoading(format!(
+                    "Sprite frames starting with {} share images packed on different atlas pages",
+                    group[0].0
+                )));
+            }
+            if frames.is_empty() {
+                continue;
+            }
+
+            // Go on the page of shared frames, or try the newest page first, then start a fresh one
+            let pages: Vec<usize> = match shared_pages.first() {
+                Some(&page) => vec![page],
+                None => [packers.len().checked_sub(1), Some(packers.len())].into_iter().flatten().collect(),
+            };
+            let mut placed = None;
+            for page in pages {
+                let mut packer = packers.get(page).cloned()
+                    .unwrap_or_else(|| ShelfPacker::new(page_size, padding));
+                let positions: Option<Vec<_>> = frames.iter()
+                    .map(|(_, width, height)| packer.insert(*width, *height))
+                    .collect();
+                if let Some(positions) = positions {
+                    placed = Some((page, packer, positions));
+                    break;
+                }
+            }
+
+            let Some((page, packer, positions)) = placed else {
+                if let Some(page) = shared_pages.first() {
+                    return Err(Error::SpriteLoading(format!(
+                        "Sprite frames starting with {} don't fit on atlas page {} with the images they share",
+                        group[0].0, page
+                    )));
+                }
+                let (key, width, height) = frames.iter().max_by_key(|(_, w, h)| w.max(h)).unwrap();
+                return Err(Error::SpriteLoading(format!(
+                    "Sprite frames don't fit on a {0}x{0} atlas page (largest is {1} at {2}x{3})",
+                    page_size, key, width, height
+                )));
+            };
+
+            if page == packers.len() {
+                packers.push(packer);
+            } else {
+                packers[page] = packer;
+            }
+            for ((key, width, height), (x, y)) in frames.into_iter().zip(positions) {
+                layout.frames.insert(key.clone(), AtlasEntry {
+                    page,
+                    rect: Rect::new(x as f32, y as f32, *width as f32, *height as f32),
+                });
+            }
+        }
+
+        layout.pages = packers.iter().map(ShelfPacker::used_size).collect();
+        Ok(layout)
+    }
+}
+
+/// Packs rectangles into rows ("shelves") from top to bottom
+#[derive(Debug, Clone)]
+struct ShelfPacker {
+    size: u32,
+    padding: u32,
+    /// (top, height, next free x) of each shelf
+    shelves: Vec<(u32, u32, u32)>,
+    bottom: u32,
+    used_width: u32,
+}
+
+impl ShelfPacker {
+    fn new(size: u32, padding: u32) -> Self {
+        Self {
+            size,
+            padding,
+            shelves: Vec::new(),
+            bottom: 0,
+            used_width: 0,
+        }
+    }
+
+    fn insert(&mut self, width: u32, height: u32) -> Option<(u32, u32)> {
+        let size = self.size;
+        let position = match self.shelves.iter_mut().find(|(_, shelf_height, x)| height <= *shelf_height && *x + width <= size) {
+            Some((top, _, x)) => {
+                let position = (*x, *top);
+                *x += width + self.padding;
+                position
+            }
+            None if width <= size && self.bottom + height <= size => {
+                let top = self.bottom;
+                self.shelves.push((top, height, width + self.padding));
+                self.bottom += height + self.padding;
+                (0, top)
+            }
+            None => return None,
+        };
+        self.used_width = self.used_width.max(position.0 + width);
+        Some(position)
+    }
+
+    fn used_size(&self) -> (u32, u32) {
+        let height = self.shelves.last().map_or(0, |(top, height, _)| top + height);
+        (self.used_width.max(1), height.max(1))
+    }
+}
+
+/// Packed atlas pages with the layout of their frames
+#[derive(Debug, Clone)]
+pub struct TextureAtlas {
+    layout: AtlasLayout,
+    pages: Vec<RgbaImage>,
+}
+
+impl TextureAtlas {
+    /// Get the packing layout
+    pub fn layout(&self) -> &AtlasLayout {
+        &self.layout
+    }
+
+    /// Get the page images
+    pub fn pages(&self) -> &[RgbaImage] {
+        &self.pages
+    }
+
+    /// Name a page's texture is loaded under
+    pub fn page_texture_name(page: usize) -> String {
+        format!("{}{}", ATLAS_TEXTURE_PREFIX, page)
+    }
+
+    /// Get the placement of a frame by its path relative to the asset root
+    pub fn frame(&self, key: &str) -> Option<&AtlasEntry> {
+        self.layout.frames.get(key)
+    }
+
+    /// Load every page as a texture
+    pub fn upload(&self, video: &mut VideoManager) -> Result<()> {
+        for (index, page) in self.pages.iter().enumerate() {
+            video.load_texture_rgba(&Self::page_texture_name(index), page.width(), page.height(), page.as_raw().clone())?;
+        }
+        log::info!("Uploaded {} atlas pages with {} frames", self.pages.len(), self.layout.frames.len());
+        Ok(())
+    }
+
+    /// Unload every page texture
+    pub fn unload(&self, video: &mut VideoManager) {
+        for index in 0..self.pages.len() {
+            video.remove_texture(&Self::page_texture_name(index));
+        }
+    }
+
+    /// Build an animation whose frames point into the atlas, returning it
+    /// with the texture it has to be drawn from
+    pub fn animation(&self, definition: &SpriteDefinition, animation_name: &str) -> Result<(String, Animation)> {
+        let animation_def = definition.animations.get(animation_name)
+            .ok_or_else(|| Error::SpriteLoading(
+                format!("Animation '{}' not found in sprite definition '{}'", animation_name, definition.name)
+            ))?;
+
+        let mut page = None;
+        let mut frames = Vec::new();
+        for frame_def in &animation_def.frames {
+            let key = frame_key(definition, &frame_def.file_name);
+            let entry = self.frame(&key)
+                .ok_or_else(|| Error::SpriteLoading(format!("Frame '{}' is not in the atlas", key)))?;
+            if page.is_some_and(|page| page != entry.page) {
+                return Err(Error::SpriteLoading(format!(
+                    "Animation '{}' of '{}' has frames on different atlas pages",
+                    animation_name, definition.name
+                )));
+            }
+            page = Some(entry.page);
+            frames.push(AnimationFrame {
+                source_rect: entry.rect,
+                duration: frame_def.duration.unwrap_or(animation_def.frame_duration),
+            });
+        }
+
+        let page = page.ok_or_else(|| Error::SpriteLoading(
+            format!("Animation '{}' of '{}' has no frames", animation_name, definition.name)
+        ))?;
+        Ok((Self::page_texture_name(page), Animation::new(frames, animation_def.loops)))
+    }
+
+    /// Create a sprite drawing an animation of a definition from the atlas
+    pub fn create_sprite(&self, definition: &SpriteDefinition, position: Vector2, animation_name: &str) -> Result<Sprite> {
+        let (texture_name, animation) = self.animation(definition, animation_name)?;
+        let first = animation.frames[0].source_rect;
+        let size = Vector2::new(first.width, first.height);
+        let mut sprite = Sprite::with_size(texture_name, position, size);
+        sprite.set_animation(animation);
+        Ok(sprite)
+    }
+
+    /// Save the pages and layout into a cache directory
+    pub fn save<P: AsRef<Path>>(&self, dir: P) -> Result<()> {
+        let dir = dir.as_ref();
+        std::fs::create_dir_all(dir)?;
+        for (index, page) in self.pages.iter().enumerate() {
+            crate::video::golden::save_png(page, dir.join(format!("page-{}.png", index)))?;
+        }
+        std::fs::write(dir.join(LAYOUT_FILE), serde_json::to_string_pretty(&self.layout)?)?;
+        Ok(())
+    }
+
+    /// Load pages and layout saved with `save`
+    pub fn load<P: AsRef<Path>>(dir: P) -> Result<Self> {
+        let dir = dir.as_ref();
+        let layout: AtlasLayout = serde_json::from_str(&std::fs::read_to_string(dir.join(LAYOUT_FILE))?)?;
+        let pages = (0..layout.pages.len())
+            .map(|index| {
+                let path = dir.join(format!("page-{}.png", index));
+                image::open(&path)
+                    .map(|page| page.to_rgba8())
+                    .map_err(|e| Error::SpriteLoading(format!("Failed to load atlas page {}: {}", path.display(), e)))
+            })
+            .collect::<Result<Vec<_>>>()?;
+        Ok(Self { layout, pages })
+    }
+}
+
+/// Collects sprite definitions and packs their frames into an atlas
+#[derive(Debug, Clone)]
+pub struct AtlasBuilder {
+    asset_root: PathBuf,
+    page_size: u32,
+    padding: u32,
+    definitions: Vec<SpriteDefinition>,
+}
+
+impl AtlasBuilder {
+    /// Create a builder reading frame images from `assets/`
+    pub fn new() -> Self {
+        Self {
+            asset_root: PathBuf::from("assets"),
+            page_size: DEFAULT_PAGE_SIZE,
+            padding: 1,
+            definitions: Vec::new(),
+        }
+    }
+
+    /// Read frame images relative to another directory
+    pub fn with_asset_root<P: AsRef<Path>>(mut self, asset_root: P) -> Self {
+        self.asset_root = asset_root.as_ref().to_path_buf();
+        self
+    }
+
+    /// Set the width and height of atlas pages
+    pub fn with_page_size(mut self, page_size: u32) -> Self {
+        self.page_size = page_size;
+        self
+    }
+
+    /// Set the empty space kept between frames
+    pub fn with_padding(mut self, padding: u32) -> Self {
+        self.padding = padding;
+        self
+    }
+
+    /// Add a sprite definition whose frames should be packed
+    pub fn add_definition(&mut self, definition: &SpriteDefinition) {
+        self.definitions.push(definition.clone());
+    }
+
+    /// Frame keys of every definition, in a stable order
+    fn frame_groups(&self) -> Vec<Vec<String>> {
+        self.definitions.iter()
+            .map(|definition| {
+                let animations: BTreeMap<_, _> = definition.animations.iter().collect();
+                let mut keys = Vec::new();
+                for animation in animations.values() {
+                    for frame in &animation.frames {
+                        let key = frame_key(definition, &frame.file_name);
+                        if !keys.contains(&key) {
+                            keys.push(key);
+                        }
+                    }
+                }
+                keys
+            })
+            .collect()
+    }
+
+    /// Stamps of every frame image that exists on disk
+    fn source_stamps(&self) -> BTreeMap<String, SourceStamp> {
+        self.frame_groups().into_iter()
+            .flatten()
+            .filter_map(|key| SourceStamp::of(&self.asset_root.join(&key)).map(|stamp| (key, stamp)))
+            .collect()
+    }
+
+    /// Load the frame images and pack them; unreadable frames are skipped
+    pub fn build(&self) -> Result<TextureAtlas> {
+        let mut images = BTreeMap::new();
+        let mut groups = Vec::new();
+        for keys in self.frame_groups() {
+            let mut group = Vec::new();
+            for key in keys {
+                if !images.contains_key(&key) {
+                    match image::open(self.asset_root.join(&key)) {
+                        Ok(image) => {
+                            images.insert(key.clone(), image.to_rgba8());
+                        }
+                        Err(e) => {
+                            log::warn!("Skipping atlas frame {}: {}", key, e);
+                            continue;
+                        }
+                    }
+                }
+                let image = &images[&key];
+                group.push((key, image.width(), image.height()));
+            }
+            groups.push(group);
+        }
+
+        let mut layout = AtlasLayout::pack(&groups, self.page_size, self.padding)?;
+        layout.sources = self.source_stamps();
+
+        let mut pages: Vec<_> = layout.pages.iter().map(|(width, height)| RgbaImage::new(*width, *height)).collect();
+        for (key, entry) in &layout.frames {
+            let rect = entry.rect;
+            image::imageops::replace(&mut pages[entry.page], &images[key], rect.x as i64, rect.y as i64);
+        }
+
+        log::info!("Packed {} sprite frames into {} atlas pages", layout.frames.len(), pages.len());
+        Ok(TextureAtlas { layout, pages })
+    }
+
+    /// Load the atlas from a cache directory if it is up to date, or build
+    /// it and refresh the cache
+    pub fn build_cached<P: AsRef<Path>>(&self, cache_dir: P) -> Result<TextureAtlas> {
+        let cache_dir = cache_dir.as_ref();
+        match TextureAtlas::load(cache_dir) {
+            Ok(atlas) if self.is_current(atlas.layout()) => {
+                log::debug!("Using cached atlas from {}", cache_dir.display());
+                return Ok(atlas);
+            }
+            Ok(_) => log::info!("Atlas cache in {} is stale, rebuilding", cache_dir.display()),
+            Err(e) => log::debug!("No usable atlas cache in {}: {}", cache_dir.display(), e),
+        }
+
+        let atlas = self.build()?;
+        if let Err(e) = atlas.save(cache_dir) {
+            log::warn!("Failed to cache atlas in {}: {}", cache_dir.display(), e);
+        }
+        Ok(atlas)
+    }
+
+    /// Check if a cached layout was built from the current frames and settings
+    fn is_current(&self, layout: &AtlasLayout) -> bool {
+        let sources = self.source_stamps();
+        layout.page_size == self.page_size
+            && layout.padding == self.padding
+            && layout.sources == sources
+            && sources.keys().all(|key| layout.frames.contains_key(key))
+    }
+}
+
+impl Default for AtlasBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Path of a definition's frame image relative to the asset root
+fn frame_key(definition: &SpriteDefinition, file_name: &str) -> String {
+    format!("{}/{}", definition.texture_path.trim_end_matches('/'), file_name)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::assets::{AnimationDefinition, FrameDefinition};
+    use image::Rgba;
+    use std::collections::HashMap;
+
+    fn group(name: &str, sizes: &[(u32, u32)]) -> Vec<(String, u32, u32)> {
+        sizes.iter().enumerate().map(|(i, (w, h))| (format!("{}/{}.png", name, i), *w, *h)).collect()
+    }
+
+    fn overlaps(a: &Rect, b: &Rect) -> bool {
+        a.left() < b.right() && b.left() < a.right() && a.top() < b.bottom() && b.top() < a.bottom()
+    }
+
+    #[test]
+    fn definitions_stay_on_one_page_without_overlapping() {
+        let groups = vec![
+            group("tux", &[(30, 30), (30, 30), (20, 20)]),
+            group("snowball", &[(60, 20), (60, 20)]),
+            group("coin", &[(16, 16)]),
+        ];
+        let layout = AtlasLayout::pack(&groups, 64, 1).unwrap();
+
+        assert_eq!(layout.frames.len(), 6);
+        assert_eq!(layout.pages.len(), 2);
+        let page_of = |name: &str| -> Vec<usize> {
+            layout.frames.iter().filter(|(key, _)| key.starts_with(name)).map(|(_, e)| e.page).collect()
+        };
+        assert!(page_of("tux").iter().all(|page| *page == page_of("tux")[0]));
+        assert!(page_of("snowball").iter().all(|page| *page == page_of("snowball")[0]));
+
+        let entries: Vec<_> = layout.frames.values().collect();
+        for (i, a) in entries.iter().enumerate() {
+            let (width, height) = layout.pages[a.page];
+            assert!(a.rect.right() <= width as f32 && a.rect.bottom() <= height as f32);
+            for b in &entries[i + 1..] {
+                assert!(a.page != b.page || !overlaps(&a.rect, &b.rect), "{:?} overlaps {:?}", a, b);
+            }
+        }
+    }
+
+    #[test]
+    fn shared_frames_keep_definitions_on_one_page() {
+        let shared = ("shared.png".to_string(), 30, 30);
+        let a = vec![shared.clone(), ("a.png".to_string(), 30, 30)];
+        let b = vec![("b.png".to_string(), 30, 30), shared.clone()];
+
+        let layout = AtlasLayout::pack(&[a.clone(), b], 64, 0).unwrap();
+        assert_eq!(layout.frames.len(), 3);
+        assert!(layout.frames.values().all(|entry| entry.page == 0));
+
+        // The first page is full, so the second definition can't join its shared frame
+        let c = vec![("c.png".to_string(), 60, 40), shared];
+        assert!(AtlasLayout::pack(&[a, c], 64, 0).is_err());
+    }
+
+    #[test]
+    fn animations_spanning_pages_are_rejected() {
+        let mut layout = AtlasLayout::pack(&[group("tux", &[(8, 8), (8, 8)])], 64, 0).unwrap();
+        layout.frames.get_mut("tux/1.png").unwrap().page = 1;
+        let atlas = TextureAtlas { layout, pages: Vec::new() };
+
+        let mut animations = HashMap::new();
+        animations.insert("walk".to_string(), AnimationDefinition {
+            frames: (0..2).map(|i| FrameDefinition { file_name: format!("{}.png", i), duration: None }).collect(),
+            loops: true,
+            frame_duration: 0.1,
+        });
+        let definition = SpriteDefinition {
+            name: "tux".to_string(),
+            texture_path: "tux".to_string(),
+            animations,
+        };
+        assert!(atlas.animation(&definition, "walk").is_err());
+    }
+
+    #[test]
+    fn oversized_definitions_are_rejected() {
+        assert!(AtlasLayout::pack(&[group("big", &[(65, 10)])], 64, 0).is_err());
+        assert!(AtlasLayout::pack(&[group("two", &[(64, 40), (64, 40)])], 64, 0).is_err());
+        assert!(AtlasLayout::pack(&[group("exact", &[(64, 64)])], 64, 0).is_ok());
+    }
+
+    #[test]
+    fn sprites_are_built_from_a_cached_atlas() {
+        let root = std::env::temp_dir().join(format!("rustux_atlas_test_{}", std::process::id()));
+        let frames = root.join("sprites/coin");
+        std::fs::create_dir_all(&frames).unwrap();
+        for (i, color) in [[255, 0, 0, 255], [0, 255, 0, 255]].into_iter().enumerate() {
+            RgbaImage::from_pixel(8, 12, Rgba(color)).save(frames.join(format!("spin-{}.png", i))).unwrap();
+        }
+
+        let mut animations = HashMap::new();
+        animations.insert("spin".to_string(), AnimationDefinition {
+            frames: (0..2).map(|i| FrameDefinition { file_name: format!("spin-{}.png", i), duration: None }).collect(),
+            loops: true,
+            frame_duration: 0.1,
+        });
+        let definition = SpriteDefinition {
+            name: "coin".to_string(),
+            texture_path: "sprites/coin".to_string(),
+            animations,
+        };
+
+        let mut builder = AtlasBuilder::new().with_asset_root(&root).with_page_size(64);
+        builder.add_definition(&definition);
+        let built = builder.build_cached(root.join("cache")).unwrap();
+        assert!(root.join("cache").join(LAYOUT_FILE).exists());
+
+        let cached = TextureAtlas::load(root.join("cache")).unwrap();
+        assert_eq!(cached.layout(), built.layout());
+        assert!(builder.is_current(cached.layout()));
+
+        let sprite = cached.create_sprite(&definition, Vector2::ZERO, "spin").unwrap();
+        assert_eq!(sprite.texture_name, TextureAtlas::page_texture_name(0));
+        assert_eq!(sprite.size, Vector2::new(8.0, 12.0));
+        let rects: Vec<_> = sprite.animation.as_ref().unwrap().frames.iter().map(|f| f.source_rect).collect();
+        assert_ne!(rects[0], rects[1]);
+        let green = rects[1];
+        assert_eq!(cached.pages()[0].get_pixel(green.x as u32 + 7, green.y as u32 + 11).0, [0, 255, 0, 255]);
+
+        // Once a frame changes, the cache no longer matches the sources
+        std::fs::remove_file(frames.join("spin-1.png")).unwrap();
+        assert!(!builder.is_current(cached.layout()));
+        std::fs::remove_dir_all(&root).unwrap();
+    }
+}
@@ -2,12 +2,16 @@
 
 use crate::util::Result;
 use crate::math::{Vector2, Rect};
-use sdl2::render::{Canvas, Texture, TextureCreator};
-use sdl2::video::{Window, WindowContext};
-use sdl2::rect::Rect as SdlRect;
+use crate::video::{Color, DrawParams, RenderQueue, RenderStats, VideoManager};
+use sdl2::render::{Texture, TextureCreator};
+use sdl2::video::WindowContext;
 use std::collections::HashMap;
 use std::path::Path;
 
+pub mod atlas;
+
+pub use atlas::{AtlasBuilder, AtlasLayout, TextureAtlas};
+
 /// Sprite animation frame
 #[derive(Debug, Clone)]
 pub struct AnimationFrame {
@@ -119,14 +123,21 @@ pub struct Sprite {
     pub source_rect: Option<Rect>,
     /// Whether the sprite is visible
     pub visible: bool,
-    /// Sprite rotation in degrees
+    /// Sprite rotation in degrees, clockwise
     pub rotation: f64,
-    /// Sprite scale
+    /// Point to rotate around, relative to the unscaled sprite's top-left
+    /// corner (`None` = the center)
+    pub pivot: Option<Vector2>,
+    /// Sprite scale (a negative axis mirrors the sprite along it)
     pub scale: Vector2,
     /// Flip horizontally
     pub flip_horizontal: bool,
     /// Flip vertically
     pub flip_vertical: bool,
+    /// Color the texture is multiplied with
+    pub tint: Color,
+    /// Opacity from 0 (invisible) to 1, on top of the tint's alpha
+    pub alpha: f32,
 }
 
 impl Sprite {
@@ -140,9 +151,12 @@ impl Sprite {
             source_rect: None,
             visible: true,
             rotation: 0.0,
+            pivot: None,
             scale: Vector2::new(1.0, 1.0),
             flip_horizontal: false,
             flip_vertical: false,
+            tint: Color::WHITE,
+            alpha: 1.0,
         }
     }
 
@@ -196,8 +210,8 @@ impl Sprite {
     /// Get the destination rectangle for rendering
     pub fn get_dest_rect(&self) -> Rect {
         let scaled_size = Vector2::new(
-            self.size.x * self.scale.x,
-            self.size.y * self.scale.y,
+            self.size.x * self.scale.x.abs(),
+            self.size.y * self.scale.y.abs(),
         );
         Rect::new(
             self.position.x,
@@ -206,30 +220,71 @@ impl Sprite {
             scaled_size.y,
         )
     }
+
+    /// Get the flips, rotation and tint to draw the sprite with
+    pub fn draw_params(&self) -> DrawParams {
+        let alpha = (self.tint.a as f32 * self.alpha.clamp(0.0, 1.0)).round() as u8;
+        DrawParams {
+            flip_horizontal: self.flip_horizontal != (self.scale.x < 0.0),
+            flip_vertical: self.flip_vertical != (self.scale.y < 0.0),
+            rotation: self.rotation,
+            pivot: self.pivot.map(|pivot| pivot * self.scale.abs()),
+            color: self.tint.with_alpha(alpha),
+            ..DrawParams::default()
+        }
+    }
 }
 
 /// Texture manager for loading and caching textures
-pub struct TextureManager<'a> {
-    textures: HashMap<String, Texture<'a>>,
-    texture_creator: &'a TextureCreator<WindowContext>,
+///
+/// The manager holds on to its texture creator, which keeps the renderer
+/// alive until every texture has been destroyed.
+pub struct TextureManager {
+    textures: HashMap<String, Texture>,
+    texture_creator: TextureCreator<WindowContext>,
 }
 
-impl<'a> TextureManager<'a> {
+impl TextureManager {
     /// Create a new texture manager
-    pub fn new(texture_creator: &'a TextureCreator<WindowContext>) -> Self {
+    pub fn new(texture_creator: TextureCreator<WindowContext>) -> Self {
         Self {
             textures: HashMap::new(),
             texture_creator,
         }
     }
 
+    /// Store a texture, destroying any texture it replaces
+    fn insert(&mut self, name: &str, texture: Texture) {
+        if let Some(old) = self.textures.insert(name.to_string(), texture) {
+            // SAFETY: the renderer outlives the texture, as we hold its creator
+            unsafe { old.destroy() };
+        }
+    }
+
+    /// Load a texture from raw RGBA pixels
+    pub fn load_texture_from_rgba(&mut self, name: &str, width: u32, height: u32, mut pixels: Vec<u8>) -> Result<()> {
+        let surface = sdl2::surface::Surface::from_data(
+            &mut pixels,
+            width,
+            height,
+            width * 4,
+            sdl2::pixels::PixelFormatEnum::RGBA32,
+        ).map_err(|e| crate::util::Error::SpriteLoading(e.to_string()))?;
+
+        let texture = self.texture_creator.create_texture_from_surface(&surface)
+            .map_err(|e| crate::util::Error::SpriteLoading(e.to_string()))?;
+
+        self.insert(name, texture);
+        Ok(())
+    }
+
     /// Load a texture from file
     pub fn load_texture<P: AsRef<Path>>(&mut self, name: &str, path: P) -> Result<()> {
         let surface = sdl2::surface::Surface::load_bmp(path)?;
         let texture = self.texture_creator.create_texture_from_surface(&surface)
             .map_err(|e| crate::util::Error::SpriteLoading(e.to_string()))?;
         
-        self.textures.insert(name.to_string(), texture);
+        self.insert(name, texture);
         log::debug!("Loaded texture: {}", name);
         Ok(())
     }
@@ -242,21 +297,7 @@ impl<'a> TextureManager<'a> {
         
         let rgba_img = img.to_rgba8();
         let (width, height) = rgba_img.dimensions();
-        
-        // Create SDL surface from image data
-        let mut rgba_data = rgba_img.into_raw();
-        let surface = sdl2::surface::Surface::from_data(
-            &mut rgba_data,
-            width,
-            height,
-            width * 4,
-            sdl2::pixels::PixelFormatEnum::RGBA32,
-        ).map_err(|e| crate::util::Error::SpriteLoading(e.to_string()))?;
-        
-        let texture = self.texture_creator.create_texture_from_surface(&surface)
-            .map_err(|e| crate::util::Error::SpriteLoading(e.to_string()))?;
-        
-        self.textures.insert(name.to_string(), texture);
+        self.load_texture_from_rgba(name, width, height, rgba_img.into_raw())?;
         log::debug!("Loaded texture from bytes: {}", name);
         Ok(())
     }
@@ -266,6 +307,11 @@ impl<'a> TextureManager<'a> {
         self.textures.get(name)
     }
 
+    /// Get a texture by name (mutable)
+    pub fn get_texture_mut(&mut self, name: &str) -> Option<&mut Texture> {
+        self.textures.get_mut(name)
+    }
+
     /// Check if a texture is loaded
     pub fn has_texture(&self, name: &str) -> bool {
         self.textures.contains_key(name)
@@ -273,12 +319,22 @@ impl<'a> TextureManager<'a> {
 
     /// Remove a texture
     pub fn remove_texture(&mut self, name: &str) -> bool {
-        self.textures.remove(name).is_some()
+        match self.textures.remove(name) {
+            Some(texture) => {
+                // SAFETY: the renderer outlives the texture, as we hold its creator
+                unsafe { texture.destroy() };
+                true
+            }
+            None => false,
+        }
     }
 
     /// Clear all textures
     pub fn clear(&mut self) {
-        self.textures.clear();
+        for (_, texture) in self.textures.drain() {
+            // SAFETY: the renderer outlives the texture, as we hold its creator
+            unsafe { texture.destroy() };
+        }
     }
 
     /// Get the number of loaded textures
@@ -286,7 +342,9 @@ impl<'a> TextureManager<'a> {
         self.textures.len()
     }
 
-    /// Load textures from a SuperTux sprite definition
+    /// Load textures from a SuperTux sprite definition, one per frame
+    ///
+    /// Prefer packing definitions into a `TextureAtlas` with `AtlasBuilder`.
     pub fn load_from_sprite_definition(&mut self, definition: &crate::assets::SpriteDefinition) -> Result<()> {
         use std::path::Path;
         
@@ -314,21 +372,7 @@ impl<'a> TextureManager<'a> {
         
         let rgba_img = img.to_rgba8();
         let (width, height) = rgba_img.dimensions();
-        
-        // Create SDL surface from image data
-        let mut rgba_data = rgba_img.into_raw();
-        let surface = sdl2::surface::Surface::from_data(
-            &mut rgba_data,
-            width,
-            height,
-            width * 4,
-            sdl2::pixels::PixelFormatEnum::RGBA32,
-        ).map_err(|e| crate::util::Error::SpriteLoading(e.to_string()))?;
-        
-        let texture = self.texture_creator.create_texture_from_surface(&surface)
-            .map_err(|e| crate::util::Error::SpriteLoading(e.to_string()))?;
-        
-        self.textures.insert(name.to_string(), texture);
+        self.load_texture_from_rgba(name, width, height, rgba_img.into_raw())?;
         log::debug!("Loaded texture from file: {} -> {}", path.as_ref().display(), name);
         Ok(())
     }
@@ -342,63 +386,32 @@ impl<'a> TextureManager<'a> {
     }
 }
 
-/// Sprite renderer for drawing sprites to the canvas
+impl Drop for TextureManager {
+    fn drop(&mut self) {
+        self.clear();
+    }
+}
+
+/// Sprite renderer for drawing sprites through the video manager
 pub struct SpriteRenderer;
 
 impl SpriteRenderer {
-    /// Render a sprite to the canvas
-    pub fn render_sprite(
-        canvas: &mut Canvas<Window>,
-        texture_manager: &TextureManager,
-        sprite: &Sprite,
-    ) -> Result<()> {
+    /// Render a sprite
+    pub fn render_sprite(video: &mut VideoManager, sprite: &Sprite) -> Result<()> {
         if !sprite.visible {
             return Ok(());
         }
 
-        let texture = texture_manager.get_texture(&sprite.texture_name)
-            .ok_or_else(|| crate::util::Error::SpriteLoading(
-                format!("Texture not found: {}", sprite.texture_name)
-            ))?;
-
-        let dest_rect = sprite.get_dest_rect();
-        let sdl_dest = SdlRect::new(
-            dest_rect.x as i32,
-            dest_rect.y as i32,
-            dest_rect.width as u32,
-            dest_rect.height as u32,
-        );
-
-        let sdl_src = sprite.get_source_rect().map(|rect| SdlRect::new(
-            rect.x as i32,
-            rect.y as i32,
-            rect.width as u32,
-            rect.height as u32,
-        ));
-
-        canvas.copy_ex(
-            texture,
-            sdl_src,
-            Some(sdl_dest),
-            sprite.rotation,
-            None,
-            sprite.flip_horizontal,
-            sprite.flip_vertical,
-        ).map_err(|e| crate::util::Error::Video(e))?;
-
-        Ok(())
+        video.draw_texture(&sprite.texture_name, sprite.get_source_rect(), sprite.get_dest_rect(), &sprite.draw_params())
     }
 
-    /// Render multiple sprites
-    pub fn render_sprites(
-        canvas: &mut Canvas<Window>,
-        texture_manager: &TextureManager,
-        sprites: &[&Sprite],
-    ) -> Result<()> {
+    /// Render multiple sprites, batched by texture
+    pub fn render_sprites(video: &mut VideoManager, sprites: &[&Sprite]) -> Result<RenderStats> {
+        let mut queue = RenderQueue::new();
         for sprite in sprites {
-            Self::render_sprite(canvas, texture_manager, sprite)?;
+            queue.push_sprite(sprite, 0);
         }
-        Ok(())
+        queue.flush(video)
     }
 }
 
@@ -469,6 +482,17 @@ pub mod animations {
         Animation::from_sprite_sheet(frame_width, frame_height, frame_count, 0.1, true)
     }
 
+    /// Create a looping animation of `frame_count` frames laid out to the right of `first_frame`
+    pub fn cycle(first_frame: Rect, frame_count: usize, frame_duration: f32) -> Animation {
+        let frames = (0..frame_count)
+            .map(|i| AnimationFrame {
+                source_rect: first_frame.translate(Vector2::new(first_frame.width * i as f32, 0.0)),
+                duration: frame_duration,
+            })
+            .collect();
+        Animation::new(frames, true)
+    }
+
     /// Create a jumping animation
     pub fn jump(source_rect: Rect) -> Animation {
         Animation::new(
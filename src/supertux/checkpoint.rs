@@ -0,0 +1,129 @@
+//! Checkpoint bells for RustUX
+//!
+//! Ringing a bell by touching it moves the point Tux comes back to after
+//! dying. A bell only rings once; bells rung before a restart stay rung.
+
+use crate::math::Vector2;
+use crate::object::{factory, Component, GameObjectManager, ObjectId};
+use crate::physics::PhysicsWorld;
+use crate::supertux::TuxForm;
+use std::any::Any;
+
+/// Size of a checkpoint bell
+pub const CHECKPOINT_SIZE: Vector2 = Vector2::new(32.0, 64.0);
+
+/// Texture used when the level doesn't specify one
+pub const CHECKPOINT_TEXTURE: &str = "bell";
+
+/// Image file of the default texture
+pub const CHECKPOINT_TEXTURE_PATH: &str = "assets/sprites/objects/bell/bell-m.png";
+
+/// Checkpoint component for bells that record where Tux respawns
+#[derive(Debug, Clone)]
+pub struct Checkpoint {
+    /// Where Tux comes back after dying once the bell has rung
+    pub respawn_position: Vector2,
+    pub reached: bool,
+}
+
+impl Checkpoint {
+    pub fn new(respawn_position: Vector2) -> Self {
+        Self {
+            respawn_position,
+            reached: false,
+        }
+    }
+
+    /// Ring the bell, returning true the first time only
+    pub fn reach(&mut self) -> bool {
+        !std::mem::replace(&mut self.reached, true)
+    }
+}
+
+impl Component for Checkpoint {
+    fn as_any(&self) -> &dyn Any { self }
+    fn as_any_mut(&mut self) -> &mut dyn Any { self }
+    fn clone_component(&self) -> Box<dyn Component> { Box::new(self.clone()) }
+}
+
+/// Create a checkpoint bell with a sensor body the player can touch
+///
+/// Tux respawns standing at the bottom of the bell.
+pub fn create_checkpoint(
+    object_manager: &mut GameObjectManager,
+    physics_world: &mut PhysicsWorld,
+    position: Vector2,
+    texture_name: String,
+) -> ObjectId {
+    let id = factory::create_sensor_item(
+        object_manager,
+        physics_world,
+        "Checkpoint".to_string(),
+        position,
+        CHECKPOINT_SIZE,
+        texture_name,
+    );
+
+    let respawn_position = position + Vector2::new(0.0, CHECKPOINT_SIZE.y - TuxForm::Small.hitbox_size().y);
+    if let Some(object) = object_manager.get_object_mut(id) {
+        object.add_component(Checkpoint::new(respawn_position));
+        object.tag = "checkpoint".to_string();
+    }
+
+    id
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::control::InputManager;
+    use crate::physics::{BodyType, PhysicsBody};
+    use crate::supertux::{GameWorld, PlayerController, DEATH_TIME};
+
+    const DT: f32 = 1.0 / 60.0;
+
+    /// A flat level with Tux spawning at x = 100 and a bell at x = 300
+    fn level() -> (GameWorld, ObjectId) {
+        let mut world = GameWorld::new();
+        world.set_spawn_point(Vector2::new(100.0, 168.0));
+        world.create_player(Vector2::new(100.0, 168.0), "tux".to_string());
+        let (object_manager, physics_world) = world.managers_mut();
+        physics_world.add_body(PhysicsBody::new(0, Vector2::new(0.0, 200.0), Vector2::new(2000.0, 32.0), BodyType::Static));
+        let bell = create_checkpoint(object_manager, physics_world, Vector2::new(300.0, 136.0), CHECKPOINT_TEXTURE.to_string());
+        (world, bell)
+    }
+
+    fn reached(world: &GameWorld, bell: ObjectId) -> bool {
+        world.object_manager().get_object(bell).and_then(|o| o.get_component::<Checkpoint>()).unwrap().reached
+    }
+
+    #[test]
+    fn ringing_a_bell_moves_the_respawn_point_across_restarts() {
+        let (mut world, bell) = level();
+        let input = InputManager::new().unwrap();
+        world.update(&input, DT).unwrap();
+        assert_eq!(world.respawn_point(), Vector2::new(100.0, 168.0));
+        assert!(!reached(&world, bell));
+
+        world.teleport_player(Vector2::new(300.0, 168.0));
+        world.update(&input, DT).unwrap();
+        assert!(reached(&world, bell));
+        assert_eq!(world.respawn_point(), Vector2::new(300.0, 168.0));
+
+        // Die, then restart the level the way the playing state does
+        world.get_player_mut().and_then(|p| p.get_component_mut::<PlayerController>()).unwrap().add_score(500);
+        world.kill_player();
+        for _ in 0..(DEATH_TIME / DT).ceil() as usize + 1 {
+            world.update(&input, DT).unwrap();
+        }
+        assert!(world.is_death_finished());
+
+        let (mut restarted, bell) = level();
+        restarted.resume_from(&world);
+        assert!(reached(&restarted, bell));
+        assert!(restarted.is_player_alive());
+        assert_eq!(restarted.get_player_lives(), 2);
+        assert_eq!(restarted.get_player_score(), 500);
+        assert_eq!(restarted.get_player().unwrap().position(), Vector2::new(300.0, 168.0));
+    }
+}
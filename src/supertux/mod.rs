@@ -1,13 +1,35 @@
 //! Main SuperTux game logic for RustUX
 
-use crate::object::{GameObject, GameObjectManager, Component, Transform, SpriteComponent, Health, ObjectId};
-use crate::physics::{PhysicsWorld, PhysicsBody, BodyType};
-use crate::collision::CollisionLayer;
+use crate::object::{GameObject, GameObjectManager, Component, Transform, SpriteComponent, Health, Collectible, PhysicsComponent, ObjectId};
+use crate::physics::{PhysicsWorld, PhysicsBody, PhysicsMaterial, BodyType, ContactEvent, ContactPhase};
+use crate::badguy::{Badguy, BadguySystem, PlayerContact, ProjectileHit};
+use crate::particle::{effects, ParticleSystem};
+use crate::projectile::{Projectile, ProjectileSystem};
+use crate::trigger::{Trigger, TriggerAction};
+use crate::tilemap::Tilemap;
+use crate::collision::{CollisionLayer, CollisionType};
 use crate::control::{InputManager, GameAction};
-use crate::sprite::{Sprite, Animation, animations};
+use crate::sprite::{Sprite, Animation, TextureAtlas, animations};
 use crate::math::{Vector2, Rect};
 use crate::util::Result;
+use crate::video::{BackgroundLayer, Camera, RenderStats, VideoManager};
 use std::any::Any;
+use std::collections::HashMap;
+
+pub mod checkpoint;
+pub mod powerup;
+
+pub use checkpoint::Checkpoint;
+pub use powerup::{PowerUp, PowerUpKind, TuxForm};
+
+/// Seconds Tux can't be hurt after losing a power-up
+const INVULNERABILITY_TIME: f32 = 2.0;
+
+/// Seconds the death sequence lasts before the level restarts
+pub const DEATH_TIME: f32 = 2.5;
+
+/// Upward speed of the hop Tux makes when he dies
+const DEATH_JUMP_SPEED: f32 = -450.0;
 
 /// Player state enumeration
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -19,6 +41,7 @@ pub enum PlayerState {
     Falling,
     Ducking,
     Climbing,
+    Swimming,
     Dead,
 }
 
@@ -37,6 +60,37 @@ pub struct PlayerController {
     pub lives: i32,
     pub score: i32,
     pub coins: i32,
+    /// Power-up form
+    pub form: TuxForm,
+    /// Multiplier on the upward speed when jump is released mid-jump
+    pub jump_cut_multiplier: f32,
+    /// Seconds after walking off a ledge that Tux can still jump
+    pub coyote_time: f32,
+    /// Seconds a jump press is remembered before Tux lands
+    pub jump_buffer_time: f32,
+    /// Extra upward speed for a jump taken at full run speed
+    pub run_jump_boost: f32,
+    /// Time left to jump after leaving the ground
+    pub coyote_timer: f32,
+    /// Time left for a buffered jump press
+    pub jump_buffer_timer: f32,
+    /// Whether jump was held last update
+    pub jump_held: bool,
+    /// Whether Tux is rising from a jump that can still be cut
+    pub jump_rising: bool,
+    /// How quickly Tux speeds up and slows down on ground with the default
+    /// friction; slipperier ground scales it down
+    pub ground_acceleration: f32,
+    /// Speed on a climbable
+    pub climb_speed: f32,
+    /// Speed under water
+    pub swim_speed: f32,
+    /// Upward speed of a swimming stroke
+    pub swim_stroke: f32,
+    /// Whether Tux is holding on to a climbable
+    pub climbing: bool,
+    /// Time left in the death sequence
+    pub death_timer: f32,
 }
 
 impl PlayerController {
@@ -54,14 +108,117 @@ impl PlayerController {
             lives: 3,
             score: 0,
             coins: 0,
+            form: TuxForm::Small,
+            jump_cut_multiplier: 0.5,
+            coyote_time: 0.1,
+            jump_buffer_time: 0.1,
+            run_jump_boost: 60.0,
+            coyote_timer: 0.0,
+            jump_buffer_timer: 0.0,
+            jump_held: false,
+            jump_rising: false,
+            ground_acceleration: 4000.0,
+            climb_speed: 100.0,
+            swim_speed: 120.0,
+            swim_stroke: -250.0,
+            climbing: false,
+            death_timer: 0.0,
+        }
+    }
+
+    /// Horizontal velocity after steering from `current` towards `target`
+    ///
+    /// Tux has full control in the air (`friction` is `None`) and on normal
+    /// ground. On slippery ground he only speeds up and slows down gradually.
+    pub fn steer(&self, current: f32, target: f32, friction: Option<f32>, delta_time: f32) -> f32 {
+        let grip = friction.map_or(1.0, |friction| friction / PhysicsMaterial::default().friction);
+        if grip >= 1.0 {
+            return target;
+        }
+        let max_change = self.ground_acceleration * grip * delta_time;
+        current + (target - current).clamp(-max_change, max_change)
+    }
+
+    /// Grab hold of a climbable
+    pub fn start_climbing(&mut self) {
+        self.climbing = true;
+        self.state = PlayerState::Climbing;
+        self.jump_buffer_timer = 0.0;
+        self.jump_rising = false;
+    }
+
+    /// Let go of a climbable
+    pub fn stop_climbing(&mut self) {
+        self.climbing = false;
+        if self.state == PlayerState::Climbing {
+            self.state = PlayerState::Falling;
         }
     }
 
-    pub fn jump(&mut self) {
-        if self.can_jump && self.on_ground {
+    /// Vertical speed of a jump taken at `horizontal_speed`
+    ///
+    /// The run boost ramps in between walking and running speed.
+    pub fn jump_speed(&self, horizontal_speed: f32) -> f32 {
+        let range = self.run_speed - self.move_speed;
+        let boost = if range > 0.0 {
+            ((horizontal_speed.abs() - self.move_speed) / range).clamp(0.0, 1.0)
+        } else {
+            0.0
+        };
+        self.jump_velocity - self.run_jump_boost * boost
+    }
+
+    /// Feed the jump button for one update and return the new vertical velocity
+    ///
+    /// A press is buffered for `jump_buffer_time` and taken as soon as Tux is
+    /// on the ground or within `coyote_time` of leaving it. Letting go while
+    /// rising cuts the upward speed by `jump_cut_multiplier`.
+    pub fn update_jump(&mut self, jump_held: bool, on_ground: bool, velocity: Vector2, delta_time: f32) -> f32 {
+        let pressed = jump_held && !self.jump_held;
+        let released = !jump_held && self.jump_held;
+        self.jump_held = jump_held;
+
+        if on_ground {
+            self.coyote_timer = self.coyote_time;
+            self.can_jump = true;
+            self.jump_rising = false;
+        } else {
+            self.coyote_timer = (self.coyote_timer - delta_time).max(0.0);
+        }
+
+        if pressed {
+            self.jump_buffer_timer = self.jump_buffer_time;
+        } else {
+            self.jump_buffer_timer = (self.jump_buffer_timer - delta_time).max(0.0);
+        }
+
+        let wants_jump = pressed || self.jump_buffer_timer > 0.0;
+        if wants_jump && self.can_jump && (on_ground || self.coyote_timer > 0.0) {
             self.state = PlayerState::Jumping;
             self.can_jump = false;
+            self.coyote_timer = 0.0;
+            self.jump_buffer_timer = 0.0;
+            self.jump_rising = true;
+
+            let speed = self.jump_speed(velocity.x);
+            // A buffered tap that was already let go gives a short hop
+            if !jump_held {
+                self.jump_rising = false;
+                return speed * self.jump_cut_multiplier;
+            }
+            return speed;
+        }
+
+        let mut vertical = velocity.y;
+        if self.jump_rising {
+            if vertical >= 0.0 {
+                self.jump_rising = false;
+            } else if released {
+                vertical *= self.jump_cut_multiplier;
+                self.jump_rising = false;
+            }
         }
+        vertical
     }
 
     pub fn start_moving_left(&mut self) {
@@ -96,18 +253,25 @@ impl PlayerController {
         }
     }
 
+    /// Die unless invulnerable
     pub fn take_damage(&mut self) {
         if !self.invulnerable {
-            self.lives -= 1;
-            self.invulnerable = true;
-            self.invulnerability_time = 2.0; // 2 seconds of invulnerability
-            
-            if self.lives <= 0 {
-                self.state = PlayerState::Dead;
-            }
+            self.die();
         }
     }
 
+    /// Lose a life and start the death sequence
+    pub fn die(&mut self) {
+        if !self.is_alive() {
+            return;
+        }
+        self.lives -= 1;
+        self.state = PlayerState::Dead;
+        self.death_timer = DEATH_TIME;
+        self.climbing = false;
+        self.invulnerable = false;
+    }
+
     pub fn add_score(&mut self, points: i32) {
         self.score += points;
     }
@@ -134,11 +298,26 @@ impl Component for PlayerController {
 }
 
 /// Player input system
-pub struct PlayerInputSystem;
+pub struct PlayerInputSystem {
+    /// Areas the player can climb in
+    climbables: Vec<Rect>,
+}
 
 impl PlayerInputSystem {
     pub fn new() -> Self {
-        Self
+        Self {
+            climbables: Vec::new(),
+        }
+    }
+
+    /// Add an area the player can climb in
+    pub fn add_climbable(&mut self, area: Rect) {
+        self.climbables.push(area);
+    }
+
+    /// Get the areas the player can climb in
+    pub fn climbables(&self) -> &[Rect] {
+        &self.climbables
     }
 
     pub fn update(
@@ -146,6 +325,7 @@ impl PlayerInputSystem {
         player_id: ObjectId,
         object_manager: &mut GameObjectManager,
         physics_world: &mut PhysicsWorld,
+        projectile_system: &mut ProjectileSystem,
         input_manager: &InputManager,
         delta_time: f32,
     ) -> Result<()> {
@@ -209,56 +389,117 @@ impl PlayerInputSystem {
             }
         }
 
-        if input_manager.is_action_just_pressed(GameAction::Jump) {
-            controller.jump();
-            log::debug!("Player jumping");
+        // Ducking and jumping on a one-way platform drops through it
+        let jump_held = input_manager.is_action_pressed(GameAction::Jump);
+        if jump_held
+            && !controller.jump_held
+            && input_manager.is_action_pressed(GameAction::Duck)
+            && physics_world.drop_through(body_id)
+        {
+            // Swallow the press so it isn't taken as a jump
+            controller.jump_held = true;
+            log::debug!("Player dropping through platform");
         }
 
-        if input_manager.is_action_pressed(GameAction::Duck) {
+        // Fire and Ice Tux throw projectiles in the direction they face
+        let throw = if input_manager.is_action_just_pressed(GameAction::Action) {
+            controller.form.projectile().map(|kind| (kind, controller.facing_right))
+        } else {
+            None
+        };
+
+        let duck_held = input_manager.is_action_pressed(GameAction::Duck);
+        let up_held = input_manager.is_action_pressed(GameAction::Up);
+        if duck_held {
             controller.start_ducking();
         } else {
             controller.stop_ducking();
         }
 
+        let (center, in_water, submerged) = match physics_world.get_body(body_id) {
+            Some(body) => (body.get_center(), body.in_water(), body.submerged),
+            None => return Ok(()),
+        };
+
+        // Up grabs a climbable; leaving it lets go
+        let in_climbable = self.climbables.iter().any(|zone| zone.contains_point(center));
+        if controller.climbing && !in_climbable {
+            controller.stop_climbing();
+        } else if !controller.climbing && in_climbable && input_manager.is_action_just_pressed(GameAction::Up) {
+            controller.start_climbing();
+            // Up doubles as jump; don't take the grab as a jump
+            controller.jump_held = jump_held;
+        }
+
+        let vertical_input = if up_held {
+            -1.0
+        } else if duck_held {
+            1.0
+        } else {
+            0.0
+        };
+        let jump_pressed = jump_held && !controller.jump_held;
+
         // Apply movement to physics body
         if let Some(body) = physics_world.get_body(body_id) {
             let current_velocity = body.velocity;
             let speed = if is_running { controller.run_speed } else { controller.move_speed };
             
             let mut new_velocity = current_velocity;
-            
-            // Horizontal movement
-            if !matches!(controller.state, PlayerState::Ducking) {
-                new_velocity.x = horizontal_input * speed;
+
+            if controller.climbing {
+                if jump_pressed && !up_held {
+                    // Jumping off, as if from the ground
+                    controller.climbing = false;
+                    new_velocity.x = horizontal_input * speed;
+                    new_velocity.y = controller.update_jump(jump_held, true, new_velocity, delta_time);
+                } else {
+                    controller.jump_held = jump_held;
+                    new_velocity = Vector2::new(horizontal_input, vertical_input) * controller.climb_speed;
+                }
+            } else if in_water && !body.on_ground {
+                // Strokes swim up, or leap out when Tux is at the surface
+                if jump_pressed {
+                    new_velocity.y = if submerged < 1.0 { controller.jump_speed(0.0) } else { controller.swim_stroke };
+                } else if vertical_input != 0.0 {
+                    new_velocity.y = vertical_input * controller.swim_speed;
+                }
+                controller.jump_held = jump_held;
+                new_velocity.x = horizontal_input * controller.swim_speed;
             } else {
-                new_velocity.x = 0.0; // Can't move while ducking
-            }
-            
-            // Jumping
-            if matches!(controller.state, PlayerState::Jumping) && controller.can_jump {
-                new_velocity.y = controller.jump_velocity;
-                controller.can_jump = false;
+                // Horizontal movement; can't move while ducking, but may slide
+                let target = if matches!(controller.state, PlayerState::Ducking) { 0.0 } else { horizontal_input * speed };
+                new_velocity.x = controller.steer(current_velocity.x, target, physics_world.ground_friction(body_id), delta_time);
+
+                // Jumping
+                new_velocity.y = controller.update_jump(jump_held, body.on_ground, new_velocity, delta_time);
             }
             
             physics_world.set_body_velocity(body_id, new_velocity);
             log::debug!("Player velocity set to: ({:.2}, {:.2})", new_velocity.x, new_velocity.y);
         }
 
+        // Climbing Tux hangs on instead of falling
+        if let Some(body) = physics_world.get_body_mut(body_id) {
+            body.use_gravity = !controller.climbing;
+        }
+
         // Update state based on physics
         if let Some(body) = physics_world.get_body(body_id) {
             controller.on_ground = body.on_ground;
             
             // Update state based on velocity and ground status
-            if !controller.on_ground {
-                if body.velocity.y < 0.0 {
+            if controller.climbing {
+                controller.state = PlayerState::Climbing;
+            } else if !controller.on_ground {
+                if body.in_water() {
+                    controller.state = PlayerState::Swimming;
+                } else if body.velocity.y < 0.0 {
                     controller.state = PlayerState::Jumping;
                 } else {
                     controller.state = PlayerState::Falling;
                 }
             } else {
-                // Reset jump ability when on ground
-                controller.can_jump = true;
-                
                 // Update ground state
                 if horizontal_input == 0.0 && !matches!(controller.state, PlayerState::Ducking) {
                     controller.state = PlayerState::Idle;
@@ -266,20 +507,69 @@ impl PlayerInputSystem {
             }
         }
 
+        if let Some((kind, facing_right)) = throw {
+            if let Some(center) = physics_world.get_body(body_id).map(|body| body.get_center()) {
+                if projectile_system.spawn(object_manager, physics_world, kind, center, facing_right).is_some() {
+                    log::debug!("Player threw a {:?}", kind);
+                }
+            }
+        }
+
         Ok(())
     }
 }
 
 /// Player animation system
-pub struct PlayerAnimationSystem;
+pub struct PlayerAnimationSystem {
+    /// Animations of each form from a texture atlas, with the page they are drawn from
+    atlas_animations: HashMap<(TuxForm, String), (String, Animation)>,
+    /// Form and atlas animation the player sprite is playing
+    current: Option<(TuxForm, &'static str)>,
+}
 
 impl PlayerAnimationSystem {
     pub fn new() -> Self {
-        Self
+        Self {
+            atlas_animations: HashMap::new(),
+            current: None,
+        }
+    }
+
+    /// Animate Tux with the frames of his forms' sprite definitions in an atlas
+    pub fn use_atlas(&mut self, atlas: &TextureAtlas) {
+        self.atlas_animations.clear();
+        self.current = None;
+        for form in TuxForm::ALL {
+            let definition = form.sprite_definition();
+            for name in definition.animations.keys() {
+                match atlas.animation(&definition, name) {
+                    Ok(animation) => {
+                        self.atlas_animations.insert((form, name.clone()), animation);
+                    }
+                    Err(e) => log::debug!("No {} animation for {} Tux in the atlas: {}", name, form.name(), e),
+                }
+            }
+        }
+    }
+
+    /// Check if Tux is animated from an atlas
+    pub fn uses_atlas(&self) -> bool {
+        !self.atlas_animations.is_empty()
+    }
+
+    /// Sprite definition animation shown in a player state
+    fn animation_name(state: PlayerState) -> &'static str {
+        match state {
+            PlayerState::Walking | PlayerState::Running | PlayerState::Swimming => "walk",
+            PlayerState::Jumping | PlayerState::Falling => "jump",
+            PlayerState::Ducking => "duck",
+            PlayerState::Climbing => "climb",
+            PlayerState::Idle | PlayerState::Dead => "idle",
+        }
     }
 
     pub fn update(
-        &self,
+        &mut self,
         player_id: ObjectId,
         object_manager: &mut GameObjectManager,) -> Result<()> {
         let player_obj = match object_manager.get_object_mut(player_id) {
@@ -298,11 +588,13 @@ impl PlayerAnimationSystem {
             return Ok(());
         };
 
-        let state = if let Some(controller) = player_obj.get_component::<PlayerController>() {
-            controller.state
+        let (state, form) = if let Some(controller) = player_obj.get_component::<PlayerController>() {
+            (controller.state, controller.form)
         } else {
             return Ok(());
         };
+        // Frames are laid out left to right in the form's sprite size
+        let Vector2 { x: width, y: height } = form.hitbox_size();
 
         let sprite_comp = match player_obj.get_component_mut::<SpriteComponent>() {
             Some(s) => s,
@@ -312,20 +604,36 @@ impl PlayerAnimationSystem {
         // Update sprite flip based on facing direction
         sprite_comp.sprite.flip_horizontal = !facing_right;
 
+        // Atlas animations only restart when they change; forms missing one show their idle frames
+        if self.uses_atlas() {
+            let mut name = Self::animation_name(state);
+            if !self.atlas_animations.contains_key(&(form, name.to_string())) {
+                name = "idle";
+            }
+            if self.current != Some((form, name)) {
+                if let Some((texture, animation)) = self.atlas_animations.get(&(form, name.to_string())) {
+                    sprite_comp.sprite.texture_name = texture.clone();
+                    sprite_comp.sprite.set_animation(animation.clone());
+                }
+                self.current = Some((form, name));
+            }
+            return Ok(());
+        }
+
         // Set animation based on state
         match state {
             PlayerState::Idle => {
                 // Set idle animation
-                let idle_rect = Rect::new(0.0, 0.0, 32.0, 32.0);
+                let idle_rect = Rect::new(0.0, 0.0, width, height);
                 sprite_comp.sprite.set_animation(animations::idle(idle_rect));
             }
             PlayerState::Walking => {
                 // Set walking animation
-                sprite_comp.sprite.set_animation(animations::walk(32.0, 32.0, 4));
+                sprite_comp.sprite.set_animation(animations::walk(width, height, 4));
             }
             PlayerState::Running => {
                 // Set running animation (faster walking)
-                let mut run_anim = animations::walk(32.0, 32.0, 4);
+                let mut run_anim = animations::walk(width, height, 4);
                 // Make it faster by reducing frame duration
                 for frame in &mut run_anim.frames {
                     frame.duration *= 0.7;
@@ -334,26 +642,38 @@ impl PlayerAnimationSystem {
             }
             PlayerState::Jumping | PlayerState::Falling => {
                 // Set jump animation
-                let jump_rect = Rect::new(128.0, 0.0, 32.0, 32.0);
+                let jump_rect = Rect::new(width * 4.0, 0.0, width, height);
                 sprite_comp.sprite.set_animation(animations::jump(jump_rect));
             }
             PlayerState::Ducking => {
                 // Set ducking animation
-                let duck_rect = Rect::new(160.0, 0.0, 32.0, 24.0);
+                let duck_rect = Rect::new(width * 5.0, 0.0, width, height * 0.75);
                 sprite_comp.sprite.set_animation(animations::idle(duck_rect));
             }
+            PlayerState::Climbing => {
+                // Set climbing animation
+                let climb_rect = Rect::new(width * 7.0, 0.0, width, height);
+                sprite_comp.sprite.set_animation(animations::cycle(climb_rect, 2, 0.15));
+            }
+            PlayerState::Swimming => {
+                // Set swimming animation
+                let swim_rect = Rect::new(width * 9.0, 0.0, width, height);
+                sprite_comp.sprite.set_animation(animations::cycle(swim_rect, 2, 0.25));
+            }
             PlayerState::Dead => {
                 // Set death animation
-                let death_rect = Rect::new(192.0, 0.0, 32.0, 32.0);
+                let death_rect = Rect::new(width * 6.0, 0.0, width, height);
                 sprite_comp.sprite.set_animation(animations::idle(death_rect));
             }
-            _ => {}
         }
 
         Ok(())
     }
 }
 
+/// Falling speed from which landing kicks up dust
+const LANDING_DUST_SPEED: f32 = 250.0;
+
 /// Game world containing all game objects and systems
 pub struct GameWorld {
     object_manager: GameObjectManager,
@@ -361,8 +681,20 @@ pub struct GameWorld {
     player_id: Option<ObjectId>,
     player_input_system: PlayerInputSystem,
     player_animation_system: PlayerAnimationSystem,
-    camera_position: Vector2,
+    badguy_system: BadguySystem,
+    projectile_system: ProjectileSystem,
+    triggers: Vec<Trigger>,
+    /// Fired trigger actions that have to be carried out outside the world
+    trigger_actions: Vec<TriggerAction>,
+    tilemap: Option<Tilemap>,
+    /// Background layers sorted by `z`
+    backgrounds: Vec<BackgroundLayer>,
+    particles: ParticleSystem,
+    camera: Camera,
     world_bounds: Rect,
+    spawn_point: Vector2,
+    /// Where the player respawns once a checkpoint bell has rung
+    checkpoint: Option<Vector2>,
 }
 
 impl GameWorld {
@@ -373,15 +705,25 @@ impl GameWorld {
             player_id: None,
             player_input_system: PlayerInputSystem::new(),
             player_animation_system: PlayerAnimationSystem::new(),
-            camera_position: Vector2::ZERO,
+            badguy_system: BadguySystem::new(),
+            projectile_system: ProjectileSystem::new(),
+            triggers: Vec::new(),
+            trigger_actions: Vec::new(),
+            tilemap: None,
+            backgrounds: Vec::new(),
+            particles: ParticleSystem::new(),
+            camera: Camera::for_player().with_bounds(Rect::new(0.0, 0.0, 2048.0, 768.0)),
             world_bounds: Rect::new(0.0, 0.0, 2048.0, 768.0), // Default world size
+            spawn_point: Vector2::ZERO,
+            checkpoint: None,
         }
     }
 
     /// Create the player character
     pub fn create_player(&mut self, position: Vector2, texture_name: String) -> ObjectId {
         // Create physics body for player
-        let player_body = PhysicsBody::new(0, position, Vector2::new(32.0, 32.0), BodyType::Dynamic);
+        let mut player_body = PhysicsBody::new(0, position, Vector2::new(32.0, 32.0), BodyType::Dynamic);
+        player_body.collision_layer = CollisionLayer::Player;
         let body_id = self.physics_world.add_body(player_body);
 
         // Create player object
@@ -395,38 +737,178 @@ impl GameWorld {
             log::info!("Created Tux sprite with source rect: {:?}", sprite.get_source_rect());
             player_obj.add_component(SpriteComponent::new(sprite));
             
-            player_obj.add_component(crate::object::PhysicsComponent::new(
-                body_id,
-                BodyType::Dynamic,
-                CollisionLayer::Player,
-            ));
-            
             player_obj.add_component(PlayerController::new());
-            player_obj.add_component(Health::new(1)); // Tux has 1 HP (power-ups can increase this)
+            player_obj.add_component(Health::new(1)); // Power-ups protect Tux through his form instead
             
             player_obj.tag = "player".to_string();}
+        self.object_manager.add_physics_component(player_id, PhysicsComponent::new(
+            body_id,
+            BodyType::Dynamic,
+            CollisionLayer::Player,
+        ));
 
         self.player_id = Some(player_id);
+        self.badguy_system.set_player_id(player_id);
+        self.camera.snap_to(position + Vector2::new(16.0, 16.0));
         player_id
     }
 
+    /// Draw Tux from a texture atlas holding his forms' sprite definitions
+    pub fn use_sprite_atlas(&mut self, atlas: &TextureAtlas) {
+        self.player_animation_system.use_atlas(atlas);
+
+        let Some(player) = self.player_id.and_then(|id| self.object_manager.get_object_mut(id)) else {
+            return;
+        };
+        let Some(form) = player.get_component::<PlayerController>().map(|controller| controller.form) else {
+            return;
+        };
+        match atlas.create_sprite(&form.sprite_definition(), Vector2::ZERO, "idle") {
+            Ok(mut sprite) => {
+                sprite.size = form.hitbox_size();
+                if let Some(sprite_comp) = player.get_component_mut::<SpriteComponent>() {
+                    sprite.position = sprite_comp.sprite.position;
+                    sprite_comp.sprite = sprite;
+                }
+            }
+            Err(e) => log::warn!("Could not create Tux's sprite from the atlas: {}", e),
+        }
+    }
+
+    /// Add a trigger area to the world, backed by a sensor body
+    pub fn add_trigger(&mut self, mut trigger: Trigger) {
+        let mut body = PhysicsBody::new(0, trigger.area.top_left(), Vector2::new(trigger.area.width, trigger.area.height), BodyType::Static)
+            .with_collision_type(CollisionType::Trigger);
+        body.collision_layer = CollisionLayer::Trigger;
+        trigger.body_id = Some(self.physics_world.add_body(body));
+        self.triggers.push(trigger);
+    }
+
+    /// Get all trigger areas
+    pub fn triggers(&self) -> &[Trigger] {
+        &self.triggers
+    }
+
+    /// Add an area the player can climb in
+    pub fn add_climbable(&mut self, area: Rect) {
+        self.player_input_system.add_climbable(area);
+    }
+
+    /// Get the areas the player can climb in
+    pub fn climbables(&self) -> &[Rect] {
+        self.player_input_system.climbables()
+    }
+
+    /// Fill an area with water
+    pub fn add_water(&mut self, area: Rect) {
+        self.physics_world.add_water_volume(area);
+    }
+
+    /// Add a background layer, keeping the layers sorted by `z`
+    pub fn add_background(&mut self, layer: BackgroundLayer) {
+        let index = self.backgrounds.partition_point(|other| other.z <= layer.z);
+        self.backgrounds.insert(index, layer);
+    }
+
+    /// Get the background layers, back to front
+    pub fn backgrounds(&self) -> &[BackgroundLayer] {
+        &self.backgrounds
+    }
+
+    /// Get the particle effects
+    pub fn particles(&self) -> &ParticleSystem {
+        &self.particles
+    }
+
+    /// Get the particle effects (mutable)
+    pub fn particles_mut(&mut self) -> &mut ParticleSystem {
+        &mut self.particles
+    }
+
+    /// Get the player's projectiles
+    pub fn projectile_system(&self) -> &ProjectileSystem {
+        &self.projectile_system
+    }
+
+    /// Set the level tilemap and hand its solid tiles to the physics world
+    pub fn set_tilemap(&mut self, tilemap: Tilemap) {
+        self.physics_world.set_tile_grid(tilemap.collision_grid());
+        self.tilemap = Some(tilemap);
+    }
+
+    /// Get the level tilemap
+    pub fn tilemap(&self) -> Option<&Tilemap> {
+        self.tilemap.as_ref()
+    }
+
+    /// Change a tile at runtime, keeping the physics world in sync
+    pub fn set_tile(&mut self, layer_index: usize, x: i32, y: i32, id: u32) {
+        let tilemap = match self.tilemap.as_mut() {
+            Some(tilemap) => tilemap,
+            None => return,
+        };
+
+        if tilemap.set_tile(layer_index, x, y, id) {
+            let attributes = tilemap.cell_attributes(x, y);
+            if let Some(grid) = self.physics_world.tile_grid_mut() {
+                grid.set_attributes(x, y, attributes);
+            }
+        }
+    }
+
     /// Update the game world
     pub fn update(&mut self, input_manager: &InputManager, delta_time: f32) -> Result<()> {
+        let falling_speed = self.player_body().filter(|body| !body.on_ground).map(|body| body.velocity.y);
+
         // Update physics
         self.physics_world.update(delta_time);
 
+        // Kick up dust when the player lands from a real fall
+        if let (Some(speed), Some(body)) = (falling_speed, self.player_body()) {
+            if body.on_ground && speed >= LANDING_DUST_SPEED {
+                let feet = Vector2::new(body.position.x + body.size.x * 0.5, body.position.y + body.size.y);
+                self.particles.add(effects::landing_dust(feet));
+            }
+        }
+
+        // Falling out of the bottom of the world is fatal
+        if self.player_body().is_some_and(|body| body.active && body.position.y > self.world_bounds.bottom()) {
+            self.kill_player();
+        }
+
         // Update player input
         if let Some(player_id) = self.player_id {
             self.player_input_system.update(
                 player_id,
                 &mut self.object_manager,
                 &mut self.physics_world,
+                &mut self.projectile_system,
                 input_manager,
                 delta_time,
             )?;
 
             self.player_animation_system.update(player_id, &mut self.object_manager)?;
         }
+        self.update_death(delta_time);
+
+        // React to bodies that touched during the physics update
+        let contacts = self.physics_world.take_contact_events();
+        for event in &contacts {
+            self.handle_contact(event)?;
+        }
+
+        // Update badguy AI
+        self.badguy_system.update(&mut self.object_manager, &mut self.physics_world, delta_time)?;
+
+        // Resolve projectile hits, then bounce the rest along and clear away spent ones
+        self.handle_projectile_hits(&contacts)?;
+        self.projectile_system.update(
+            &mut self.object_manager,
+            &mut self.physics_world,
+            &contacts,
+            self.camera.view(),
+            delta_time,
+        );
 
         // Sync object positions from physics
         self.object_manager.sync_from_physics(&self.physics_world)?;
@@ -434,38 +916,455 @@ impl GameWorld {
         // Update all game objects
         self.object_manager.update(delta_time)?;
 
-        // Update camera to follow player
-        self.update_camera();
+        // Update camera to follow player; it stays where Tux died
+        if self.is_player_alive() {
+            self.update_camera(delta_time);
+        }
+
+        for background in &mut self.backgrounds {
+            background.update(delta_time);
+        }
+        self.particles.update(delta_time);
 
         Ok(())
     }
 
-    /// Update camera to follow the player
-    fn update_camera(&mut self) {
-        if let Some(player_id) = self.player_id {
-            if let Some(player_obj) = self.object_manager.get_object(player_id) {
-                let player_pos = player_obj.position();
-                // Center camera on player with some offset
-                self.camera_position.x = player_pos.x -400.0; // Half screen width
-                self.camera_position.y = player_pos.y - 300.0; // Slightly above center
-                
-                // Clamp camera to world bounds
-                self.camera_position.x = self.camera_position.x.max(0.0)
-                    .min(self.world_bounds.width -800.0); // Screen width
-                self.camera_position.y = self.camera_position.y.max(0.0)
-                    .min(self.world_bounds.height - 600.0); // Screen height
+    /// Dispatch a contact event to player, badguy, collectible and trigger logic
+    fn handle_contact(&mut self, event: &ContactEvent) -> Result<()> {
+        let player_id = match self.player_id {
+            Some(player_id) => player_id,
+            None => return Ok(()),
+        };
+        let player_body = match self.object_manager.get_object(player_id).and_then(|p| p.get_component::<PhysicsComponent>()) {
+            Some(physics) => physics.body_id,
+            None => return Ok(()),
+        };
+        let other_body = match event.other(player_body) {
+            Some(other_body) => other_body,
+            None => return Ok(()),
+        };
+
+        if let Some(index) = self.triggers.iter().position(|t| t.body_id == Some(other_body)) {
+            self.handle_trigger_contact(index, event.phase);
+            return Ok(());
+        }
+
+        // Everything else reacts to things starting to touch; badguys keep
+        // hurting Tux for as long as they touch him
+        if event.phase == ContactPhase::End {
+            return Ok(());
+        }
+
+        let other_id = match self.object_manager.object_for_body(other_body) {
+            Some(other_id) => other_id,
+            None => return Ok(()),
+        };
+        let (is_badguy, is_collectible, is_power_up, is_checkpoint) = match self.object_manager.get_object(other_id) {
+            Some(other) if other.active => (
+                other.has_component::<Badguy>(),
+                other.has_component::<Collectible>(),
+                other.has_component::<PowerUp>(),
+                other.has_component::<Checkpoint>(),
+            ),
+            _ => return Ok(()),
+        };
+        if event.phase != ContactPhase::Begin && !is_badguy {
+            return Ok(());
+        }
+
+        if is_badguy {
+            match self.badguy_system.handle_player_collision(other_id, player_id, &mut self.object_manager)? {
+                PlayerContact::Stomped if event.phase == ContactPhase::Begin => {
+                    if let Some(badguy) = self.object_manager.get_object(other_id) {
+                        self.particles.add(effects::stomp_stars(badguy.position() + Vector2::new(16.0, 0.0)));
+                    }
+                }
+                PlayerContact::Hurt { damage } => {
+                    self.hurt_player(damage);
+                }
+                PlayerContact::Stomped | PlayerContact::None => {}
+            }
+        } else if is_collectible {
+            self.collect(player_id, other_id, other_body);
+        } else if is_power_up {
+            self.collect_power_up(other_id, other_body);
+        } else if is_checkpoint {
+            self.reach_checkpoint(other_id);
+        }
+
+        Ok(())
+    }
+
+    /// Let projectiles that ran into badguys burn or freeze them
+    fn handle_projectile_hits(&mut self, contacts: &[ContactEvent]) -> Result<()> {
+        for (projectile_id, badguy_body) in self.projectile_system.hits(&self.object_manager, contacts) {
+            let kind = match self.object_manager.get_object(projectile_id).and_then(|p| p.get_component::<Projectile>()) {
+                Some(projectile) => projectile.kind,
+                None => continue,
+            };
+            let badguy_id = match self.object_manager.object_for_body(badguy_body) {
+                Some(id) => id,
+                None => continue,
+            };
+
+            match self.badguy_system.handle_projectile_hit(badguy_id, kind, &mut self.object_manager)? {
+                ProjectileHit::Killed { points } => {
+                    self.physics_world.remove_body(badguy_body);
+                    if let Some(badguy) = self.object_manager.get_object(badguy_id) {
+                        self.particles.add(effects::stomp_stars(Self::sprite_center(badguy)));
+                    }
+                    if let Some(controller) = self.get_player_mut().and_then(|p| p.get_component_mut::<PlayerController>()) {
+                        controller.add_score(points);
+                    }
+                }
+                ProjectileHit::Frozen | ProjectileHit::Immune => {}
+                // Not a badguy, or already defeated
+                ProjectileHit::None => continue,
+            }
+
+            self.projectile_system.despawn(&mut self.object_manager, &mut self.physics_world, projectile_id);
+        }
+        Ok(())
+    }
+
+    /// Fire a trigger if the player's contact matches its activation mode
+    fn handle_trigger_contact(&mut self, index: usize, phase: ContactPhase) {
+        let trigger = &mut self.triggers[index];
+        if !trigger.activate(phase) {
+            return;
+        }
+        log::debug!("Trigger '{}' fired", trigger.name);
+
+        let actions = trigger.actions.clone();
+        // Spent one-shot triggers don't need their sensor any more
+        if !trigger.active {
+            if let Some(body_id) = trigger.body_id.take() {
+                self.physics_world.remove_body(body_id);
+            }
+        }
+
+        for action in actions {
+            self.run_trigger_action(action);
+        }
+    }
+
+    /// Carry out a trigger action, queueing those the world can't handle itself
+    fn run_trigger_action(&mut self, action: TriggerAction) {
+        match action {
+            TriggerAction::Teleport { x, y } => self.teleport_player(Vector2::new(x, y)),
+            TriggerAction::SpawnObject { object } => crate::level::LevelLoader::build_object(self, &object),
+            action => self.trigger_actions.push(action),
+        }
+    }
+
+    /// Move the player to a position, stopping it
+    pub fn teleport_player(&mut self, position: Vector2) {
+        let body_id = match self.get_player().and_then(|p| p.get_component::<PhysicsComponent>()) {
+            Some(physics) => physics.body_id,
+            None => return,
+        };
+        self.physics_world.set_body_position(body_id, position);
+        self.physics_world.set_body_velocity(body_id, Vector2::ZERO);
+        if let Some(player) = self.get_player_mut() {
+            player.set_position(position);
+        }
+        if let Some(focus) = self.get_player().map(Self::sprite_center) {
+            self.camera.snap_to(focus);
+        }
+    }
+
+    /// Take fired trigger actions the game has to carry out: level end,
+    /// messages and music changes
+    pub fn take_trigger_actions(&mut self) -> Vec<TriggerAction> {
+        std::mem::take(&mut self.trigger_actions)
+    }
+
+    /// Give a collectible to the player and remove it from the world
+    fn collect(&mut self, player_id: ObjectId, collectible_id: ObjectId, body_id: u32) {
+        let (value, center) = match self.object_manager.get_object_mut(collectible_id) {
+            Some(object) => {
+                let value = object.get_component_mut::<Collectible>().map(|c| c.collect()).unwrap_or(0);
+                object.active = false;
+                (value, Self::sprite_center(object))
+            }
+            None => return,
+        };
+        self.physics_world.remove_body(body_id);
+        self.particles.add(effects::coin_sparkle(center));
+
+        if let Some(controller) = self.object_manager.get_object_mut(player_id).and_then(|p| p.get_component_mut::<PlayerController>()) {
+            for _ in 0..value {
+                controller.add_coin();
+            }
+        }
+    }
+
+    /// Give a power-up to the player and remove it from the world
+    fn collect_power_up(&mut self, power_up_id: ObjectId, body_id: u32) {
+        let kind = match self.object_manager.get_object_mut(power_up_id) {
+            Some(object) => {
+                object.active = false;
+                object.get_component_mut::<PowerUp>().and_then(|p| p.collect())
+            }
+            None => return,
+        };
+        self.physics_world.remove_body(body_id);
+
+        let form = match (kind, self.player_form()) {
+            (Some(kind), Some(form)) => form.with_power_up(kind),
+            _ => return,
+        };
+        self.set_player_form(form);
+    }
+
+    /// Ring a checkpoint bell, making it the player's respawn point
+    fn reach_checkpoint(&mut self, checkpoint_id: ObjectId) {
+        let Some(object) = self.object_manager.get_object_mut(checkpoint_id) else {
+            return;
+        };
+        let center = Self::sprite_center(object);
+        let Some(checkpoint) = object.get_component_mut::<Checkpoint>() else {
+            return;
+        };
+        if !checkpoint.reach() {
+            return;
+        }
+        let respawn_position = checkpoint.respawn_position;
+        log::info!("Checkpoint reached at {:?}", respawn_position);
+        self.checkpoint = Some(respawn_position);
+        self.particles.add(effects::coin_sparkle(center));
+    }
+
+    /// Get where the player comes back after dying: the last checkpoint
+    /// reached, or the spawn point
+    pub fn respawn_point(&self) -> Vector2 {
+        self.checkpoint.unwrap_or(self.spawn_point)
+    }
+
+    /// Carry the player's progress over from the world it died in
+    ///
+    /// Lives, score, coins and rung checkpoints are kept, and the player is
+    /// moved to the last checkpoint reached.
+    pub fn resume_from(&mut self, previous: &GameWorld) {
+        if let Some(previous_controller) = previous.get_player().and_then(|p| p.get_component::<PlayerController>()) {
+            if let Some(controller) = self.get_player_mut().and_then(|p| p.get_component_mut::<PlayerController>()) {
+                controller.lives = previous_controller.lives;
+                controller.score = previous_controller.score;
+                controller.coins = previous_controller.coins;
+            }
+        }
+
+        let Some(respawn_position) = previous.checkpoint else {
+            return;
+        };
+        for id in self.object_manager.get_object_ids() {
+            if let Some(checkpoint) = self.object_manager.get_object_mut(id).and_then(|o| o.get_component_mut::<Checkpoint>()) {
+                if checkpoint.respawn_position == respawn_position {
+                    checkpoint.reached = true;
+                }
+            }
+        }
+        self.checkpoint = Some(respawn_position);
+        self.teleport_player(respawn_position);
+    }
+
+    /// Get the player's power-up form
+    pub fn player_form(&self) -> Option<TuxForm> {
+        self.get_player()
+            .and_then(|player| player.get_component::<PlayerController>())
+            .map(|controller| controller.form)
+    }
+
+    /// Change the player's form, resizing its hitbox and sprite
+    ///
+    /// Tux grows and shrinks around his feet so he stays on the ground.
+    pub fn set_player_form(&mut self, form: TuxForm) {
+        let Some(player_id) = self.player_id else {
+            return;
+        };
+        let Some(player) = self.object_manager.get_object_mut(player_id) else {
+            return;
+        };
+        match player.get_component_mut::<PlayerController>() {
+            Some(controller) if controller.form != form => controller.form = form,
+            _ => return,
+        }
+        log::info!("Tux is now {}", form.name());
+
+        let size = form.hitbox_size();
+        let uses_atlas = self.player_animation_system.uses_atlas();
+        if let Some(sprite_comp) = player.get_component_mut::<SpriteComponent>() {
+            // Atlas frames are picked up by the animation system on its next update
+            if !uses_atlas {
+                sprite_comp.sprite.texture_name = form.texture_name().to_string();
+            }
+            sprite_comp.sprite.size = size;
+        }
+
+        let Some(body_id) = player.get_component::<PhysicsComponent>().map(|physics| physics.body_id) else {
+            return;
+        };
+        let Some(rect) = self.physics_world.get_body(body_id).map(|body| body.get_rect()) else {
+            return;
+        };
+        let position = Vector2::new(rect.x, rect.bottom() - size.y);
+        self.physics_world.set_body_size(body_id, size);
+        self.physics_world.set_body_position(body_id, position);
+        player.set_position(position);
+    }
+
+    /// Hurt the player, returning false if it was invulnerable
+    ///
+    /// A powered-up Tux loses one form and becomes invulnerable for a while;
+    /// small Tux takes the damage.
+    pub fn hurt_player(&mut self, damage: i32) -> bool {
+        let Some(player) = self.get_player_mut() else {
+            return false;
+        };
+        let Some(controller) = player.get_component_mut::<PlayerController>() else {
+            return false;
+        };
+        if controller.invulnerable {
+            return false;
+        }
+
+        match controller.form.after_damage() {
+            Some(form) => {
+                controller.invulnerable = true;
+                controller.invulnerability_time = INVULNERABILITY_TIME;
+                self.set_player_form(form);
+            }
+            None => {
+                if let Some(health) = player.get_component_mut::<Health>() {
+                    health.take_damage(damage);
+                    log::info!("Player took {} damage from badguy", damage);
+                    if !health.is_alive() {
+                        self.kill_player();
+                    }
+                }
             }
         }
+        true
+    }
+
+    /// Kill the player, starting the death sequence
+    ///
+    /// Tux hops up and falls out of the level through everything in his way.
+    pub fn kill_player(&mut self) {
+        let Some(player) = self.get_player_mut() else {
+            return;
+        };
+        let Some(controller) = player.get_component_mut::<PlayerController>() else {
+            return;
+        };
+        if !controller.is_alive() {
+            return;
+        }
+        controller.die();
+        log::info!("Tux died, {} lives left", controller.lives);
+
+        let Some(body_id) = player.get_component::<PhysicsComponent>().map(|physics| physics.body_id) else {
+            return;
+        };
+        // The death hop is played out by the world, not the physics
+        self.physics_world.set_body_collision_type(body_id, CollisionType::Sensor);
+        let bottom = self.world_bounds.bottom();
+        if let Some(body) = self.physics_world.get_body_mut(body_id) {
+            // No hop after falling down a pit, Tux would come back into view
+            let speed = if body.position.y < bottom { DEATH_JUMP_SPEED } else { 0.0 };
+            body.active = false;
+            body.on_ground = false;
+            body.velocity = Vector2::new(0.0, speed);
+        }
+    }
+
+    /// Move a dead player along its death hop and count down the death sequence
+    fn update_death(&mut self, delta_time: f32) {
+        let gravity = self.physics_world.get_gravity();
+        let Some(player) = self.get_player_mut() else {
+            return;
+        };
+        let Some(controller) = player.get_component_mut::<PlayerController>() else {
+            return;
+        };
+        if controller.is_alive() {
+            return;
+        }
+        controller.death_timer = (controller.death_timer - delta_time).max(0.0);
+
+        let Some(body_id) = player.get_component::<PhysicsComponent>().map(|physics| physics.body_id) else {
+            return;
+        };
+        let Some(body) = self.physics_world.get_body_mut(body_id) else {
+            return;
+        };
+        body.velocity += gravity * delta_time;
+        let position = body.position + body.velocity * delta_time;
+        self.physics_world.set_body_position(body_id, position);
+    }
+
+    /// Check if the player's death sequence is over and the level should restart
+    pub fn is_death_finished(&self) -> bool {
+        self.get_player()
+            .and_then(|p| p.get_component::<PlayerController>())
+            .is_some_and(|c| !c.is_alive() && c.death_timer <= 0.0)
+    }
+
+    /// Update camera to follow the player
+    fn update_camera(&mut self, delta_time: f32) {
+        let player = match self.get_player() {
+            Some(player) => player,
+            None => return,
+        };
+        let velocity = player
+            .get_component::<PhysicsComponent>()
+            .and_then(|physics| self.physics_world.get_body(physics.body_id))
+            .map(|body| body.velocity)
+            .unwrap_or(Vector2::ZERO);
+        let focus = Self::sprite_center(player);
+
+        self.camera.update(focus, velocity, delta_time);
+    }
+
+    /// Get the player's physics body
+    fn player_body(&self) -> Option<&PhysicsBody> {
+        let physics = self.get_player()?.get_component::<PhysicsComponent>()?;
+        self.physics_world.get_body(physics.body_id)
+    }
+
+    /// Center of an object's sprite, the point the camera keeps in view for the player
+    fn sprite_center(object: &GameObject) -> Vector2 {
+        let size = object
+            .get_component::<SpriteComponent>()
+            .map(|sprite| sprite.sprite.size)
+            .unwrap_or(Vector2::new(32.0, 32.0));
+        object.position() + size * 0.5
     }
 
-    /// Render the game world
+    /// Render the game world, returning the sprite stats of the frame
     pub fn render(
         &self,
-        canvas: &mut sdl2::render::Canvas<sdl2::video::Window>,
-        texture_manager: &crate::sprite::TextureManager,
-    ) -> Result<()> {
-        // TODO: Apply camera transform to rendering
-        self.object_manager.render(canvas, texture_manager)
+        video: &mut VideoManager,
+    ) -> Result<RenderStats> {
+        for background in &self.backgrounds {
+            if let Err(e) = background.render(video, &self.camera) {
+                log::debug!("Skipping background layer: {}", e);
+            }
+        }
+
+        let mut stats = RenderStats::default();
+        if let Some(ref tilemap) = self.tilemap {
+            stats += tilemap.render_background(video, &self.camera)?;
+        }
+
+        stats += self.object_manager.render(video, &self.camera)?;
+        stats += self.particles.render(video, &self.camera)?;
+
+        if let Some(ref tilemap) = self.tilemap {
+            stats += tilemap.render_foreground(video, &self.camera)?;
+        }
+
+        Ok(stats)
     }
 
     /// Get the player object
@@ -503,14 +1402,35 @@ impl GameWorld {
         &mut self.physics_world
     }
 
-    /// Get the camera position
+    /// Get the object manager and physics world together (mutable)
+    pub fn managers_mut(&mut self) -> (&mut GameObjectManager, &mut PhysicsWorld) {
+        (&mut self.object_manager, &mut self.physics_world)
+    }
+
+    /// Get the camera position (top-left corner of the view)
     pub fn camera_position(&self) -> Vector2 {
-        self.camera_position
+        self.camera.position()
+    }
+
+    /// Get the camera
+    pub fn camera(&self) -> &Camera {
+        &self.camera
+    }
+
+    /// Get the camera (mutable)
+    pub fn camera_mut(&mut self) -> &mut Camera {
+        &mut self.camera
+    }
+
+    /// Set the size of the screen area the world is drawn to
+    pub fn set_viewport_size(&mut self, viewport_size: Vector2) {
+        self.camera.set_viewport_size(viewport_size);
     }
 
     /// Set the world bounds
     pub fn set_world_bounds(&mut self, bounds: Rect) {
         self.world_bounds = bounds;
+        self.camera.set_bounds(Some(bounds));
     }
 
     /// Get the world bounds
@@ -518,6 +1438,16 @@ impl GameWorld {
         self.world_bounds
     }
 
+    /// Set the player spawn point
+    pub fn set_spawn_point(&mut self, position: Vector2) {
+        self.spawn_point = position;
+    }
+
+    /// Get the player spawn point
+    pub fn spawn_point(&self) -> Vector2 {
+        self.spawn_point
+    }
+
     /// Check if the player is alive
     pub fn is_player_alive(&self) -> bool {
         self.get_player()
@@ -555,4 +1485,326 @@ impl Default for GameWorld {
     fn default() -> Self {
         Self::new()
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::GRAVITY;
+
+    const DT: f32 = 1.0 / 60.0;
+
+    /// Fly one jump arc from the ground and return the peak height
+    ///
+    /// `held` says whether jump is held on each frame; frames past its end
+    /// release the button.
+    fn peak_height(controller: &mut PlayerController, held: &[bool], horizontal_speed: f32) -> f32 {
+        let mut velocity = Vector2::new(horizontal_speed, 0.0);
+        let mut height = 0.0f32;
+        let mut peak = 0.0f32;
+        for frame in 0..600 {
+            let on_ground = height <= 0.0 && velocity.y >= 0.0;
+            let jump = held.get(frame).copied().unwrap_or(false);
+            velocity.y = controller.update_jump(jump, on_ground, velocity, DT);
+            velocity.y += GRAVITY * DT;
+            height = (height - velocity.y * DT).max(0.0);
+            peak = peak.max(height);
+            if frame > 0 && height <= 0.0 {
+                break;
+            }
+        }
+        peak
+    }
+
+    #[test]
+    fn releasing_jump_early_cuts_the_arc() {
+        let full = peak_height(&mut PlayerController::new(), &[true; 60], 0.0);
+        let tapped = peak_height(&mut PlayerController::new(), &[true; 4], 0.0);
+        assert!((full - 80.0).abs() < 5.0, "full jump peaked at {}", full);
+        assert!(tapped < full * 0.5, "tap peaked at {} of {}", tapped, full);
+
+        // Running jumps go higher than walking ones
+        let controller = PlayerController::new();
+        assert_eq!(controller.jump_speed(controller.move_speed), controller.jump_velocity);
+        let running = peak_height(&mut PlayerController::new(), &[true; 60], controller.run_speed);
+        assert!(running > full + 10.0);
+    }
+
+    #[test]
+    fn coyote_time_allows_a_late_jump() {
+        let mut controller = PlayerController::new();
+        controller.update_jump(false, true, Vector2::ZERO, DT);
+
+        // Walked off a ledge three frames ago
+        for _ in 0..3 {
+            controller.update_jump(false, false, Vector2::ZERO, DT);
+        }
+        assert_eq!(controller.update_jump(true, false, Vector2::ZERO, DT), controller.jump_velocity);
+
+        // No second jump in mid-air, and none once the window has passed
+        controller.update_jump(false, false, Vector2::ZERO, DT);
+        assert_eq!(controller.update_jump(true, false, Vector2::new(0.0, 50.0), DT), 50.0);
+
+        let mut controller = PlayerController::new();
+        controller.update_jump(false, true, Vector2::ZERO, DT);
+        for _ in 0..10 {
+            controller.update_jump(false, false, Vector2::ZERO, DT);
+        }
+        assert_eq!(controller.update_jump(true, false, Vector2::new(0.0, 50.0), DT), 50.0);
+    }
+
+    #[test]
+    fn jump_pressed_before_landing_is_buffered() {
+        let mut controller = PlayerController::new();
+        controller.coyote_time = 0.0;
+        let falling = Vector2::new(0.0, 200.0);
+
+        // Pressed four frames before touching down, still held on landing
+        controller.update_jump(true, false, falling, DT);
+        for _ in 0..3 {
+            assert_eq!(controller.update_jump(true, false, falling, DT), falling.y);
+        }
+        assert_eq!(controller.update_jump(true, true, falling, DT), controller.jump_velocity);
+
+        // Released before landing: a short hop
+        let mut controller = PlayerController::new();
+        controller.update_jump(true, false, falling, DT);
+        controller.update_jump(false, false, falling, DT);
+        let hop = controller.update_jump(false, true, falling, DT);
+        assert_eq!(hop, controller.jump_velocity * controller.jump_cut_multiplier);
+
+        // Pressed too early: forgotten by the time Tux lands
+        let mut controller = PlayerController::new();
+        controller.update_jump(true, false, falling, DT);
+        for _ in 0..10 {
+            controller.update_jump(true, false, falling, DT);
+        }
+        assert_eq!(controller.update_jump(true, true, falling, DT), falling.y);
+    }
+
+    #[test]
+    fn ice_lets_tux_slide() {
+        let controller = PlayerController::new();
+        let ice = Some(PhysicsMaterial::ice().friction);
+
+        // Full control on normal ground and in the air
+        assert_eq!(controller.steer(200.0, 0.0, Some(PhysicsMaterial::default().friction), DT), 0.0);
+        assert_eq!(controller.steer(200.0, 0.0, None, DT), 0.0);
+
+        // On ice Tux keeps sliding after letting go and takes a while to get going
+        let mut velocity = controller.run_speed;
+        let mut frames = 0;
+        while velocity > 0.0 {
+            velocity = controller.steer(velocity, 0.0, ice, DT);
+            frames += 1;
+        }
+        assert!(frames > 10, "stopped after {} frames", frames);
+        let started = controller.steer(0.0, controller.move_speed, ice, DT);
+        assert!(started > 0.0 && started < controller.move_speed);
+    }
+
+    fn player_body(world: &GameWorld) -> PhysicsBody {
+        let body_id = world.get_player().and_then(|p| p.get_component::<PhysicsComponent>()).unwrap().body_id;
+        world.physics_world().get_body(body_id).unwrap().clone()
+    }
+
+    fn player_state(world: &GameWorld) -> PlayerState {
+        world.get_player().and_then(|p| p.get_component::<PlayerController>()).unwrap().state
+    }
+
+    #[test]
+    fn tux_climbs_and_swims() {
+        let mut world = GameWorld::new();
+        world.create_player(Vector2::new(100.0, 136.0), "tux".to_string());
+        world.physics_world_mut().add_body(PhysicsBody::new(0, Vector2::new(0.0, 200.0), Vector2::new(2000.0, 32.0), BodyType::Static));
+        world.add_climbable(Rect::new(90.0, 100.0, 40.0, 100.0));
+        world.add_water(Rect::new(400.0, 0.0, 200.0, 200.0));
+
+        let mut input = InputManager::new().unwrap();
+        for _ in 0..30 {
+            world.update(&input, DT).unwrap();
+        }
+        let standing = player_body(&world).position.y;
+
+        // Holding Up grabs the climbable and carries Tux up until he runs out of it
+        input.press_for_test(GameAction::Up);
+        world.update(&input, DT).unwrap();
+        input.update();
+        assert_eq!(player_state(&world), PlayerState::Climbing);
+        assert!(!player_body(&world).use_gravity);
+        for _ in 0..20 {
+            world.update(&input, DT).unwrap();
+        }
+        assert_eq!(player_state(&world), PlayerState::Climbing);
+        assert!(player_body(&world).position.y < standing - 20.0);
+        for _ in 0..60 {
+            world.update(&input, DT).unwrap();
+        }
+        assert_ne!(player_state(&world), PlayerState::Climbing);
+        assert!(player_body(&world).use_gravity);
+        input.release_for_test(GameAction::Up);
+
+        // Dropped into water, Tux swims
+        let body_id = world.get_player().and_then(|p| p.get_component::<PhysicsComponent>()).unwrap().body_id;
+        world.physics_world_mut().set_body_position(body_id, Vector2::new(480.0, 80.0));
+        for _ in 0..10 {
+            world.update(&input, DT).unwrap();
+        }
+        assert!(player_body(&world).in_water());
+        assert_eq!(player_state(&world), PlayerState::Swimming);
+    }
+
+    #[test]
+    fn duck_and_jump_drops_through_a_one_way_platform() {
+        let mut world = GameWorld::new();
+        world.create_player(Vector2::new(120.0, 60.0), "tux".to_string());
+        let platform = PhysicsBody::new(0, Vector2::new(96.0, 100.0), Vector2::new(128.0, 8.0), BodyType::Static)
+            .with_collision_type(CollisionType::Platform);
+        world.physics_world_mut().add_body(platform);
+        world.physics_world_mut().add_body(PhysicsBody::new(0, Vector2::new(0.0, 200.0), Vector2::new(2000.0, 32.0), BodyType::Static));
+        let mut input = InputManager::new().unwrap();
+        for _ in 0..30 {
+            world.update(&input, DT).unwrap();
+        }
+        assert_eq!(player_body(&world).position.y, 68.0);
+
+        // Jumping alone goes up, not down
+        input.press_for_test(GameAction::Jump);
+        world.update(&input, DT).unwrap();
+        input.update();
+        input.release_for_test(GameAction::Jump);
+        let mut highest = f32::MAX;
+        for _ in 0..60 {
+            world.update(&input, DT).unwrap();
+            highest = highest.min(player_body(&world).position.y);
+        }
+        assert!(highest < 60.0);
+        assert_eq!(player_body(&world).position.y, 68.0);
+
+        input.press_for_test(GameAction::Duck);
+        world.update(&input, DT).unwrap();
+        input.press_for_test(GameAction::Jump);
+        world.update(&input, DT).unwrap();
+        input.update();
+        input.release_for_test(GameAction::Jump);
+        input.release_for_test(GameAction::Duck);
+        for _ in 0..60 {
+            world.update(&input, DT).unwrap();
+        }
+        assert_eq!(player_body(&world).position.y, 168.0);
+    }
+
+    #[test]
+    fn falling_out_of_the_world_kills_tux() {
+        let mut world = GameWorld::new();
+        world.set_world_bounds(Rect::new(0.0, 0.0, 640.0, 480.0));
+        world.create_player(Vector2::new(100.0, 300.0), "tux".to_string());
+        let input = InputManager::new().unwrap();
+
+        let mut frames = 0;
+        while world.is_player_alive() {
+            world.update(&input, DT).unwrap();
+            frames += 1;
+            assert!(frames < 120, "Tux never died");
+        }
+        assert_eq!(world.get_player_lives(), 2);
+        assert!(player_body(&world).position.y > 480.0);
+
+        // The camera stays put while the death sequence plays out
+        let camera = world.camera_position();
+        for _ in 0..(DEATH_TIME / DT).ceil() as usize {
+            assert!(!world.is_death_finished());
+            world.update(&input, DT).unwrap();
+        }
+        assert_eq!(world.camera_position(), camera);
+        assert!(world.is_death_finished());
+    }
+
+    #[test]
+    fn a_fatal_hit_makes_tux_hop_through_the_floor() {
+        let mut world = GameWorld::new();
+        world.create_player(Vector2::new(100.0, 168.0), "tux".to_string());
+        world.physics_world_mut().add_body(PhysicsBody::new(0, Vector2::new(0.0, 200.0), Vector2::new(2000.0, 32.0), BodyType::Static));
+        let input = InputManager::new().unwrap();
+        world.update(&input, DT).unwrap();
+        let standing = player_body(&world).position.y;
+
+        assert!(world.hurt_player(1));
+        assert_eq!(player_state(&world), PlayerState::Dead);
+        assert_eq!(world.get_player_lives(), 2);
+        for _ in 0..10 {
+            world.update(&input, DT).unwrap();
+        }
+        assert!(player_body(&world).position.y < standing - 20.0);
+        for _ in 0..(DEATH_TIME / DT) as usize {
+            world.update(&input, DT).unwrap();
+        }
+        assert!(player_body(&world).position.y > 232.0);
+        assert!(world.is_death_finished());
+    }
+
+    #[test]
+    fn tux_is_animated_from_a_sprite_atlas() {
+        use crate::sprite::AtlasBuilder;
+        use image::{Rgba, RgbaImage};
+
+        let root = std::env::temp_dir().join(format!("rustux_tux_atlas_test_{}", std::process::id()));
+        let definition = TuxForm::Small.sprite_definition();
+        let frames = root.join(&definition.texture_path);
+        std::fs::create_dir_all(&frames).unwrap();
+        let names: Vec<String> = std::iter::once("idle-0.png".to_string()).chain((0..8).map(|i| format!("walk-{}.png", i))).collect();
+        for name in &names {
+            RgbaImage::from_pixel(32, 32, Rgba([255, 255, 255, 255])).save(frames.join(name)).unwrap();
+        }
+        let mut builder = AtlasBuilder::new().with_asset_root(&root).with_page_size(128);
+        builder.add_definition(&definition);
+        let atlas = builder.build().unwrap();
+        let frame = |name: &str| atlas.frame(&format!("{}/{}", definition.texture_path, name)).unwrap().rect;
+
+        let mut world = GameWorld::new();
+        world.create_player(Vector2::new(100.0, 168.0), "tux".to_string());
+        world.physics_world_mut().add_body(PhysicsBody::new(0, Vector2::new(0.0, 200.0), Vector2::new(2000.0, 32.0), BodyType::Static));
+        world.use_sprite_atlas(&atlas);
+        let sprite = |world: &GameWorld| world.get_player().and_then(|p| p.get_component::<SpriteComponent>()).unwrap().sprite.clone();
+        assert_eq!(sprite(&world).texture_name, TextureAtlas::page_texture_name(0));
+        assert_eq!(sprite(&world).get_source_rect(), Some(frame("idle-0.png")));
+
+        let mut input = InputManager::new().unwrap();
+        input.press_for_test(GameAction::MoveRight);
+        for _ in 0..10 {
+            world.update(&input, DT).unwrap();
+        }
+        assert_eq!(player_state(&world), PlayerState::Walking);
+        let walk = sprite(&world);
+        assert_eq!(walk.texture_name, TextureAtlas::page_texture_name(0));
+        let rects: Vec<_> = walk.animation.unwrap().frames.iter().map(|f| f.source_rect).collect();
+        assert_eq!(rects, names[1..].iter().map(|name| frame(name)).collect::<Vec<_>>());
+
+        std::fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn a_badguy_still_touching_tux_hurts_him_again_after_invulnerability() {
+        let mut world = GameWorld::new();
+        world.create_player(Vector2::new(100.0, 168.0), "tux".to_string());
+        world.set_player_form(TuxForm::Fire);
+        let (object_manager, physics_world) = world.managers_mut();
+        physics_world.add_body(PhysicsBody::new(0, Vector2::new(0.0, 200.0), Vector2::new(2000.0, 32.0), BodyType::Static));
+        crate::badguy::factory::create_goomba(object_manager, physics_world, Vector2::new(140.0, 168.0), "goomba".to_string());
+        let form = |world: &GameWorld| world.get_player().and_then(|p| p.get_component::<PlayerController>()).unwrap().form;
+
+        let input = InputManager::new().unwrap();
+        let mut frames = 0;
+        while form(&world) == TuxForm::Fire && frames < 120 {
+            world.update(&input, DT).unwrap();
+            frames += 1;
+        }
+        assert_eq!(form(&world), TuxForm::Big);
+
+        // Waiting out the invulnerability next to the badguy gets Tux hurt again
+        for _ in 0..(INVULNERABILITY_TIME / DT).ceil() as usize + 10 {
+            world.update(&input, DT).unwrap();
+        }
+        assert_eq!(form(&world), TuxForm::Small);
+    }
+}
@@ -0,0 +1,323 @@
+//! Tux forms and power-ups for RustUX
+//!
+//! Tux is always in one form. Power-up objects move him to a bigger or
+//! stronger form; getting hurt moves him one step back down (fire, ice, air
+//! and earth drop to big, big drops to small), and only small Tux is killed
+//! by a hit. Every form has its own hitbox and sprite definition.
+
+use crate::assets::{AnimationDefinition, FrameDefinition, SpriteDefinition};
+use crate::math::Vector2;
+use crate::object::{factory, Component, GameObjectManager, ObjectId};
+use crate::physics::PhysicsWorld;
+use crate::projectile::ProjectileKind;
+use serde::{Deserialize, Serialize};
+use std::any::Any;
+use std::collections::HashMap;
+
+/// Size of a power-up object
+pub const POWER_UP_SIZE: f32 = 32.0;
+
+/// The form Tux is in
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TuxForm {
+    #[default]
+    Small,
+    Big,
+    Fire,
+    Ice,
+    Air,
+    Earth,
+}
+
+impl TuxForm {
+    pub const ALL: [TuxForm; 6] = [
+        TuxForm::Small,
+        TuxForm::Big,
+        TuxForm::Fire,
+        TuxForm::Ice,
+        TuxForm::Air,
+        TuxForm::Earth,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            TuxForm::Small => "small",
+            TuxForm::Big => "big",
+            TuxForm::Fire => "fire",
+            TuxForm::Ice => "ice",
+            TuxForm::Air => "air",
+            TuxForm::Earth => "earth",
+        }
+    }
+
+    /// Check if Tux is grown up in this form
+    pub fn is_big(self) -> bool {
+        self != TuxForm::Small
+    }
+
+    /// Size of Tux's physics body
+    pub fn hitbox_size(self) -> Vector2 {
+        if self.is_big() {
+            Vector2::new(32.0, 64.0)
+        } else {
+            Vector2::new(32.0, 32.0)
+        }
+    }
+
+    /// Form after taking a hit, or `None` if the hit is fatal
+    pub fn after_damage(self) -> Option<TuxForm> {
+        match self {
+            TuxForm::Small => None,
+            TuxForm::Big => Some(TuxForm::Small),
+            TuxForm::Fire | TuxForm::Ice | TuxForm::Air | TuxForm::Earth => Some(TuxForm::Big),
+        }
+    }
+
+    /// Form after collecting a power-up
+    ///
+    /// An egg only grows small Tux; it never takes away a flower's power.
+    pub fn with_power_up(self, kind: PowerUpKind) -> TuxForm {
+        match kind {
+            PowerUpKind::Egg if self.is_big() => self,
+            kind => kind.grants(),
+        }
+    }
+
+    /// Projectile Tux throws in this form, if any
+    pub fn projectile(self) -> Option<ProjectileKind> {
+        match self {
+            TuxForm::Fire => Some(ProjectileKind::Fireball),
+            TuxForm::Ice => Some(ProjectileKind::Iceball),
+            TuxForm::Small | TuxForm::Big | TuxForm::Air | TuxForm::Earth => None,
+        }
+    }
+
+    /// Texture the in-game sprite draws with
+    pub fn texture_name(self) -> &'static str {
+        match self {
+            TuxForm::Small => "tux",
+            TuxForm::Big => "tux-big",
+            TuxForm::Fire => "tux-fire",
+            TuxForm::Ice => "tux-ice",
+            TuxForm::Air => "tux-air",
+            TuxForm::Earth => "tux-earth",
+        }
+    }
+
+    /// Image file of the in-game sprite texture (the idle frame)
+    pub fn texture_path(self) -> String {
+        let definition = self.sprite_definition();
+        let idle = &definition.animations["idle"].frames[0].file_name;
+        format!("assets/{}/{}", definition.texture_path, idle)
+    }
+
+    /// Sprite definition with this form's animations
+    pub fn sprite_definition(self) -> SpriteDefinition {
+        if !self.is_big() {
+            return SpriteDefinition::tux_small();
+        }
+
+        // Big forms share one directory, told apart by a file name prefix
+        let prefix = match self {
+            TuxForm::Small | TuxForm::Big => String::new(),
+            form => format!("{}-", form.name()),
+        };
+        let animation = |name: &str, frames: usize, loops: bool, frame_duration: f32| {
+            let frames = (0..frames)
+                .map(|i| FrameDefinition {
+                    file_name: format!("{}{}-{}.png", prefix, name, i),
+                    duration: None,
+                })
+                .collect();
+            (name.to_string(), AnimationDefinition { frames, loops, frame_duration })
+        };
+
+        let animations: HashMap<_, _> = [
+            animation("idle", 1, true, 1.0),
+            animation("walk", 8, true, 0.1),
+            animation("jump", 2, false, 0.2),
+            animation("skid", 1, false, 1.0),
+            animation("duck", 1, true, 1.0),
+            animation("kick", 1, false, 0.3),
+        ]
+        .into_iter()
+        .collect();
+
+        SpriteDefinition {
+            name: format!("tux_{}", self.name()),
+            texture_path: "sprites/creatures/tux/big".to_string(),
+            animations,
+        }
+    }
+}
+
+/// Power-up items that change Tux's form
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PowerUpKind {
+    Egg,
+    FireFlower,
+    IceFlower,
+    AirFlower,
+    EarthFlower,
+}
+
+impl PowerUpKind {
+    pub const ALL: [PowerUpKind; 5] = [
+        PowerUpKind::Egg,
+        PowerUpKind::FireFlower,
+        PowerUpKind::IceFlower,
+        PowerUpKind::AirFlower,
+        PowerUpKind::EarthFlower,
+    ];
+
+    /// Form the power-up gives small Tux
+    pub fn grants(self) -> TuxForm {
+        match self {
+            PowerUpKind::Egg => TuxForm::Big,
+            PowerUpKind::FireFlower => TuxForm::Fire,
+            PowerUpKind::IceFlower => TuxForm::Ice,
+            PowerUpKind::AirFlower => TuxForm::Air,
+            PowerUpKind::EarthFlower => TuxForm::Earth,
+        }
+    }
+
+    /// Texture used when the level doesn't specify one
+    pub fn default_texture(self) -> &'static str {
+        match self {
+            PowerUpKind::Egg => "egg",
+            PowerUpKind::FireFlower => "fireflower",
+            PowerUpKind::IceFlower => "iceflower",
+            PowerUpKind::AirFlower => "airflower",
+            PowerUpKind::EarthFlower => "earthflower",
+        }
+    }
+
+    /// Image file of the default texture
+    pub fn texture_path(self) -> String {
+        format!("assets/sprites/objects/powerup/{}.png", self.default_texture())
+    }
+
+    /// Find the power-up a SuperTux sprite file shows, e.g. `images/powerups/egg/egg.sprite`
+    pub fn from_sprite_path(path: &str) -> Option<Self> {
+        let file = path.rsplit('/').next()?.split('.').next()?;
+        Self::ALL.into_iter().find(|kind| kind.default_texture() == file)
+    }
+}
+
+/// Power-up component for items that change Tux's form when touched
+#[derive(Debug, Clone)]
+pub struct PowerUp {
+    pub kind: PowerUpKind,
+    pub collected: bool,
+}
+
+impl PowerUp {
+    pub fn new(kind: PowerUpKind) -> Self {
+        Self {
+            kind,
+            collected: false,
+        }
+    }
+
+    /// Take the power-up, returning its kind the first time only
+    pub fn collect(&mut self) -> Option<PowerUpKind> {
+        if self.collected {
+            return None;
+        }
+        self.collected = true;
+        Some(self.kind)
+    }
+}
+
+impl Component for PowerUp {
+    fn as_any(&self) -> &dyn Any { self }
+    fn as_any_mut(&mut self) -> &mut dyn Any { self }
+    fn clone_component(&self) -> Box<dyn Component> { Box::new(self.clone()) }
+}
+
+/// Create a power-up item with a sensor body the player can touch
+pub fn create_power_up(
+    object_manager: &mut GameObjectManager,
+    physics_world: &mut PhysicsWorld,
+    kind: PowerUpKind,
+    position: Vector2,
+    texture_name: String,
+) -> ObjectId {
+    let size = Vector2::splat(POWER_UP_SIZE);
+    let id = factory::create_sensor_item(object_manager, physics_world, format!("{:?}", kind), position, size, texture_name);
+    if let Some(object) = object_manager.get_object_mut(id) {
+        object.add_component(PowerUp::new(kind));
+        object.tag = "powerup".to_string();
+    }
+
+    id
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::control::InputManager;
+    use crate::object::PhysicsComponent;
+    use crate::supertux::{GameWorld, PlayerController};
+
+    fn player_form(world: &GameWorld) -> TuxForm {
+        world.get_player().and_then(|p| p.get_component::<PlayerController>()).unwrap().form
+    }
+
+    fn player_rect(world: &GameWorld) -> crate::math::Rect {
+        let body_id = world.get_player().and_then(|p| p.get_component::<PhysicsComponent>()).unwrap().body_id;
+        world.physics_world().get_body(body_id).unwrap().get_rect()
+    }
+
+    #[test]
+    fn power_ups_upgrade_and_hits_downgrade_one_step() {
+        assert_eq!(TuxForm::Small.with_power_up(PowerUpKind::Egg), TuxForm::Big);
+        assert_eq!(TuxForm::Fire.with_power_up(PowerUpKind::Egg), TuxForm::Fire);
+        assert_eq!(TuxForm::Small.with_power_up(PowerUpKind::IceFlower), TuxForm::Ice);
+        assert_eq!(TuxForm::Fire.with_power_up(PowerUpKind::AirFlower), TuxForm::Air);
+
+        assert_eq!(TuxForm::Earth.after_damage(), Some(TuxForm::Big));
+        assert_eq!(TuxForm::Big.after_damage(), Some(TuxForm::Small));
+        assert_eq!(TuxForm::Small.after_damage(), None);
+
+        assert_eq!(PowerUpKind::from_sprite_path("images/powerups/fireflower/fireflower.sprite"), Some(PowerUpKind::FireFlower));
+        assert_eq!(PowerUpKind::from_sprite_path("images/powerups/star/star.sprite"), None);
+    }
+
+    #[test]
+    fn every_form_has_a_sprite_definition() {
+        assert_eq!(TuxForm::Small.texture_path(), "assets/sprites/creatures/tux/small/idle-0.png");
+        assert_eq!(TuxForm::Big.texture_path(), "assets/sprites/creatures/tux/big/idle-0.png");
+        assert_eq!(TuxForm::Fire.texture_path(), "assets/sprites/creatures/tux/big/fire-idle-0.png");
+        for form in TuxForm::ALL {
+            let definition = form.sprite_definition();
+            assert!(["idle", "walk", "jump"].iter().all(|name| definition.animations.contains_key(*name)));
+        }
+    }
+
+    #[test]
+    fn touching_a_power_up_changes_form_and_hitbox() {
+        let mut world = GameWorld::new();
+        world.create_player(Vector2::new(100.0, 100.0), "tux".to_string());
+        let (object_manager, physics_world) = world.managers_mut();
+        create_power_up(object_manager, physics_world, PowerUpKind::FireFlower, Vector2::new(100.0, 100.0), "fireflower".to_string());
+
+        let input = InputManager::new().unwrap();
+        let feet = player_rect(&world).bottom();
+        world.update(&input, 1.0 / 60.0).unwrap();
+
+        assert_eq!(player_form(&world), TuxForm::Fire);
+        let rect = player_rect(&world);
+        assert_eq!(rect.height, 64.0);
+        // Tux grows upwards, his feet stay where they were
+        assert!((rect.bottom() - feet).abs() < 1.0);
+
+        assert!(world.hurt_player(1));
+        assert_eq!(player_form(&world), TuxForm::Big);
+        // Invulnerable right after a hit
+        assert!(!world.hurt_player(1));
+        assert_eq!(player_form(&world), TuxForm::Big);
+    }
+}
@@ -0,0 +1,160 @@
+//! Chunked grid storage for tile data
+
+use std::collections::HashMap;
+
+/// Width and height of a chunk in cells
+pub const CHUNK_SIZE: i32 = 16;
+
+/// Number of cells in a chunk
+const CHUNK_AREA: usize = (CHUNK_SIZE * CHUNK_SIZE) as usize;
+
+/// Sparse grid that stores cells in fixed-size chunks
+///
+/// Chunks are only allocated once a non-default value is written, so large
+/// mostly-empty maps stay cheap. Cell coordinates may be negative.
+#[derive(Debug, Clone, Default)]
+pub struct ChunkedGrid<T> {
+    chunks: HashMap<(i32, i32), Vec<T>>,
+}
+
+impl<T: Copy + Default + PartialEq> ChunkedGrid<T> {
+    /// Create an empty grid
+    pub fn new() -> Self {
+        Self {
+            chunks: HashMap::new(),
+        }
+    }
+
+    /// Split cell coordinates into chunk coordinates and an index within the chunk
+    fn locate(x: i32, y: i32) -> ((i32, i32), usize) {
+        let chunk = (x.div_euclid(CHUNK_SIZE), y.div_euclid(CHUNK_SIZE));
+        let local_x = x.rem_euclid(CHUNK_SIZE);
+        let local_y = y.rem_euclid(CHUNK_SIZE);
+        (chunk, (local_y * CHUNK_SIZE + local_x) as usize)
+    }
+
+    /// Get the value of a cell (default if never set)
+    pub fn get(&self, x: i32, y: i32) -> T {
+        let (chunk, index) = Self::locate(x, y);
+        self.chunks.get(&chunk).map(|cells| cells[index]).unwrap_or_default()
+    }
+
+    /// Set the value of a cell
+    pub fn set(&mut self, x: i32, y: i32, value: T) {
+        let (chunk, index) = Self::locate(x, y);
+        if let Some(cells) = self.chunks.get_mut(&chunk) {
+            cells[index] = value;
+        } else if value != T::default() {
+            let mut cells = vec![T::default(); CHUNK_AREA];
+            cells[index] = value;
+            self.chunks.insert(chunk, cells);
+        }
+    }
+
+    /// Collect all non-default cells in the inclusive cell range `min..=max`
+    pub fn cells_in(&self, min: (i32, i32), max: (i32, i32)) -> Vec<(i32, i32, T)> {
+        let mut cells = Vec::new();
+        if min.0 > max.0 || min.1 > max.1 {
+            return cells;
+        }
+
+        let (min_chunk, _) = Self::locate(min.0, min.1);
+        let (max_chunk, _) = Self::locate(max.0, max.1);
+
+        for chunk_y in min_chunk.1..=max_chunk.1 {
+            for chunk_x in min_chunk.0..=max_chunk.0 {
+                let chunk_cells = match self.chunks.get(&(chunk_x, chunk_y)) {
+                    Some(chunk_cells) => chunk_cells,
+                    None => continue,
+                };
+
+                // Clip the requested range to this chunk
+                let origin_x = chunk_x * CHUNK_SIZE;
+                let origin_y = chunk_y * CHUNK_SIZE;
+                let x0 = min.0.max(origin_x);
+                let x1 = max.0.min(origin_x + CHUNK_SIZE - 1);
+                let y0 = min.1.max(origin_y);
+                let y1 = max.1.min(origin_y + CHUNK_SIZE - 1);
+
+                for y in y0..=y1 {
+                    for x in x0..=x1 {
+                        let value = chunk_cells[((y - origin_y) * CHUNK_SIZE + (x - origin_x)) as usize];
+                        if value != T::default() {
+                            cells.push((x, y, value));
+                        }
+                    }
+                }
+            }
+        }
+
+        cells
+    }
+
+    /// Get the number of allocated chunks
+    pub fn chunk_count(&self) -> usize {
+        self.chunks.len()
+    }
+
+    /// Remove all cells
+    pub fn clear(&mut self) {
+        self.chunks.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn set_and_get_across_chunks() {
+        let value = |x: i32, y: i32| ((x + 100) * 1000 + y + 100) as u32;
+        let mut grid = ChunkedGrid::<u32>::new();
+        assert_eq!(grid.get(5, 5), 0);
+
+        // Writing the default value doesn't allocate
+        grid.set(3, 3, 0);
+        assert_eq!(grid.chunk_count(), 0);
+
+        for (x, y) in [(0, 0), (15, 15), (16, 0), (-1, -1), (-16, 0), (-17, 3)] {
+            grid.set(x, y, value(x, y));
+        }
+        for (x, y) in [(0, 0), (15, 15), (16, 0), (-1, -1), (-16, 0), (-17, 3)] {
+            assert_eq!(grid.get(x, y), value(x, y));
+        }
+        // Neighbours of set cells on the other side of a chunk border are untouched
+        assert_eq!(grid.get(-1, 0), 0);
+        assert_eq!(grid.get(0, -1), 0);
+        assert_eq!(grid.get(17, 0), 0);
+        // Only (0, 0) and (15, 15) share a chunk
+        assert_eq!(grid.chunk_count(), 5);
+
+        grid.set(16, 0, 0);
+        assert_eq!(grid.get(16, 0), 0);
+        grid.clear();
+        assert_eq!(grid.chunk_count(), 0);
+        assert_eq!(grid.get(0, 0), 0);
+    }
+
+    #[test]
+    fn cells_in_clips_to_the_range_across_chunk_borders() {
+        let mut grid = ChunkedGrid::<u8>::new();
+        for x in -20..20 {
+            grid.set(x, -1, 1);
+            grid.set(x, 16, 2);
+        }
+
+        let mut cells = grid.cells_in((-17, -1), (16, 0));
+        cells.sort();
+        assert_eq!(cells.len(), 34);
+        assert_eq!(cells.first(), Some(&(-17, -1, 1)));
+        assert_eq!(cells.last(), Some(&(16, -1, 1)));
+
+        let cells = grid.cells_in((-2, 15), (1, 17));
+        assert_eq!(cells.len(), 4);
+        assert!(cells.iter().all(|&(x, y, value)| (-2..=1).contains(&x) && y == 16 && value == 2));
+
+        // Empty and inverted ranges
+        assert!(grid.cells_in((0, 0), (10, 10)).is_empty());
+        assert!(grid.cells_in((5, 0), (4, 0)).is_empty());
+    }
+}
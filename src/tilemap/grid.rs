@@ -0,0 +1,95 @@
+//! Collision view of a tilemap
+
+use crate::tilemap::chunk::ChunkedGrid;
+use crate::tilemap::slope::SlopeType;
+use crate::tilemap::tileset::TileAttributes;
+use crate::math::{Rect, Vector2};
+use crate::config::TILE_SIZE;
+
+/// Tile attributes of all solid layers merged into a single grid
+///
+/// This is what the physics world queries; it knows nothing about textures
+/// or rendering.
+#[derive(Debug, Clone, Default)]
+pub struct TileGrid {
+    cells: ChunkedGrid<TileAttributes>,
+}
+
+impl TileGrid {
+    /// Create an empty grid
+    pub fn new() -> Self {
+        Self {
+            cells: ChunkedGrid::new(),
+        }
+    }
+
+    /// Get the attributes of a cell
+    pub fn attributes(&self, x: i32, y: i32) -> TileAttributes {
+        self.cells.get(x, y)
+    }
+
+    /// Set the attributes of a cell
+    pub fn set_attributes(&mut self, x: i32, y: i32, attributes: TileAttributes) {
+        self.cells.set(x, y, attributes);
+    }
+
+    /// Get the cell containing a world position
+    pub fn cell_at(&self, point: Vector2) -> (i32, i32) {
+        let tile_size = TILE_SIZE as f32;
+        ((point.x / tile_size).floor() as i32, (point.y / tile_size).floor() as i32)
+    }
+
+    /// Get the world rectangle covered by a cell
+    pub fn cell_rect(&self, x: i32, y: i32) -> Rect {
+        let tile_size = TILE_SIZE as f32;
+        Rect::new(x as f32 * tile_size, y as f32 * tile_size, tile_size, tile_size)
+    }
+
+    /// Get the attributes of the cell containing a world position
+    pub fn attributes_at(&self, point: Vector2) -> TileAttributes {
+        let (x, y) = self.cell_at(point);
+        self.attributes(x, y)
+    }
+
+    /// Check if the cell containing a world position is solid
+    pub fn is_solid_at(&self, point: Vector2) -> bool {
+        self.attributes_at(point).solid
+    }
+
+    /// Get all collidable cells overlapping a world rectangle
+    pub fn collidable_cells_in_rect(&self, rect: &Rect) -> Vec<(i32, i32, TileAttributes)> {
+        let tile_size = TILE_SIZE as f32;
+        // Touching an edge doesn't count as overlapping
+        let min = ((rect.left() / tile_size).floor() as i32, (rect.top() / tile_size).floor() as i32);
+        let max = (
+            (rect.right() / tile_size).ceil() as i32 - 1,
+            (rect.bottom() / tile_size).ceil() as i32 - 1,
+        );
+
+        self.cells
+            .cells_in(min, max)
+            .into_iter()
+            .filter(|(_, _, attributes)| attributes.is_collidable())
+            .collect()
+    }
+
+    /// Get the rectangle and shape of all slope cells overlapping a world rectangle
+    pub fn slope_cells_in_rect(&self, rect: &Rect) -> Vec<(Rect, SlopeType)> {
+        self.collidable_cells_in_rect(rect)
+            .into_iter()
+            .filter_map(|(x, y, attributes)| attributes.slope.map(|slope| (self.cell_rect(x, y), slope)))
+            .collect()
+    }
+
+    /// Check if any solid cell overlaps a world rectangle
+    pub fn is_solid_in_rect(&self, rect: &Rect) -> bool {
+        self.collidable_cells_in_rect(rect)
+            .iter()
+            .any(|(_, _, attributes)| attributes.solid)
+    }
+
+    /// Remove all cells
+    pub fn clear(&mut self) {
+        self.cells.clear();
+    }
+}
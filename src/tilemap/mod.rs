@@ -0,0 +1,292 @@
+//! Tilemap system for RustUX
+//!
+//! A tilemap is a stack of tile layers sharing one tileset. Layers store
+//! their tiles in chunks so that large, sparse levels stay cheap. Solid
+//! layers are merged into a `TileGrid` that the physics world queries.
+
+use crate::math::{Rect, Vector2};
+use crate::config::TILE_SIZE;
+use crate::util::Result;
+use crate::video::{Camera, DrawParams, RenderCommand, RenderQueue, RenderStats, VideoManager};
+
+pub mod chunk;
+pub mod grid;
+pub mod slope;
+pub mod tileset;
+
+pub use chunk::{ChunkedGrid, CHUNK_SIZE};
+pub use grid::TileGrid;
+pub use slope::{SlopeDirection, SlopeGradient, SlopeType};
+pub use tileset::{TileAttributes, TileDefinition, Tileset};
+
+/// Layers with a `z` at or above this are drawn in front of game objects
+pub const FOREGROUND_Z: i32 = 100;
+
+/// A single layer of tiles
+#[derive(Debug, Clone)]
+pub struct TileLayer {
+    /// Layer name
+    pub name: String,
+    /// Whether tiles in this layer take part in collision
+    pub solid: bool,
+    /// Rendering layer (higher = rendered on top)
+    pub z: i32,
+    width: u32,
+    height: u32,
+    tiles: ChunkedGrid<u32>,
+}
+
+impl TileLayer {
+    /// Create an empty layer
+    pub fn new(name: String, width: u32, height: u32) -> Self {
+        Self {
+            name,
+            solid: false,
+            z: 0,
+            width,
+            height,
+            tiles: ChunkedGrid::new(),
+        }
+    }
+
+    /// Create a layer from tile ids stored row by row
+    pub fn from_tiles(name: String, width: u32, height: u32, tiles: &[u32]) -> Self {
+        let mut layer = Self::new(name, width, height);
+        for (index, &id) in tiles.iter().enumerate().take((width as usize).saturating_mul(height as usize)) {
+            let x = index as u32 % width;
+            let y = index as u32 / width;
+            layer.set_tile(x as i32, y as i32, id);
+        }
+        layer
+    }
+
+    pub fn with_solid(mut self, solid: bool) -> Self {
+        self.solid = solid;
+        self
+    }
+
+    pub fn with_z(mut self, z: i32) -> Self {
+        self.z = z;
+        self
+    }
+
+    /// Get the tile id at the given tile coordinates (0 = empty)
+    pub fn tile_at(&self, x: i32, y: i32) -> u32 {
+        self.tiles.get(x, y)
+    }
+
+    /// Set the tile id at the given tile coordinates
+    pub fn set_tile(&mut self, x: i32, y: i32, id: u32) {
+        self.tiles.set(x, y, id);
+    }
+
+    /// Get all non-empty tiles in the inclusive cell range `min..=max`
+    pub fn tiles_in(&self, min: (i32, i32), max: (i32, i32)) -> Vec<(i32, i32, u32)> {
+        self.tiles.cells_in(min, max)
+    }
+
+    /// Width in tiles
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    /// Height in tiles
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+
+    /// Get the number of allocated chunks
+    pub fn chunk_count(&self) -> usize {
+        self.tiles.chunk_count()
+    }
+}
+
+/// Tile-based level geometry
+#[derive(Debug, Clone, Default)]
+pub struct Tilemap {
+    layers: Vec<TileLayer>,
+    tileset: Tileset,
+}
+
+impl Tilemap {
+    /// Create an empty tilemap using the given tileset
+    pub fn new(tileset: Tileset) -> Self {
+        Self {
+            layers: Vec::new(),
+            tileset,
+        }
+    }
+
+    /// Add a layer, keeping layers sorted by `z`
+    pub fn add_layer(&mut self, layer: TileLayer) -> usize {
+        let index = self.layers.partition_point(|existing| existing.z <= layer.z);
+        self.layers.insert(index, layer);
+        index
+    }
+
+    /// Get all layers in drawing order
+    pub fn layers(&self) -> &[TileLayer] {
+        &self.layers
+    }
+
+    /// Get a layer by name
+    pub fn layer(&self, name: &str) -> Option<&TileLayer> {
+        self.layers.iter().find(|layer| layer.name == name)
+    }
+
+    /// Get the index of a layer by name
+    pub fn layer_index(&self, name: &str) -> Option<usize> {
+        self.layers.iter().position(|layer| layer.name == name)
+    }
+
+    /// Get the tileset
+    pub fn tileset(&self) -> &Tileset {
+        &self.tileset
+    }
+
+    /// Set a tile in a layer, returning false if the layer doesn't exist
+    pub fn set_tile(&mut self, layer_index: usize, x: i32, y: i32, id: u32) -> bool {
+        match self.layers.get_mut(layer_index) {
+            Some(layer) => {
+                layer.set_tile(x, y, id);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Get the combined attributes of all solid layers at a cell
+    pub fn cell_attributes(&self, x: i32, y: i32) -> TileAttributes {
+        self.layers
+            .iter()
+            .filter(|layer| layer.solid)
+            .map(|layer| self.tileset.attributes(layer.tile_at(x, y)))
+            .fold(TileAttributes::default(), merge_attributes)
+    }
+
+    /// Check if the cell containing a world position is solid
+    pub fn is_solid_at(&self, point: Vector2) -> bool {
+        let tile_size = TILE_SIZE as f32;
+        let x = (point.x / tile_size).floor() as i32;
+        let y = (point.y / tile_size).floor() as i32;
+        self.cell_attributes(x, y).solid
+    }
+
+    /// Build the collision grid for the physics world
+    pub fn collision_grid(&self) -> TileGrid {
+        let mut grid = TileGrid::new();
+        for layer in self.layers.iter().filter(|layer| layer.solid) {
+            let max = (layer.width as i32 - 1, layer.height as i32 - 1);
+            for (x, y, id) in layer.tiles_in((0, 0), max) {
+                let attributes = merge_attributes(grid.attributes(x, y), self.tileset.attributes(id));
+                grid.set_attributes(x, y, attributes);
+            }
+        }
+        grid
+    }
+
+    /// Size of the map in pixels (the largest layer)
+    pub fn pixel_size(&self) -> Vector2 {
+        let tile_size = TILE_SIZE as f32;
+        self.layers.iter().fold(Vector2::ZERO, |size, layer| {
+            size.max(Vector2::new(layer.width as f32 * tile_size, layer.height as f32 * tile_size))
+        })
+    }
+
+    /// Render layers behind game objects, returning the tile stats
+    pub fn render_background(
+        &self,
+        video: &mut VideoManager,
+        camera: &Camera,
+    ) -> Result<RenderStats> {
+        self.render_layers(video, camera, |layer| layer.z < FOREGROUND_Z)
+    }
+
+    /// Render layers in front of game objects, returning the tile stats
+    pub fn render_foreground(
+        &self,
+        video: &mut VideoManager,
+        camera: &Camera,
+    ) -> Result<RenderStats> {
+        self.render_layers(video, camera, |layer| layer.z >= FOREGROUND_Z)
+    }
+
+    /// Queue the visible tiles of the matching layers and draw them batched by texture
+    ///
+    /// Tiles without a loaded texture are skipped by the queue.
+    fn render_layers(
+        &self,
+        video: &mut VideoManager,
+        camera: &Camera,
+        include: impl Fn(&TileLayer) -> bool,
+    ) -> Result<RenderStats> {
+        let tile_size = TILE_SIZE as f32;
+        let screen_tile_size = tile_size * camera.zoom();
+        let view = camera.view();
+        let min = ((view.left() / tile_size).floor() as i32, (view.top() / tile_size).floor() as i32);
+        let max = ((view.right() / tile_size).floor() as i32, (view.bottom() / tile_size).floor() as i32);
+
+        let mut queue = RenderQueue::new();
+        for layer in self.layers.iter().filter(|layer| include(layer)) {
+            for (x, y, id) in layer.tiles_in(min, max) {
+                let Some(definition) = self.tileset.get(id) else {
+                    continue;
+                };
+                let position = camera.world_to_screen(Vector2::new(x as f32 * tile_size, y as f32 * tile_size));
+                queue.push(RenderCommand {
+                    layer: layer.z,
+                    texture_name: definition.texture.clone(),
+                    src: definition.source_rect(),
+                    dst: Rect::new(position.x, position.y, screen_tile_size, screen_tile_size),
+                    params: DrawParams::default(),
+                });
+            }
+        }
+
+        queue.flush(video)
+    }
+}
+
+/// Combine the attributes of tiles stacked in the same cell
+fn merge_attributes(a: TileAttributes, b: TileAttributes) -> TileAttributes {
+    TileAttributes {
+        solid: a.solid || b.solid,
+        one_way: a.one_way || b.one_way,
+        slope: a.slope.or(b.slope),
+        hurts: a.hurts || b.hurts,
+        ice: a.ice || b.ice,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tiles_are_drawn_batched_per_texture() {
+        let mut tileset = Tileset::new("test".to_string());
+        for (id, cell) in [(1, [0, 0]), (2, [1, 0])] {
+            tileset.add_tile(TileDefinition {
+                id,
+                texture: "blocks".to_string(),
+                cell: Some(cell),
+                attributes: TileAttributes::solid(),
+            });
+        }
+        let mut tilemap = Tilemap::new(tileset);
+        let mut layer = TileLayer::new("ground".to_string(), 4, 1);
+        for x in 0..4 {
+            layer.set_tile(x, 0, 1 + x as u32 % 2);
+        }
+        tilemap.add_layer(layer);
+
+        let mut video = VideoManager::headless(256, 64).unwrap();
+        video.load_texture_rgba("blocks", 64, 32, vec![255; 64 * 32 * 4]).unwrap();
+        let mut camera = Camera::new(Vector2::new(256.0, 64.0));
+        camera.snap_to(Vector2::new(128.0, 32.0));
+
+        let stats = tilemap.render_background(&mut video, &camera).unwrap();
+        assert_eq!((stats.submitted, stats.draw_calls, stats.missing), (4, 1, 0));
+        assert_eq!(tilemap.render_foreground(&mut video, &camera).unwrap(), RenderStats::default());
+    }
+}
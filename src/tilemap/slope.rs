@@ -0,0 +1,220 @@
+//! Slope tile geometry for RustUX
+//!
+//! A slope tile is split by a straight surface line. Floor slopes are solid
+//! below the line, ceiling slopes are the same shapes mirrored vertically and
+//! are solid above it. 22.5° slopes span two tiles, so each tile only covers
+//! the lower or upper half of the rise.
+
+use crate::math::{Rect, Vector2};
+use serde::{Deserialize, Serialize};
+
+/// Which way a slope rises
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SlopeDirection {
+    /// Surface goes up from left to right
+    RisingRight,
+    /// Surface goes up from right to left
+    RisingLeft,
+}
+
+/// Steepness of a slope and which part of it a tile covers
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SlopeGradient {
+    /// 45° slope covering a single tile
+    Steep,
+    /// Lower tile of a 22.5° slope spanning two tiles
+    GentleLower,
+    /// Upper tile of a 22.5° slope spanning two tiles
+    GentleUpper,
+}
+
+/// Shape of a slope tile
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SlopeType {
+    pub direction: SlopeDirection,
+    pub gradient: SlopeGradient,
+    /// Solid part is above the surface (ceiling slope) instead of below it
+    #[serde(default)]
+    pub ceiling: bool,
+}
+
+impl SlopeType {
+    /// Create a floor slope
+    pub fn floor(direction: SlopeDirection, gradient: SlopeGradient) -> Self {
+        Self {
+            direction,
+            gradient,
+            ceiling: false,
+        }
+    }
+
+    /// Create a ceiling slope
+    pub fn ceiling(direction: SlopeDirection, gradient: SlopeGradient) -> Self {
+        Self {
+            direction,
+            gradient,
+            ceiling: true,
+        }
+    }
+
+    /// Distance from the top of the tile down to the surface, as a fraction
+    /// of the tile size, at a horizontal fraction `t` across the tile
+    pub fn surface_depth(&self, t: f32) -> f32 {
+        let t = t.clamp(0.0, 1.0);
+        // Fraction across the tile measured from the low end of the slope
+        let rise = match self.direction {
+            SlopeDirection::RisingRight => t,
+            SlopeDirection::RisingLeft => 1.0 - t,
+        };
+        let height = match self.gradient {
+            SlopeGradient::Steep => rise,
+            SlopeGradient::GentleLower => rise * 0.5,
+            SlopeGradient::GentleUpper => 0.5 + rise * 0.5,
+        };
+
+        if self.ceiling {
+            height
+        } else {
+            1.0 - height
+        }
+    }
+
+    /// World y of the surface at world x within the given tile
+    pub fn surface_y(&self, cell: &Rect, x: f32) -> f32 {
+        let t = (x - cell.left()) / cell.width;
+        cell.top() + self.surface_depth(t) * cell.height
+    }
+
+    /// The x a box edge should sample the surface at: the side where the
+    /// floor is highest, or where a ceiling hangs lowest
+    pub fn contact_x(&self, cell: &Rect, rect: &Rect) -> f32 {
+        let x = match self.direction {
+            SlopeDirection::RisingRight => rect.right(),
+            SlopeDirection::RisingLeft => rect.left(),
+        };
+        x.clamp(cell.left(), cell.right())
+    }
+
+    /// Check if a world point lies in the solid part of the tile
+    pub fn contains(&self, cell: &Rect, point: Vector2) -> bool {
+        if !cell.contains_point(point) {
+            return false;
+        }
+        let surface = self.surface_y(cell, point.x);
+        if self.ceiling {
+            point.y < surface
+        } else {
+            point.y > surface
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use SlopeDirection::*;
+    use SlopeGradient::*;
+
+    fn cell() -> Rect {
+        Rect::new(64.0, 32.0, 32.0, 32.0)
+    }
+
+    /// Surface y at the left edge, middle and right edge of the cell
+    fn profile(slope: SlopeType) -> [f32; 3] {
+        let cell = cell();
+        [
+            slope.surface_y(&cell, cell.left()),
+            slope.surface_y(&cell, cell.left() + 16.0),
+            slope.surface_y(&cell, cell.right()),
+        ]
+    }
+
+    #[test]
+    fn steep_floor_rising_right() {
+        assert_eq!(profile(SlopeType::floor(RisingRight, Steep)), [64.0, 48.0, 32.0]);
+    }
+
+    #[test]
+    fn steep_floor_rising_left() {
+        assert_eq!(profile(SlopeType::floor(RisingLeft, Steep)), [32.0, 48.0, 64.0]);
+    }
+
+    #[test]
+    fn gentle_lower_floor_rising_right() {
+        assert_eq!(profile(SlopeType::floor(RisingRight, GentleLower)), [64.0, 56.0, 48.0]);
+    }
+
+    #[test]
+    fn gentle_upper_floor_rising_right() {
+        assert_eq!(profile(SlopeType::floor(RisingRight, GentleUpper)), [48.0, 40.0, 32.0]);
+    }
+
+    #[test]
+    fn gentle_lower_floor_rising_left() {
+        assert_eq!(profile(SlopeType::floor(RisingLeft, GentleLower)), [48.0, 56.0, 64.0]);
+    }
+
+    #[test]
+    fn gentle_upper_floor_rising_left() {
+        assert_eq!(profile(SlopeType::floor(RisingLeft, GentleUpper)), [32.0, 40.0, 48.0]);
+    }
+
+    #[test]
+    fn steep_ceiling_rising_right() {
+        assert_eq!(profile(SlopeType::ceiling(RisingRight, Steep)), [32.0, 48.0, 64.0]);
+    }
+
+    #[test]
+    fn steep_ceiling_rising_left() {
+        assert_eq!(profile(SlopeType::ceiling(RisingLeft, Steep)), [64.0, 48.0, 32.0]);
+    }
+
+    #[test]
+    fn gentle_ceilings() {
+        assert_eq!(profile(SlopeType::ceiling(RisingRight, GentleLower)), [32.0, 40.0, 48.0]);
+        assert_eq!(profile(SlopeType::ceiling(RisingRight, GentleUpper)), [48.0, 56.0, 64.0]);
+        assert_eq!(profile(SlopeType::ceiling(RisingLeft, GentleLower)), [48.0, 40.0, 32.0]);
+        assert_eq!(profile(SlopeType::ceiling(RisingLeft, GentleUpper)), [64.0, 56.0, 48.0]);
+    }
+
+    #[test]
+    fn gentle_halves_join_up() {
+        let lower = SlopeType::floor(RisingRight, GentleLower);
+        let upper = SlopeType::floor(RisingRight, GentleUpper);
+        let left = cell();
+        let right = Rect::new(left.right(), left.top(), 32.0, 32.0);
+        assert_eq!(lower.surface_y(&left, left.right()), upper.surface_y(&right, right.left()));
+    }
+
+    #[test]
+    fn surface_is_clamped_to_the_cell() {
+        let slope = SlopeType::floor(RisingRight, Steep);
+        let cell = cell();
+        assert_eq!(slope.surface_y(&cell, cell.left() - 100.0), cell.bottom());
+        assert_eq!(slope.surface_y(&cell, cell.right() + 100.0), cell.top());
+    }
+
+    #[test]
+    fn contact_x_uses_the_high_side() {
+        let cell = cell();
+        let body = Rect::new(70.0, 0.0, 10.0, 20.0);
+        assert_eq!(SlopeType::floor(RisingRight, Steep).contact_x(&cell, &body), 80.0);
+        assert_eq!(SlopeType::floor(RisingLeft, Steep).contact_x(&cell, &body), 70.0);
+        let overhanging = Rect::new(80.0, 0.0, 32.0, 20.0);
+        assert_eq!(SlopeType::floor(RisingRight, Steep).contact_x(&cell, &overhanging), 96.0);
+    }
+
+    #[test]
+    fn contains_solid_side_only() {
+        let cell = cell();
+        let floor = SlopeType::floor(RisingRight, Steep);
+        assert!(floor.contains(&cell, Vector2::new(90.0, 60.0)));
+        assert!(!floor.contains(&cell, Vector2::new(70.0, 36.0)));
+        let ceiling = SlopeType::ceiling(RisingRight, Steep);
+        assert!(ceiling.contains(&cell, Vector2::new(90.0, 36.0)));
+        assert!(!ceiling.contains(&cell, Vector2::new(70.0, 60.0)));
+        assert!(!floor.contains(&cell, Vector2::new(0.0, 60.0)));
+    }
+}
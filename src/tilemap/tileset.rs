@@ -0,0 +1,180 @@
+//! Tileset definitions for RustUX
+//!
+//! A tileset maps tile ids to a texture region and a set of gameplay
+//! attributes. Tilesets are stored as JSON files under `tilesets/<name>.json`
+//! in the data directory.
+
+use crate::tilemap::slope::SlopeType;
+use crate::math::Rect;
+use crate::config::TILE_SIZE;
+use crate::util::{Error, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::Path;
+
+/// Gameplay attributes of a tile
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TileAttributes {
+    /// Blocks movement from all sides
+    #[serde(default)]
+    pub solid: bool,
+    /// Only blocks movement from above
+    #[serde(default)]
+    pub one_way: bool,
+    /// Slope geometry, if this is a slope tile
+    #[serde(default)]
+    pub slope: Option<SlopeType>,
+    /// Damages whoever touches it
+    #[serde(default)]
+    pub hurts: bool,
+    /// Slippery surface
+    #[serde(default)]
+    pub ice: bool,
+}
+
+impl TileAttributes {
+    /// Attributes of a plain solid block
+    pub fn solid() -> Self {
+        Self {
+            solid: true,
+            ..Self::default()
+        }
+    }
+
+    /// Check if the tile takes part in collision at all
+    pub fn is_collidable(&self) -> bool {
+        self.solid || self.one_way || self.slope.is_some()
+    }
+
+    /// Check if the tile has no attributes
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+}
+
+/// A single tile in a tileset
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TileDefinition {
+    /// Tile id used in tile layers (0 is reserved for "no tile")
+    pub id: u32,
+    /// Name of the texture containing the tile
+    pub texture: String,
+    /// Column and row of the tile within its texture, in tiles
+    #[serde(default)]
+    pub cell: Option<[u32; 2]>,
+    /// Gameplay attributes
+    #[serde(flatten)]
+    pub attributes: TileAttributes,
+}
+
+impl TileDefinition {
+    /// Get the source rectangle of the tile within its texture
+    pub fn source_rect(&self) -> Option<Rect> {
+        let tile_size = TILE_SIZE as f32;
+        self.cell.map(|[column, row]| {
+            Rect::new(column as f32 * tile_size, row as f32 * tile_size, tile_size, tile_size)
+        })
+    }
+}
+
+/// On-disk representation of a tileset
+#[derive(Debug, Serialize, Deserialize)]
+struct TilesetFile {
+    name: String,
+    #[serde(default = "default_tile_size")]
+    tile_size: u32,
+    #[serde(default)]
+    textures: HashMap<String, String>,
+    tiles: Vec<TileDefinition>,
+}
+
+fn default_tile_size() -> u32 {
+    TILE_SIZE
+}
+
+/// Collection of tile definitions keyed by tile id
+#[derive(Debug, Clone, Default)]
+pub struct Tileset {
+    /// Tileset name
+    pub name: String,
+    /// Texture names used by the tileset mapped to their file paths
+    pub textures: HashMap<String, String>,
+    tiles: HashMap<u32, TileDefinition>,
+}
+
+impl Tileset {
+    /// Create an empty tileset
+    pub fn new(name: String) -> Self {
+        Self {
+            name,
+            textures: HashMap::new(),
+            tiles: HashMap::new(),
+        }
+    }
+
+    /// Parse a tileset from a JSON string
+    pub fn from_json(json: &str) -> Result<Self> {
+        let file: TilesetFile = serde_json::from_str(json)
+            .map_err(|e| Error::LevelLoading(format!("Invalid tileset JSON: {}", e)))?;
+
+        if file.tile_size != TILE_SIZE {
+            return Err(Error::LevelLoading(format!(
+                "Tileset '{}' uses {}px tiles, but the game grid is {}px",
+                file.name, file.tile_size, TILE_SIZE
+            )));
+        }
+
+        let mut tileset = Self::new(file.name);
+        tileset.textures = file.textures;
+        for tile in file.tiles {
+            if tile.id == 0 {
+                return Err(Error::LevelLoading(format!(
+                    "Tileset '{}' defines tile id 0, which is reserved for empty cells",
+                    tileset.name
+                )));
+            }
+            tileset.add_tile(tile);
+        }
+        Ok(tileset)
+    }
+
+    /// Load a tileset from a JSON file
+    pub fn load_from_file<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let content = std::fs::read_to_string(path)?;
+        Self::from_json(&content)
+    }
+
+    /// Save the tileset to a JSON file
+    pub fn save_to_file<P: AsRef<Path>>(&self, path: P) -> Result<()> {
+        let mut tiles: Vec<TileDefinition> = self.tiles.values().cloned().collect();
+        tiles.sort_by_key(|tile| tile.id);
+        let file = TilesetFile {
+            name: self.name.clone(),
+            tile_size: TILE_SIZE,
+            textures: self.textures.clone(),
+            tiles,
+        };
+        std::fs::write(path, serde_json::to_string_pretty(&file)?)?;
+        Ok(())
+    }
+
+    /// Add or replace a tile definition
+    pub fn add_tile(&mut self, tile: TileDefinition) {
+        self.tiles.insert(tile.id, tile);
+    }
+
+    /// Get a tile definition by id
+    pub fn get(&self, id: u32) -> Option<&TileDefinition> {
+        self.tiles.get(&id)
+    }
+
+    /// Get the attributes of a tile (empty for unknown tiles)
+    pub fn attributes(&self, id: u32) -> TileAttributes {
+        self.tiles.get(&id).map(|tile| tile.attributes).unwrap_or_default()
+    }
+
+    /// Get the number of tiles in the tileset
+    pub fn tile_count(&self) -> usize {
+        self.tiles.len()
+    }
+}
@@ -1,25 +1,219 @@
 //! Trigger system for RustUX
+//!
+//! A trigger is an invisible area backed by a sensor body in the physics
+//! world. When the player's contact with it matches the trigger's activation
+//! mode, the trigger fires its actions. One-shot triggers deactivate after
+//! firing once; repeatable triggers fire every time.
 
-use crate::util::Result;
+use crate::level::LevelObject;
 use crate::math::Rect;
+use crate::physics::ContactPhase;
+use serde::{Deserialize, Serialize};
 
+/// When a trigger fires
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ActivationMode {
+    /// When the player enters the area
+    #[default]
+    Enter,
+    /// When the player leaves the area
+    Exit,
+    /// Every physics step while the player is inside the area
+    Stay,
+}
+
+/// Effect of a trigger firing
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "action", rename_all = "snake_case")]
+pub enum TriggerAction {
+    /// End the level
+    EndSequence,
+    /// Move the player to another spot, e.g. through a door
+    Teleport { x: f32, y: f32 },
+    /// Show a message to the player
+    Message {
+        text: String,
+        /// How long the message stays up (seconds)
+        #[serde(default = "default_message_duration")]
+        duration: f32,
+    },
+    /// Add an object to the level
+    SpawnObject { object: Box<LevelObject> },
+    /// Switch the background music
+    ChangeMusic { music: String },
+}
+
+fn default_message_duration() -> f32 {
+    3.0
+}
+
+/// Invisible area that fires actions when the player touches it
+#[derive(Debug, Clone)]
 pub struct Trigger {
+    pub name: String,
     pub area: Rect,
     pub active: bool,
+    /// Sensor body covering the area, once added to a world
+    pub body_id: Option<u32>,
+    pub mode: ActivationMode,
+    /// Fire every time instead of only once
+    pub repeatable: bool,
+    pub actions: Vec<TriggerAction>,
+    fire_count: u32,
 }
 
 impl Trigger {
     pub fn new(area: Rect) -> Self {
-        Self { area, active: true }
+        Self {
+            name: String::new(),
+            area,
+            active: true,
+            body_id: None,
+            mode: ActivationMode::Enter,
+            repeatable: false,
+            actions: Vec::new(),
+            fire_count: 0,
+        }
+    }
+
+    pub fn with_name(mut self, name: String) -> Self {
+        self.name = name;
+        self
+    }
+
+    pub fn with_mode(mut self, mode: ActivationMode) -> Self {
+        self.mode = mode;
+        self
+    }
+
+    pub fn with_repeatable(mut self, repeatable: bool) -> Self {
+        self.repeatable = repeatable;
+        self
+    }
+
+    pub fn with_action(mut self, action: TriggerAction) -> Self {
+        self.actions.push(action);
+        self
+    }
+
+    /// Check if the player's rectangle is inside an active trigger
+    pub fn check_activation(&self, player_rect: &Rect) -> bool {
+        self.active && self.area.intersects(player_rect)
+    }
+
+    /// Handle a contact with the player, returning true if the trigger fires
+    ///
+    /// One-shot triggers deactivate when they fire.
+    pub fn activate(&mut self, phase: ContactPhase) -> bool {
+        if !self.active {
+            return false;
+        }
+
+        let fires = matches!(
+            (self.mode, phase),
+            (ActivationMode::Enter, ContactPhase::Begin)
+                | (ActivationMode::Exit, ContactPhase::End)
+                | (ActivationMode::Stay, ContactPhase::Begin | ContactPhase::Stay)
+        );
+        if !fires {
+            return false;
+        }
+
+        self.fire_count += 1;
+        if !self.repeatable {
+            self.active = false;
+        }
+        true
+    }
+
+    /// Get how many times the trigger has fired
+    pub fn fire_count(&self) -> u32 {
+        self.fire_count
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::control::InputManager;
+    use crate::math::Vector2;
+    use crate::physics::{BodyType, PhysicsBody};
+    use crate::supertux::GameWorld;
+
+    fn trigger(mode: ActivationMode, repeatable: bool) -> Trigger {
+        Trigger::new(Rect::new(0.0, 0.0, 32.0, 32.0)).with_mode(mode).with_repeatable(repeatable)
+    }
+
+    /// Feed a touch of the trigger through, returning which phases fired it
+    fn touch(trigger: &mut Trigger) -> Vec<ContactPhase> {
+        [ContactPhase::Begin, ContactPhase::Stay, ContactPhase::Stay, ContactPhase::End]
+            .into_iter()
+            .filter(|&phase| trigger.activate(phase))
+            .collect()
+    }
+
+    #[test]
+    fn one_shot_triggers_fire_once_in_their_phase() {
+        let cases = [
+            (ActivationMode::Enter, ContactPhase::Begin),
+            (ActivationMode::Exit, ContactPhase::End),
+            (ActivationMode::Stay, ContactPhase::Begin),
+        ];
+        for (mode, phase) in cases {
+            let mut trigger = trigger(mode, false);
+            assert_eq!(touch(&mut trigger), vec![phase], "{:?}", mode);
+            assert!(!trigger.active);
+            assert!(touch(&mut trigger).is_empty());
+            assert_eq!(trigger.fire_count(), 1);
+        }
     }
 
-    pub fn check_activation(&self, _player_rect: &Rect) -> bool {
-        // TODO: Implement trigger activation logic
-        false
+    #[test]
+    fn repeatable_triggers_fire_on_every_touch() {
+        let mut enter = trigger(ActivationMode::Enter, true);
+        let mut exit = trigger(ActivationMode::Exit, true);
+        let mut stay = trigger(ActivationMode::Stay, true);
+        for _ in 0..2 {
+            assert_eq!(touch(&mut enter), vec![ContactPhase::Begin]);
+            assert_eq!(touch(&mut exit), vec![ContactPhase::End]);
+            assert_eq!(touch(&mut stay), vec![ContactPhase::Begin, ContactPhase::Stay, ContactPhase::Stay]);
+        }
+        assert!(enter.active && exit.active && stay.active);
+        assert_eq!((enter.fire_count(), exit.fire_count(), stay.fire_count()), (2, 2, 6));
     }
 
-    pub fn execute(&self) -> Result<()> {
-        // TODO: Implement trigger effects
-        Ok(())
+    #[test]
+    fn inactive_triggers_never_fire() {
+        let mut trigger = trigger(ActivationMode::Stay, true);
+        trigger.active = false;
+        assert!(touch(&mut trigger).is_empty());
+        assert!(!trigger.check_activation(&Rect::new(0.0, 0.0, 16.0, 16.0)));
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn touching_a_trigger_runs_its_actions() {
+        let mut world = GameWorld::new();
+        world.create_player(Vector2::new(100.0, 168.0), "tux".to_string());
+        let (_, physics_world) = world.managers_mut();
+        physics_world.add_body(PhysicsBody::new(0, Vector2::new(0.0, 200.0), Vector2::new(2000.0, 32.0), BodyType::Static));
+        world.add_trigger(
+            Trigger::new(Rect::new(80.0, 136.0, 64.0, 64.0))
+                .with_action(TriggerAction::Teleport { x: 600.0, y: 168.0 })
+                .with_action(TriggerAction::Message { text: "Hello".to_string(), duration: 2.0 }),
+        );
+
+        let input = InputManager::new().unwrap();
+        world.update(&input, 1.0 / 60.0).unwrap();
+
+        assert!((world.get_player().unwrap().position().x - 600.0).abs() < 1.0);
+        let actions = world.take_trigger_actions();
+        assert!(matches!(actions.as_slice(), [TriggerAction::Message { text, .. }] if text == "Hello"));
+        assert!(!world.triggers()[0].active);
+
+        // The spent trigger stays quiet when Tux comes back
+        world.teleport_player(Vector2::new(100.0, 168.0));
+        world.update(&input, 1.0 / 60.0).unwrap();
+        assert!(world.take_trigger_actions().is_empty());
+    }
+}
@@ -0,0 +1,81 @@
+//! Render backends for RustUX
+//!
+//! A backend does the actual drawing for the `VideoManager`: into an SDL
+//! window, or into an in-memory image for tests and CI.
+
+use crate::math::{Rect, Vector2};
+use crate::util::Result;
+use crate::video::{BlendMode, Color, DrawParams};
+use image::RgbaImage;
+
+/// Which backend the engine draws with
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RenderBackendKind {
+    /// Hardware-accelerated SDL window
+    Window,
+    /// In-memory RGBA frame of the given size, no window needed
+    Headless { width: u32, height: u32 },
+}
+
+/// Drawing operations a backend has to provide
+pub trait RenderBackend {
+    /// Size of the drawing area in pixels
+    fn output_size(&self) -> Vector2;
+
+    /// Fill the whole drawing area with a color, ignoring the clip rectangle
+    fn clear(&mut self, color: Color);
+
+    /// Show the finished frame
+    fn present(&mut self);
+
+    /// Create a texture from raw RGBA pixels, replacing any texture with the same name
+    fn load_texture_rgba(&mut self, name: &str, width: u32, height: u32, pixels: Vec<u8>) -> Result<()>;
+
+    /// Destroy a texture, returning false if it wasn't loaded
+    fn remove_texture(&mut self, name: &str) -> bool;
+
+    /// Get the size of a texture in pixels
+    fn texture_size(&self, name: &str) -> Option<Vector2>;
+
+    /// Draw part of a texture (`src`, or all of it) into `dst`
+    fn draw_texture(&mut self, name: &str, src: Option<Rect>, dst: Rect, params: &DrawParams) -> Result<()>;
+
+    /// Draw several (src, dst, params) quads from one texture, in order
+    fn draw_texture_batch(&mut self, name: &str, quads: &[(Option<Rect>, Rect, DrawParams)]) -> Result<()> {
+        for (src, dst, params) in quads {
+            self.draw_texture(name, *src, *dst, params)?;
+        }
+        Ok(())
+    }
+
+    /// Set the blend mode used for shapes and lines
+    fn set_blend_mode(&mut self, blend: BlendMode);
+
+    fn fill_rect(&mut self, rect: Rect, color: Color) -> Result<()>;
+
+    fn draw_rect(&mut self, rect: Rect, color: Color) -> Result<()>;
+
+    fn draw_line(&mut self, from: Vector2, to: Vector2, color: Color) -> Result<()>;
+
+    /// Restrict drawing to a rectangle, or allow drawing everywhere with `None`
+    fn set_clip_rect(&mut self, clip: Option<Rect>);
+
+    /// Read back the current frame
+    fn read_pixels(&self) -> Result<RgbaImage>;
+
+    /// Switch between windowed and fullscreen; backends without a window ignore this
+    fn set_fullscreen(&mut self, fullscreen: bool) -> Result<()> {
+        let _ = fullscreen;
+        Ok(())
+    }
+}
+
+/// Whole pixels covered by a rectangle as (x, y, width, height), rounding
+/// its edges so neighbouring rectangles don't leave gaps
+pub(crate) fn pixel_rect(rect: Rect) -> (i32, i32, u32, u32) {
+    let left = rect.left().round();
+    let top = rect.top().round();
+    let width = (rect.right().round() - left).max(0.0);
+    let height = (rect.bottom().round() - top).max(0.0);
+    (left as i32, top as i32, width as u32, height as u32)
+}
@@ -0,0 +1,278 @@
+//! Parallax background layers for RustUX
+//!
+//! A background layer is an image or a gradient drawn behind the level. Image
+//! layers follow the camera by a parallax factor per axis (0 = fixed to the
+//! screen, 1 = moves with the world), can repeat along either axis and can
+//! scroll on their own. Gradients always fill the whole screen.
+
+use crate::math::{Rect, Vector2};
+use crate::util::{Error, Result};
+use crate::video::{Camera, Color, DrawParams, VideoManager};
+use serde::{Deserialize, Serialize};
+
+/// Axes along which a background image is tiled
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RepeatMode {
+    /// Draw the image once
+    None,
+    /// Tile the image left and right
+    #[default]
+    Horizontal,
+    /// Tile the image up and down
+    Vertical,
+    /// Tile the image across the whole screen
+    Both,
+}
+
+impl RepeatMode {
+    fn repeats_x(self) -> bool {
+        matches!(self, RepeatMode::Horizontal | RepeatMode::Both)
+    }
+
+    fn repeats_y(self) -> bool {
+        matches!(self, RepeatMode::Vertical | RepeatMode::Both)
+    }
+}
+
+/// Direction a gradient runs in
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum GradientDirection {
+    /// From the top of the screen to the bottom
+    #[default]
+    Vertical,
+    /// From the left of the screen to the right
+    Horizontal,
+}
+
+/// What a background layer draws
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum BackgroundFill {
+    /// A texture, drawn at its natural size
+    Image {
+        texture: String,
+        #[serde(default)]
+        repeat: RepeatMode,
+    },
+    /// A color gradient covering the screen
+    Gradient {
+        from: Color,
+        to: Color,
+        #[serde(default)]
+        direction: GradientDirection,
+    },
+}
+
+/// A background layer drawn relative to the camera
+#[derive(Debug, Clone, PartialEq)]
+pub struct BackgroundLayer {
+    pub fill: BackgroundFill,
+    /// How much the layer moves with the camera on each axis
+    pub parallax: Vector2,
+    /// Position of the image at camera position zero
+    pub offset: Vector2,
+    /// Automatic scrolling in pixels per second
+    pub scroll_speed: Vector2,
+    /// Rendering layer (higher = rendered on top)
+    pub z: i32,
+    /// Distance scrolled so far
+    scroll: Vector2,
+}
+
+impl BackgroundLayer {
+    /// Create an image layer moving at half the camera speed
+    pub fn image(texture: &str, repeat: RepeatMode) -> Self {
+        Self::new(BackgroundFill::Image {
+            texture: texture.to_string(),
+            repeat,
+        })
+        .with_parallax(Vector2::splat(0.5))
+    }
+
+    /// Create a gradient layer
+    pub fn gradient(from: Color, to: Color, direction: GradientDirection) -> Self {
+        Self::new(BackgroundFill::Gradient { from, to, direction })
+    }
+
+    /// Create a layer fixed to the screen
+    pub fn new(fill: BackgroundFill) -> Self {
+        Self {
+            fill,
+            parallax: Vector2::ZERO,
+            offset: Vector2::ZERO,
+            scroll_speed: Vector2::ZERO,
+            z: 0,
+            scroll: Vector2::ZERO,
+        }
+    }
+
+    pub fn with_parallax(mut self, parallax: Vector2) -> Self {
+        self.parallax = parallax;
+        self
+    }
+
+    pub fn with_offset(mut self, offset: Vector2) -> Self {
+        self.offset = offset;
+        self
+    }
+
+    pub fn with_scroll_speed(mut self, scroll_speed: Vector2) -> Self {
+        self.scroll_speed = scroll_speed;
+        self
+    }
+
+    pub fn with_z(mut self, z: i32) -> Self {
+        self.z = z;
+        self
+    }
+
+    /// Advance the automatic scrolling
+    pub fn update(&mut self, delta_time: f32) {
+        self.scroll += self.scroll_speed * delta_time;
+    }
+
+    /// Screen position of the image's top-left corner (before repeating)
+    pub fn screen_origin(&self, camera: &Camera) -> Vector2 {
+        (self.offset + self.scroll - camera.view().top_left() * self.parallax) * camera.zoom()
+    }
+
+    /// Draw the layer
+    pub fn render(&self, video: &mut VideoManager, camera: &Camera) -> Result<()> {
+        let viewport = video.output_size();
+        match &self.fill {
+            BackgroundFill::Image { texture, repeat } => {
+                let size = video.texture_size(texture)
+                    .ok_or_else(|| Error::SpriteLoading(format!("Texture not found: {}", texture)))?
+                    * camera.zoom();
+                let origin = self.screen_origin(camera);
+
+                let mut quads = Vec::new();
+                for y in tile_positions(origin.y, size.y, viewport.y, repeat.repeats_y()) {
+                    for x in tile_positions(origin.x, size.x, viewport.x, repeat.repeats_x()) {
+                        quads.push((None, Rect::new(x, y, size.x, size.y), DrawParams::default()));
+                    }
+                }
+                video.draw_texture_batch(texture, &quads)
+            }
+            BackgroundFill::Gradient { from, to, direction } => {
+                let texture = gradient_texture(video, *from, *to, *direction)?;
+                video.draw_texture(&texture, None, Rect::new(0.0, 0.0, viewport.x, viewport.y), &DrawParams::default())
+            }
+        }
+    }
+}
+
+/// Get the texture of a gradient, one pixel wide (or high) and as long as the
+/// screen, creating it on first use and again whenever the screen is resized
+fn gradient_texture(video: &mut VideoManager, from: Color, to: Color, direction: GradientDirection) -> Result<String> {
+    let viewport = video.output_size();
+    let (width, height) = match direction {
+        GradientDirection::Vertical => (1, viewport.y.max(1.0) as u32),
+        GradientDirection::Horizontal => (viewport.x.max(1.0) as u32, 1),
+    };
+
+    let name = format!("gradient:{:?}:{:?}:{:?}", from, to, direction);
+    if video.texture_size(&name) != Some(Vector2::new(width as f32, height as f32)) {
+        let steps = width.max(height);
+        let pixels = (0..steps)
+            .flat_map(|step| {
+                let color = from.lerp(to, step as f32 / (steps.max(2) - 1) as f32);
+                [color.r, color.g, color.b, color.a]
+            })
+            .collect();
+        video.load_texture_rgba(&name, width, height, pixels)?;
+    }
+    Ok(name)
+}
+
+/// Positions along one axis where a tile of `size` has to be drawn to cover
+/// `0..length`, or just `origin` if the image doesn't repeat
+fn tile_positions(origin: f32, size: f32, length: f32, repeat: bool) -> Vec<f32> {
+    if !repeat || size <= 0.0 {
+        let visible = origin < length && origin + size > 0.0;
+        return if visible { vec![origin] } else { Vec::new() };
+    }
+
+    let mut position = origin.rem_euclid(size);
+    if position > 0.0 {
+        position -= size;
+    }
+
+    let mut positions = Vec::new();
+    while position < length {
+        positions.push(position);
+        position += size;
+    }
+    positions
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn repeated_tiles_cover_the_screen() {
+        assert_eq!(tile_positions(10.0, 40.0, 100.0, true), [-30.0, 10.0, 50.0, 90.0]);
+        assert_eq!(tile_positions(-85.0, 40.0, 100.0, true), [-5.0, 35.0, 75.0]);
+        assert_eq!(tile_positions(0.0, 50.0, 100.0, true), [0.0, 50.0]);
+        assert_eq!(tile_positions(10.0, 40.0, 100.0, false), [10.0]);
+        assert!(tile_positions(120.0, 40.0, 100.0, false).is_empty());
+    }
+
+    #[test]
+    fn layers_follow_the_camera_by_their_parallax_factor() {
+        let mut camera = Camera::new(Vector2::new(100.0, 100.0));
+        camera.snap_to(Vector2::new(250.0, 50.0));
+
+        let mut layer = BackgroundLayer::image("sky", RepeatMode::Both)
+            .with_parallax(Vector2::new(0.5, 0.0))
+            .with_offset(Vector2::new(0.0, 10.0))
+            .with_scroll_speed(Vector2::new(-20.0, 0.0));
+        assert_eq!(layer.screen_origin(&camera), Vector2::new(-100.0, 10.0));
+
+        layer.update(0.5);
+        assert_eq!(layer.screen_origin(&camera), Vector2::new(-110.0, 10.0));
+    }
+
+    #[test]
+    fn images_tile_and_gradients_fill_the_screen() {
+        let mut video = VideoManager::headless(16, 8).unwrap();
+        video.load_texture_rgba("stripe", 3, 1, [[255, 0, 0, 255], [0, 255, 0, 255], [0, 0, 255, 255]].concat()).unwrap();
+        let camera = Camera::new(Vector2::new(16.0, 8.0));
+
+        BackgroundLayer::gradient(Color::BLACK, Color::WHITE, GradientDirection::Vertical)
+            .render(&mut video, &camera)
+            .unwrap();
+        let frame = video.screenshot().unwrap();
+        assert_eq!(frame.get_pixel(5, 0).0, [0, 0, 0, 255]);
+        assert_eq!(frame.get_pixel(5, 7).0, [255, 255, 255, 255]);
+
+        BackgroundLayer::image("stripe", RepeatMode::Horizontal)
+            .with_offset(Vector2::new(1.0, 2.0))
+            .render(&mut video, &camera)
+            .unwrap();
+        let frame = video.screenshot().unwrap();
+        let row: Vec<_> = (0..5).map(|x| frame.get_pixel(x, 2).0[..3].to_vec()).collect();
+        assert_eq!(row, [[0, 0, 255], [255, 0, 0], [0, 255, 0], [0, 0, 255], [255, 0, 0]]);
+        // Rows outside the image keep the gradient
+        assert_eq!(frame.get_pixel(1, 1).0[0], frame.get_pixel(1, 1).0[2]);
+    }
+
+    #[test]
+    fn gradients_draw_one_cached_texture() {
+        let mut video = VideoManager::headless(16, 8).unwrap();
+        let camera = Camera::new(Vector2::new(16.0, 8.0));
+        let layer = BackgroundLayer::gradient(Color::BLACK, Color::WHITE, GradientDirection::Horizontal);
+
+        layer.render(&mut video, &camera).unwrap();
+        let name = gradient_texture(&mut video, Color::BLACK, Color::WHITE, GradientDirection::Horizontal).unwrap();
+        assert_eq!(video.texture_size(&name), Some(Vector2::new(16.0, 1.0)));
+
+        let frame = video.screenshot().unwrap();
+        assert_eq!(frame.get_pixel(0, 3).0, [0, 0, 0, 255]);
+        assert_eq!(frame.get_pixel(5, 3).0, [85, 85, 85, 255]);
+        assert_eq!(frame.get_pixel(15, 3).0, [255, 255, 255, 255]);
+    }
+}
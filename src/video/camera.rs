@@ -0,0 +1,312 @@
+//! Camera for RustUX
+//!
+//! The camera follows a target through a dead zone, looks ahead in the
+//! direction the target is moving, eases towards where it wants to be and
+//! stays inside the level bounds. Rendering goes through `world_to_screen`,
+//! which also applies zoom and screen shake.
+
+use crate::config::{SCREEN_HEIGHT, SCREEN_WIDTH};
+use crate::math::{Rect, Vector2};
+
+/// How fast the screen shakes back and forth (radians/second)
+const SHAKE_FREQUENCY: f32 = 60.0;
+
+/// Smallest zoom the camera allows
+const MIN_ZOOM: f32 = 0.1;
+
+/// View of the world that follows a target
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Camera {
+    /// Center of the view in world coordinates
+    center: Vector2,
+    /// Size of the screen area the camera draws to (pixels)
+    viewport_size: Vector2,
+    zoom: f32,
+    /// Area the view has to stay inside
+    bounds: Option<Rect>,
+    /// Size of the box around the center the target can move in freely
+    dead_zone: Vector2,
+    /// How far ahead of a moving target the camera looks
+    look_ahead: f32,
+    /// Current look-ahead offset, eased towards the target's direction
+    look_offset: f32,
+    /// How quickly the camera catches up with its target (1/seconds, 0 = instantly)
+    smoothing: f32,
+    shake_magnitude: f32,
+    shake_duration: f32,
+    shake_time: f32,
+}
+
+impl Camera {
+    /// Create a camera drawing to a viewport of the given size
+    pub fn new(viewport_size: Vector2) -> Self {
+        Self {
+            center: viewport_size * 0.5,
+            viewport_size,
+            zoom: 1.0,
+            bounds: None,
+            dead_zone: Vector2::ZERO,
+            look_ahead: 0.0,
+            look_offset: 0.0,
+            smoothing: 0.0,
+            shake_magnitude: 0.0,
+            shake_duration: 0.0,
+            shake_time: 0.0,
+        }
+    }
+
+    /// Camera set up for following the player
+    pub fn for_player() -> Self {
+        Self::new(Vector2::new(SCREEN_WIDTH as f32, SCREEN_HEIGHT as f32))
+            .with_dead_zone(Vector2::new(64.0, 96.0))
+            .with_look_ahead(96.0)
+            .with_smoothing(8.0)
+    }
+
+    pub fn with_dead_zone(mut self, dead_zone: Vector2) -> Self {
+        self.dead_zone = dead_zone.max(Vector2::ZERO);
+        self
+    }
+
+    pub fn with_look_ahead(mut self, look_ahead: f32) -> Self {
+        self.look_ahead = look_ahead.max(0.0);
+        self
+    }
+
+    pub fn with_smoothing(mut self, smoothing: f32) -> Self {
+        self.smoothing = smoothing.max(0.0);
+        self
+    }
+
+    pub fn with_bounds(mut self, bounds: Rect) -> Self {
+        self.set_bounds(Some(bounds));
+        self
+    }
+
+    /// Set the area the view has to stay inside
+    pub fn set_bounds(&mut self, bounds: Option<Rect>) {
+        self.bounds = bounds;
+        self.center = self.clamp_center(self.center);
+    }
+
+    /// Get the area the view has to stay inside
+    pub fn bounds(&self) -> Option<Rect> {
+        self.bounds
+    }
+
+    /// Set the size of the screen area the camera draws to
+    pub fn set_viewport_size(&mut self, viewport_size: Vector2) {
+        self.viewport_size = viewport_size;
+        self.center = self.clamp_center(self.center);
+    }
+
+    /// Get the size of the screen area the camera draws to
+    pub fn viewport_size(&self) -> Vector2 {
+        self.viewport_size
+    }
+
+    /// Set the zoom factor (2.0 shows half as much of the world, twice as big)
+    pub fn set_zoom(&mut self, zoom: f32) {
+        self.zoom = zoom.max(MIN_ZOOM);
+        self.center = self.clamp_center(self.center);
+    }
+
+    /// Get the zoom factor
+    pub fn zoom(&self) -> f32 {
+        self.zoom
+    }
+
+    /// Get the center of the view, without shake
+    pub fn center(&self) -> Vector2 {
+        self.center
+    }
+
+    /// Get the top-left corner of the view in world coordinates, without shake
+    pub fn position(&self) -> Vector2 {
+        self.center - self.visible_size() * 0.5
+    }
+
+    /// Size of the world area the camera shows
+    pub fn visible_size(&self) -> Vector2 {
+        self.viewport_size / self.zoom
+    }
+
+    /// Get the world area currently on screen, including shake
+    pub fn view(&self) -> Rect {
+        Rect::from_pos_size(self.position() + self.shake_offset(), self.visible_size())
+    }
+
+    /// Center the view on a point immediately
+    pub fn snap_to(&mut self, target: Vector2) {
+        self.look_offset = 0.0;
+        self.center = self.clamp_center(target);
+    }
+
+    /// Follow a target moving with the given velocity
+    pub fn update(&mut self, target: Vector2, velocity: Vector2, delta_time: f32) {
+        // Look ahead in the direction the target is walking
+        let look_goal = if velocity.x.abs() > f32::EPSILON {
+            self.look_ahead * velocity.x.signum()
+        } else {
+            self.look_offset
+        };
+        self.look_offset += (look_goal - self.look_offset) * self.ease(delta_time);
+        let focus = Vector2::new(target.x + self.look_offset, target.y);
+
+        // Only move when the focus leaves the dead zone, and just far enough to bring it back
+        let half_zone = self.dead_zone * 0.5;
+        let offset = focus - self.center;
+        let excess = offset - offset.clamp(-half_zone, half_zone);
+        let goal = self.clamp_center(self.center + excess);
+
+        self.center += (goal - self.center) * self.ease(delta_time);
+        self.center = self.clamp_center(self.center);
+
+        if self.shake_time > 0.0 {
+            self.shake_time = (self.shake_time - delta_time).max(0.0);
+        }
+    }
+
+    /// Shake the screen, fading out over `duration` seconds
+    pub fn shake(&mut self, magnitude: f32, duration: f32) {
+        if duration <= 0.0 {
+            return;
+        }
+        self.shake_magnitude = magnitude;
+        self.shake_duration = duration;
+        self.shake_time = duration;
+    }
+
+    /// Check if the screen is shaking
+    pub fn is_shaking(&self) -> bool {
+        self.shake_time > 0.0
+    }
+
+    /// Convert a world position to screen coordinates
+    pub fn world_to_screen(&self, position: Vector2) -> Vector2 {
+        (position - self.view().top_left()) * self.zoom
+    }
+
+    /// Convert a screen position to world coordinates
+    pub fn screen_to_world(&self, position: Vector2) -> Vector2 {
+        position / self.zoom + self.view().top_left()
+    }
+
+    /// Convert a world rectangle to screen coordinates
+    pub fn world_rect_to_screen(&self, rect: &Rect) -> Rect {
+        let position = self.world_to_screen(rect.top_left());
+        Rect::new(position.x, position.y, rect.width * self.zoom, rect.height * self.zoom)
+    }
+
+    /// Check if any part of a world rectangle is on screen
+    pub fn is_visible(&self, rect: &Rect) -> bool {
+        self.view().intersects(rect)
+    }
+
+    /// Fraction of the distance to the goal covered this frame
+    fn ease(&self, delta_time: f32) -> f32 {
+        if self.smoothing <= 0.0 {
+            1.0
+        } else {
+            1.0 - (-self.smoothing * delta_time).exp()
+        }
+    }
+
+    /// Current shake displacement
+    fn shake_offset(&self) -> Vector2 {
+        if self.shake_time <= 0.0 {
+            return Vector2::ZERO;
+        }
+        let elapsed = self.shake_duration - self.shake_time;
+        let strength = self.shake_magnitude * self.shake_time / self.shake_duration;
+        Vector2::new(
+            (elapsed * SHAKE_FREQUENCY).sin(),
+            (elapsed * SHAKE_FREQUENCY * 1.3).cos(),
+        ) * strength
+    }
+
+    /// Keep the view inside the bounds, centering it on bounds smaller than the view
+    fn clamp_center(&self, center: Vector2) -> Vector2 {
+        let bounds = match self.bounds {
+            Some(bounds) => bounds,
+            None => return center,
+        };
+        let half = self.visible_size() * 0.5;
+        let clamp_axis = |value: f32, min: f32, max: f32, half: f32| {
+            if max - min <= half * 2.0 {
+                (min + max) * 0.5
+            } else {
+                value.clamp(min + half, max - half)
+            }
+        };
+        Vector2::new(
+            clamp_axis(center.x, bounds.left(), bounds.right(), half.x),
+            clamp_axis(center.y, bounds.top(), bounds.bottom(), half.y),
+        )
+    }
+}
+
+impl Default for Camera {
+    fn default() -> Self {
+        Self::for_player()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn camera() -> Camera {
+        Camera::new(Vector2::new(800.0, 600.0)).with_bounds(Rect::new(0.0, 0.0, 4000.0, 1000.0))
+    }
+
+    #[test]
+    fn stays_inside_the_bounds() {
+        let mut camera = camera();
+        camera.snap_to(Vector2::new(-500.0, -500.0));
+        assert_eq!(camera.position(), Vector2::ZERO);
+        camera.snap_to(Vector2::new(5000.0, 5000.0));
+        assert_eq!(camera.position(), Vector2::new(3200.0, 400.0));
+    }
+
+    #[test]
+    fn centers_levels_smaller_than_the_screen() {
+        let mut camera = Camera::new(Vector2::new(800.0, 600.0)).with_bounds(Rect::new(0.0, 0.0, 400.0, 1000.0));
+        camera.snap_to(Vector2::new(1000.0, 500.0));
+        assert_eq!(camera.position(), Vector2::new(-200.0, 200.0));
+    }
+
+    #[test]
+    fn target_moves_freely_inside_the_dead_zone() {
+        let mut camera = camera().with_dead_zone(Vector2::new(100.0, 100.0));
+        camera.snap_to(Vector2::new(1000.0, 500.0));
+        camera.update(Vector2::new(1040.0, 500.0), Vector2::ZERO, 1.0 / 60.0);
+        assert_eq!(camera.center(), Vector2::new(1000.0, 500.0));
+        camera.update(Vector2::new(1080.0, 500.0), Vector2::ZERO, 1.0 / 60.0);
+        assert_eq!(camera.center(), Vector2::new(1030.0, 500.0));
+    }
+
+    #[test]
+    fn world_to_screen_applies_zoom() {
+        let mut camera = camera();
+        camera.set_zoom(2.0);
+        camera.snap_to(Vector2::new(1000.0, 500.0));
+        assert_eq!(camera.view(), Rect::new(800.0, 350.0, 400.0, 300.0));
+        let screen = camera.world_to_screen(Vector2::new(1000.0, 500.0));
+        assert_eq!(screen, Vector2::new(400.0, 300.0));
+        assert_eq!(camera.screen_to_world(screen), Vector2::new(1000.0, 500.0));
+    }
+
+    #[test]
+    fn shake_fades_out() {
+        let mut camera = camera();
+        camera.snap_to(Vector2::new(1000.0, 500.0));
+        camera.shake(10.0, 0.5);
+        camera.update(Vector2::new(1000.0, 500.0), Vector2::ZERO, 0.1);
+        assert!(camera.is_shaking());
+        assert_ne!(camera.view().top_left(), camera.position());
+        camera.update(Vector2::new(1000.0, 500.0), Vector2::ZERO, 0.5);
+        assert!(!camera.is_shaking());
+        assert_eq!(camera.view().top_left(), camera.position());
+    }
+}
@@ -0,0 +1,87 @@
+//! Colors and blend modes for RustUX
+
+use serde::{Deserialize, Serialize};
+
+/// RGBA color
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct Color {
+    pub r: u8,
+    pub g: u8,
+    pub b: u8,
+    #[serde(default = "opaque")]
+    pub a: u8,
+}
+
+fn opaque() -> u8 {
+    255
+}
+
+impl Color {
+    pub const WHITE: Color = Color::rgb(255, 255, 255);
+    pub const BLACK: Color = Color::rgb(0, 0, 0);
+    pub const TRANSPARENT: Color = Color::rgba(0, 0, 0, 0);
+    pub const SKY_BLUE: Color = Color::rgb(135, 206, 235);
+
+    /// Create an opaque color
+    pub const fn rgb(r: u8, g: u8, b: u8) -> Self {
+        Self { r, g, b, a: 255 }
+    }
+
+    /// Create a color with transparency
+    pub const fn rgba(r: u8, g: u8, b: u8, a: u8) -> Self {
+        Self { r, g, b, a }
+    }
+
+    /// Same color with a different alpha
+    pub const fn with_alpha(self, a: u8) -> Self {
+        Self { a, ..self }
+    }
+
+    /// Blend towards another color, `t` going from 0 (self) to 1 (other)
+    pub fn lerp(self, other: Color, t: f32) -> Self {
+        let t = t.clamp(0.0, 1.0);
+        let mix = |a: u8, b: u8| (a as f32 + (b as f32 - a as f32) * t).round() as u8;
+        Self::rgba(mix(self.r, other.r), mix(self.g, other.g), mix(self.b, other.b), mix(self.a, other.a))
+    }
+
+    pub(crate) fn to_sdl(self) -> sdl2::pixels::Color {
+        sdl2::pixels::Color::RGBA(self.r, self.g, self.b, self.a)
+    }
+}
+
+impl Default for Color {
+    fn default() -> Self {
+        Self::WHITE
+    }
+}
+
+impl From<(u8, u8, u8)> for Color {
+    fn from((r, g, b): (u8, u8, u8)) -> Self {
+        Self::rgb(r, g, b)
+    }
+}
+
+/// How drawn pixels combine with what is already on screen
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum BlendMode {
+    /// Overwrite the destination
+    None,
+    /// Alpha blending
+    #[default]
+    Blend,
+    /// Add to the destination, for glows and sparkles
+    Add,
+    /// Multiply with the destination, for shadows and tinting
+    Modulate,
+}
+
+impl BlendMode {
+    pub(crate) fn to_sdl(self) -> sdl2::render::BlendMode {
+        match self {
+            BlendMode::None => sdl2::render::BlendMode::None,
+            BlendMode::Blend => sdl2::render::BlendMode::Blend,
+            BlendMode::Add => sdl2::render::BlendMode::Add,
+            BlendMode::Modulate => sdl2::render::BlendMode::Mod,
+        }
+    }
+}
@@ -0,0 +1,164 @@
+//! Built-in bitmap font for RustUX
+//!
+//! A small 5x7 pixel font baked into a texture when the video manager
+//! starts, so text can be drawn without any font files. Lowercase letters
+//! are drawn as uppercase; unknown characters are drawn as `?`.
+
+use crate::math::{Rect, Vector2};
+
+/// Width of a glyph in font pixels
+pub const GLYPH_WIDTH: u32 = 5;
+/// Height of a glyph in font pixels
+pub const GLYPH_HEIGHT: u32 = 7;
+/// Horizontal distance between glyphs in font pixels
+pub const GLYPH_ADVANCE: u32 = 6;
+/// Vertical distance between lines in font pixels
+pub const LINE_HEIGHT: u32 = 8;
+
+/// Name the font texture is stored under
+pub const FONT_TEXTURE: &str = "__font";
+
+/// Glyph bitmaps, one byte per row with the leftmost pixel in bit 4
+const GLYPHS: &[(char, [u8; 7])] = &[
+    ('?', [0x0E, 0x11, 0x01, 0x02, 0x04, 0x00, 0x04]),
+    ('A', [0x0E, 0x11, 0x11, 0x1F, 0x11, 0x11, 0x11]),
+    ('B', [0x1E, 0x11, 0x11, 0x1E, 0x11, 0x11, 0x1E]),
+    ('C', [0x0E, 0x11, 0x10, 0x10, 0x10, 0x11, 0x0E]),
+    ('D', [0x1E, 0x11, 0x11, 0x11, 0x11, 0x11, 0x1E]),
+    ('E', [0x1F, 0x10, 0x10, 0x1E, 0x10, 0x10, 0x1F]),
+    ('F', [0x1F, 0x10, 0x10, 0x1E, 0x10, 0x10, 0x10]),
+    ('G', [0x0E, 0x11, 0x10, 0x17, 0x11, 0x11, 0x0F]),
+    ('H', [0x11, 0x11, 0x11, 0x1F, 0x11, 0x11, 0x11]),
+    ('I', [0x0E, 0x04, 0x04, 0x04, 0x04, 0x04, 0x0E]),
+    ('J', [0x07, 0x02, 0x02, 0x02, 0x02, 0x12, 0x0C]),
+    ('K', [0x11, 0x12, 0x14, 0x18, 0x14, 0x12, 0x11]),
+    ('L', [0x10, 0x10, 0x10, 0x10, 0x10, 0x10, 0x1F]),
+    ('M', [0x11, 0x1B, 0x15, 0x15, 0x11, 0x11, 0x11]),
+    ('N', [0x11, 0x11, 0x19, 0x15, 0x13, 0x11, 0x11]),
+    ('O', [0x0E, 0x11, 0x11, 0x11, 0x11, 0x11, 0x0E]),
+    ('P', [0x1E, 0x11, 0x11, 0x1E, 0x10, 0x10, 0x10]),
+    ('Q', [0x0E, 0x11, 0x11, 0x11, 0x15, 0x12, 0x0D]),
+    ('R', [0x1E, 0x11, 0x11, 0x1E, 0x14, 0x12, 0x11]),
+    ('S', [0x0F, 0x10, 0x10, 0x0E, 0x01, 0x01, 0x1E]),
+    ('T', [0x1F, 0x04, 0x04, 0x04, 0x04, 0x04, 0x04]),
+    ('U', [0x11, 0x11, 0x11, 0x11, 0x11, 0x11, 0x0E]),
+    ('V', [0x11, 0x11, 0x11, 0x11, 0x11, 0x0A, 0x04]),
+    ('W', [0x11, 0x11, 0x11, 0x15, 0x15, 0x15, 0x0A]),
+    ('X', [0x11, 0x11, 0x0A, 0x04, 0x0A, 0x11, 0x11]),
+    ('Y', [0x11, 0x11, 0x0A, 0x04, 0x04, 0x04, 0x04]),
+    ('Z', [0x1F, 0x01, 0x02, 0x04, 0x08, 0x10, 0x1F]),
+    ('0', [0x0E, 0x11, 0x13, 0x15, 0x19, 0x11, 0x0E]),
+    ('1', [0x04, 0x0C, 0x04, 0x04, 0x04, 0x04, 0x0E]),
+    ('2', [0x0E, 0x11, 0x01, 0x02, 0x04, 0x08, 0x1F]),
+    ('3', [0x1F, 0x02, 0x04, 0x02, 0x01, 0x11, 0x0E]),
+    ('4', [0x02, 0x06, 0x0A, 0x12, 0x1F, 0x02, 0x02]),
+    ('5', [0x1F, 0x10, 0x1E, 0x01, 0x01, 0x11, 0x0E]),
+    ('6', [0x06, 0x08, 0x10, 0x1E, 0x11, 0x11, 0x0E]),
+    ('7', [0x1F, 0x01, 0x02, 0x04, 0x08, 0x08, 0x08]),
+    ('8', [0x0E, 0x11, 0x11, 0x0E, 0x11, 0x11, 0x0E]),
+    ('9', [0x0E, 0x11, 0x11, 0x0F, 0x01, 0x02, 0x0C]),
+    ('!', [0x04, 0x04, 0x04, 0x04, 0x04, 0x00, 0x04]),
+    ('"', [0x0A, 0x0A, 0x00, 0x00, 0x00, 0x00, 0x00]),
+    ('#', [0x0A, 0x0A, 0x1F, 0x0A, 0x1F, 0x0A, 0x0A]),
+    ('%', [0x18, 0x19, 0x02, 0x04, 0x08, 0x13, 0x03]),
+    ('\'', [0x04, 0x04, 0x00, 0x00, 0x00, 0x00, 0x00]),
+    ('(', [0x02, 0x04, 0x08, 0x08, 0x08, 0x04, 0x02]),
+    (')', [0x08, 0x04, 0x02, 0x02, 0x02, 0x04, 0x08]),
+    ('*', [0x00, 0x04, 0x15, 0x0E, 0x15, 0x04, 0x00]),
+    ('+', [0x00, 0x04, 0x04, 0x1F, 0x04, 0x04, 0x00]),
+    (',', [0x00, 0x00, 0x00, 0x00, 0x0C, 0x04, 0x08]),
+    ('-', [0x00, 0x00, 0x00, 0x1F, 0x00, 0x00, 0x00]),
+    ('.', [0x00, 0x00, 0x00, 0x00, 0x00, 0x0C, 0x0C]),
+    ('/', [0x00, 0x01, 0x02, 0x04, 0x08, 0x10, 0x00]),
+    (':', [0x00, 0x0C, 0x0C, 0x00, 0x0C, 0x0C, 0x00]),
+    (';', [0x00, 0x0C, 0x0C, 0x00, 0x0C, 0x04, 0x08]),
+    ('<', [0x02, 0x04, 0x08, 0x10, 0x08, 0x04, 0x02]),
+    ('=', [0x00, 0x00, 0x1F, 0x00, 0x1F, 0x00, 0x00]),
+    ('>', [0x08, 0x04, 0x02, 0x01, 0x02, 0x04, 0x08]),
+    ('_', [0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x1F]),
+];
+
+/// Index of a character's glyph in the font texture
+fn glyph_index(ch: char) -> usize {
+    let ch = ch.to_ascii_uppercase();
+    GLYPHS.iter().position(|(glyph, _)| *glyph == ch).unwrap_or(0)
+}
+
+/// Source rectangle of a character in the font texture
+pub fn glyph_rect(ch: char) -> Rect {
+    let index = glyph_index(ch) as u32;
+    Rect::new((index * GLYPH_WIDTH) as f32, 0.0, GLYPH_WIDTH as f32, GLYPH_HEIGHT as f32)
+}
+
+/// Font texture pixels as (width, height, RGBA data), white on transparent
+pub fn atlas_pixels() -> (u32, u32, Vec<u8>) {
+    let width = GLYPHS.len() as u32 * GLYPH_WIDTH;
+    let mut pixels = vec![0u8; (width * GLYPH_HEIGHT * 4) as usize];
+
+    for (index, (_, rows)) in GLYPHS.iter().enumerate() {
+        for (y, row) in rows.iter().enumerate() {
+            for x in 0..GLYPH_WIDTH {
+                if row & (1 << (GLYPH_WIDTH - 1 - x)) != 0 {
+                    let offset = ((y as u32 * width + index as u32 * GLYPH_WIDTH + x) * 4) as usize;
+                    pixels[offset..offset + 4].copy_from_slice(&[255, 255, 255, 255]);
+                }
+            }
+        }
+    }
+
+    (width, GLYPH_HEIGHT, pixels)
+}
+
+/// Size of one font pixel for text drawn with the given line height
+pub fn pixel_scale(line_height: f32) -> f32 {
+    line_height / LINE_HEIGHT as f32
+}
+
+/// Size of a (possibly multi-line) text drawn with the given line height
+pub fn text_size(text: &str, line_height: f32) -> Vector2 {
+    let scale = pixel_scale(line_height);
+    let columns = text.lines().map(|line| line.chars().count()).max().unwrap_or(0);
+    let lines = text.lines().count().max(1);
+    Vector2::new(columns as f32 * GLYPH_ADVANCE as f32 * scale, lines as f32 * line_height)
+}
+
+/// Screen rectangles of each visible glyph of a text, with their source rectangles
+pub fn layout(text: &str, position: Vector2, line_height: f32) -> Vec<(Rect, Rect)> {
+    let scale = pixel_scale(line_height);
+    let mut glyphs = Vec::new();
+
+    for (row, line) in text.lines().enumerate() {
+        for (column, ch) in line.chars().enumerate() {
+            if ch.is_whitespace() {
+                continue;
+            }
+            let x = position.x + column as f32 * GLYPH_ADVANCE as f32 * scale;
+            let y = position.y + row as f32 * line_height;
+            let dest = Rect::new(x, y, GLYPH_WIDTH as f32 * scale, GLYPH_HEIGHT as f32 * scale);
+            glyphs.push((dest, glyph_rect(ch)));
+        }
+    }
+
+    glyphs
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn lowercase_and_unknown_characters_share_glyphs() {
+        assert_eq!(glyph_rect('a'), glyph_rect('A'));
+        assert_eq!(glyph_rect('~'), glyph_rect('?'));
+        assert_ne!(glyph_rect('A'), glyph_rect('B'));
+    }
+
+    #[test]
+    fn text_is_laid_out_on_a_grid() {
+        assert_eq!(text_size("HELLO\nHI", 16.0), Vector2::new(60.0, 32.0));
+        let glyphs = layout("A B\nC", Vector2::new(10.0, 20.0), 16.0);
+        let positions: Vec<_> = glyphs.iter().map(|(dest, _)| dest.top_left()).collect();
+        assert_eq!(positions, [Vector2::new(10.0, 20.0), Vector2::new(34.0, 20.0), Vector2::new(10.0, 36.0)]);
+        assert_eq!(glyphs[0].0.width, 10.0);
+    }
+}
@@ -0,0 +1,139 @@
+//! Frame dumps and golden image comparison for RustUX
+//!
+//! Golden images are reference frames kept next to the tests. Set the
+//! `RUSTUX_BLESS` environment variable to (re)write them from the current
+//! output instead of comparing.
+
+use crate::util::{Error, Result};
+use image::RgbaImage;
+use std::path::Path;
+
+/// Environment variable that makes `assert_matches_golden` rewrite golden images
+pub const BLESS_VAR: &str = "RUSTUX_BLESS";
+
+/// Difference between two images of the same size
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ImageDiff {
+    /// Number of pixels with any channel differing by more than the tolerance
+    pub differing_pixels: u32,
+    /// Largest difference of a single channel
+    pub max_channel_diff: u8,
+}
+
+impl ImageDiff {
+    /// Check if no pixel differs by more than the tolerance
+    pub fn is_match(&self) -> bool {
+        self.differing_pixels == 0
+    }
+}
+
+/// Compare two images, allowing each channel to differ by up to `tolerance`
+pub fn compare(actual: &RgbaImage, expected: &RgbaImage, tolerance: u8) -> Result<ImageDiff> {
+    if actual.dimensions() != expected.dimensions() {
+        return Err(Error::Video(format!(
+            "Image sizes differ: {:?} vs {:?}",
+            actual.dimensions(),
+            expected.dimensions()
+        )));
+    }
+
+    let mut diff = ImageDiff {
+        differing_pixels: 0,
+        max_channel_diff: 0,
+    };
+    for (a, e) in actual.pixels().zip(expected.pixels()) {
+        let max = a.0.iter().zip(e.0.iter()).map(|(a, e)| a.abs_diff(*e)).max().unwrap_or(0);
+        diff.max_channel_diff = diff.max_channel_diff.max(max);
+        if max > tolerance {
+            diff.differing_pixels += 1;
+        }
+    }
+
+    Ok(diff)
+}
+
+/// Save a frame as a PNG file, creating its directory if needed
+pub fn save_png<P: AsRef<Path>>(frame: &RgbaImage, path: P) -> Result<()> {
+    let path = path.as_ref();
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir)?;
+    }
+    frame
+        .save(path)
+        .map_err(|e| Error::Video(format!("Failed to save '{}': {}", path.display(), e)))
+}
+
+/// Compare a frame against a golden PNG, writing the golden image instead if
+/// `RUSTUX_BLESS` is set. A missing golden image is an error, and on a
+/// mismatch the frame is saved next to the golden image with an
+/// `.actual.png` suffix.
+pub fn assert_matches_golden<P: AsRef<Path>>(frame: &RgbaImage, golden: P, tolerance: u8) -> Result<()> {
+    let golden = golden.as_ref();
+    if std::env::var_os(BLESS_VAR).is_some() {
+        log::info!("Writing golden image {}", golden.display());
+        return save_png(frame, golden);
+    }
+    if !golden.exists() {
+        let actual = golden.with_extension("actual.png");
+        save_png(frame, &actual)?;
+        return Err(Error::Video(format!(
+            "Golden image {} is missing; saved the frame to {}, run with {} set to accept it",
+            golden.display(),
+            actual.display(),
+            BLESS_VAR
+        )));
+    }
+
+    let expected = image::open(golden)
+        .map_err(|e| Error::Video(format!("Failed to load '{}': {}", golden.display(), e)))?
+        .to_rgba8();
+    let diff = compare(frame, &expected, tolerance)?;
+    if diff.is_match() {
+        return Ok(());
+    }
+
+    let actual = golden.with_extension("actual.png");
+    save_png(frame, &actual)?;
+    Err(Error::Video(format!(
+        "Frame differs from {} in {} pixels (max channel difference {}, tolerance {}); saved it to {}",
+        golden.display(),
+        diff.differing_pixels,
+        diff.max_channel_diff,
+        tolerance,
+        actual.display()
+    )))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use image::Rgba;
+
+    #[test]
+    fn compare_respects_the_tolerance() {
+        let expected = RgbaImage::from_pixel(2, 2, Rgba([100, 100, 100, 255]));
+        let mut actual = expected.clone();
+        actual.put_pixel(0, 0, Rgba([103, 100, 100, 255]));
+        actual.put_pixel(1, 1, Rgba([100, 90, 100, 255]));
+
+        let diff = compare(&actual, &expected, 4).unwrap();
+        assert_eq!(diff, ImageDiff { differing_pixels: 1, max_channel_diff: 10 });
+        assert!(compare(&actual, &expected, 10).unwrap().is_match());
+        assert!(compare(&actual, &RgbaImage::new(3, 2), 255).is_err());
+    }
+
+    #[test]
+    fn missing_golden_image_is_an_error() {
+        if std::env::var_os(BLESS_VAR).is_some() {
+            return;
+        }
+        let dir = std::env::temp_dir().join(format!("rustux-golden-{}", std::process::id()));
+        let golden = dir.join("missing.png");
+        let frame = RgbaImage::from_pixel(2, 2, Rgba([1, 2, 3, 255]));
+
+        assert!(assert_matches_golden(&frame, &golden, 0).is_err());
+        assert!(!golden.exists());
+        assert!(dir.join("missing.actual.png").exists());
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -1,34 +1,488 @@
 //! Video/rendering system for RustUX
+//!
+//! `VideoManager` owns the render backend and every loaded texture, and is
+//! the only drawing API game code uses: textured quads, shapes, lines and
+//! text, with blend modes, color modulation and clipping. It draws either
+//! into an SDL window or, headless, into an in-memory image. With a logical
+//! size set, game code draws in logical pixels that the manager scales to
+//! the output and letterboxes.
 
-use crate::util::Result;
+use crate::util::{Error, Result};
 use crate::math::{Vector2, Rect};
+use image::RgbaImage;
+use sdl2::render::Canvas;
+use sdl2::video::Window;
+use std::path::Path;
+
+pub mod background;
+pub mod backend;
+pub mod camera;
+pub mod color;
+pub mod font;
+pub mod golden;
+pub mod render_queue;
+pub mod sdl;
+pub mod software;
+pub mod texture_cache;
+pub mod viewport;
+
+pub use background::{BackgroundFill, BackgroundLayer, GradientDirection, RepeatMode};
+pub use backend::{RenderBackend, RenderBackendKind};
+pub use camera::Camera;
+pub use render_queue::{RenderCommand, RenderQueue, RenderStats};
+pub use color::{BlendMode, Color};
+pub use sdl::SdlBackend;
+pub use software::SoftwareBackend;
+pub use texture_cache::TextureCache;
+pub use viewport::{ScalingMode, VideoConfig, Viewport};
+
+/// How a texture is drawn
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct DrawParams {
+    pub flip_horizontal: bool,
+    pub flip_vertical: bool,
+    /// Clockwise rotation in degrees
+    pub rotation: f64,
+    /// Point to rotate around, relative to the destination's top-left
+    /// corner (`None` = the center)
+    pub pivot: Option<Vector2>,
+    /// Color the texture is multiplied with, including its alpha
+    pub color: Color,
+    pub blend: BlendMode,
+}
+
+impl Default for DrawParams {
+    fn default() -> Self {
+        Self {
+            flip_horizontal: false,
+            flip_vertical: false,
+            rotation: 0.0,
+            pivot: None,
+            color: Color::WHITE,
+            blend: BlendMode::Blend,
+        }
+    }
+}
 
 /// Video manager for rendering
 pub struct VideoManager {
-    // Rendering context will go here
+    backend: Box<dyn RenderBackend>,
+    cache: TextureCache,
+    clip: Option<Rect>,
+    /// Size game code draws at (`None` = the output size)
+    logical_size: Option<Vector2>,
+    scaling: ScalingMode,
+    viewport: Viewport,
+    fullscreen: bool,
 }
 
 impl VideoManager {
-    /// Create a new video manager
-    pub fn new() -> Result<Self> {
-        Ok(Self {})
+    /// Create a video manager drawing to a window canvas
+    pub fn new(canvas: Canvas<Window>) -> Result<Self> {
+        Self::with_backend(Box::new(SdlBackend::new(canvas)))
+    }
+
+    /// Create a video manager drawing into an in-memory frame, without a window
+    pub fn headless(width: u32, height: u32) -> Result<Self> {
+        Self::with_backend(Box::new(SoftwareBackend::new(width, height)))
+    }
+
+    /// Create a video manager drawing with the given backend
+    pub fn with_backend(mut backend: Box<dyn RenderBackend>) -> Result<Self> {
+        let (width, height, pixels) = font::atlas_pixels();
+        backend.load_texture_rgba(font::FONT_TEXTURE, width, height, pixels)?;
+
+        let viewport = Viewport::identity(backend.output_size());
+        Ok(Self {
+            backend,
+            cache: TextureCache::new(),
+            clip: None,
+            logical_size: None,
+            scaling: ScalingMode::default(),
+            viewport,
+            fullscreen: false,
+        })
+    }
+
+    /// Apply the resolution, scaling and fullscreen settings
+    pub fn apply_config(&mut self, config: &VideoConfig) -> Result<()> {
+        self.logical_size = Some(config.logical_size());
+        self.scaling = config.scaling;
+        self.set_fullscreen(config.fullscreen)
+    }
+
+    /// Size of the drawing area in logical pixels
+    pub fn output_size(&self) -> Vector2 {
+        self.viewport.logical_size
+    }
+
+    /// Size of the real output in pixels
+    pub fn physical_size(&self) -> Vector2 {
+        self.backend.output_size()
+    }
+
+    /// Draw at a fixed size scaled to the output, or at the output size with `None`
+    pub fn set_logical_size(&mut self, logical_size: Option<Vector2>) {
+        self.logical_size = logical_size;
+        self.refresh_viewport();
+    }
+
+    /// Get the fixed size drawing happens at, if any
+    pub fn logical_size(&self) -> Option<Vector2> {
+        self.logical_size
+    }
+
+    /// Set how the logical size is scaled to the output
+    pub fn set_scaling_mode(&mut self, scaling: ScalingMode) {
+        self.scaling = scaling;
+        self.refresh_viewport();
+    }
+
+    pub fn scaling_mode(&self) -> ScalingMode {
+        self.scaling
+    }
+
+    /// Get the placement of the logical drawing area on the output
+    pub fn viewport(&self) -> Viewport {
+        self.viewport
     }
 
-    /// Render a texture at the given position
-    pub fn render_texture(&self, _texture_name: &str, _position: Vector2) -> Result<()> {
-        // TODO: Implement texture rendering
+    /// Recompute the viewport after the output changed size
+    ///
+    /// `clear` does this at the start of every frame.
+    pub fn refresh_viewport(&mut self) {
+        let output_size = self.backend.output_size();
+        self.viewport = match self.logical_size {
+            Some(logical_size) => Viewport::new(logical_size, output_size, self.scaling),
+            None => Viewport::identity(output_size),
+        };
+        self.apply_clip();
+    }
+
+    /// Convert a window position, such as the mouse, to logical coordinates
+    pub fn window_to_logical(&self, position: Vector2) -> Vector2 {
+        self.viewport.to_logical(position)
+    }
+
+    /// Switch between windowed and fullscreen
+    pub fn set_fullscreen(&mut self, fullscreen: bool) -> Result<()> {
+        self.backend.set_fullscreen(fullscreen)?;
+        self.fullscreen = fullscreen;
+        self.refresh_viewport();
+        Ok(())
+    }
+
+    /// Toggle fullscreen, returning the new setting
+    pub fn toggle_fullscreen(&mut self) -> Result<bool> {
+        self.set_fullscreen(!self.fullscreen)?;
+        Ok(self.fullscreen)
+    }
+
+    pub fn is_fullscreen(&self) -> bool {
+        self.fullscreen
+    }
+
+    /// Fill the drawing area with a color and the letterbox bars with black
+    pub fn clear(&mut self, color: Color) {
+        self.refresh_viewport();
+        if self.viewport.is_identity() {
+            self.backend.clear(color);
+            return;
+        }
+
+        self.backend.clear(Color::BLACK);
+        self.backend.set_clip_rect(Some(self.viewport.rect()));
+        self.backend.set_blend_mode(BlendMode::None);
+        if let Err(e) = self.backend.fill_rect(self.viewport.rect(), color) {
+            log::warn!("Failed to clear the viewport: {}", e);
+        }
+        self.apply_clip();
+    }
+
+    /// Show the finished frame
+    pub fn present(&mut self) {
+        self.backend.present();
+    }
+
+    /// Read back the current frame, at the output's real size
+    pub fn screenshot(&self) -> Result<RgbaImage> {
+        self.backend.read_pixels()
+    }
+
+    /// Save the current frame as a PNG file
+    pub fn save_screenshot<P: AsRef<Path>>(&self, path: P) -> Result<()> {
+        golden::save_png(&self.screenshot()?, path)
+    }
+
+    /// Load a texture from an image file (PNG, JPG, ...)
+    pub fn load_texture<P: AsRef<Path>>(&mut self, name: &str, path: P) -> Result<()> {
+        let path = path.as_ref();
+        let image = image::open(path)
+            .map_err(|e| Error::SpriteLoading(format!("Failed to load image {}: {}", path.display(), e)))?
+            .to_rgba8();
+        let (width, height) = image.dimensions();
+        self.backend.load_texture_rgba(name, width, height, image.into_raw())?;
+        log::debug!("Loaded texture from file: {} -> {}", path.display(), name);
+        Ok(())
+    }
+
+    /// Create a texture from raw RGBA pixels
+    pub fn load_texture_rgba(&mut self, name: &str, width: u32, height: u32, pixels: Vec<u8>) -> Result<()> {
+        self.backend.load_texture_rgba(name, width, height, pixels)
+    }
+
+    /// Destroy a texture, returning false if it wasn't loaded
+    ///
+    /// This ignores references taken with `acquire_texture`.
+    pub fn remove_texture(&mut self, name: &str) -> bool {
+        self.cache.forget(name);
+        self.backend.remove_texture(name)
+    }
+
+    /// Take a reference to a texture, loading it from `path` if nobody holds one yet
+    pub fn acquire_texture<P: AsRef<Path>>(&mut self, name: &str, path: P) -> Result<()> {
+        let path = path.as_ref();
+        if self.cache.acquire(name, path)? {
+            if let Err(e) = self.load_texture(name, path) {
+                self.cache.release(name);
+                return Err(e);
+            }
+        }
         Ok(())
     }
 
+    /// Drop a reference to a texture, unloading it when it was the last one
+    pub fn release_texture(&mut self, name: &str) -> bool {
+        let unload = self.cache.release(name);
+        if unload {
+            self.backend.remove_texture(name);
+            log::debug!("Unloaded texture: {}", name);
+        }
+        unload
+    }
+
+    /// Get the number of references held to a texture
+    pub fn texture_ref_count(&self, name: &str) -> u32 {
+        self.cache.ref_count(name)
+    }
+
+    /// Get the reference counts of acquired textures
+    pub fn texture_cache(&self) -> &TextureCache {
+        &self.cache
+    }
+
+    /// Check if a texture is loaded
+    pub fn has_texture(&self, name: &str) -> bool {
+        self.backend.texture_size(name).is_some()
+    }
+
+    /// Get the size of a texture in pixels
+    pub fn texture_size(&self, name: &str) -> Option<Vector2> {
+        self.backend.texture_size(name)
+    }
+
+    /// Render a whole texture at its natural size
+    pub fn render_texture(&mut self, texture_name: &str, position: Vector2) -> Result<()> {
+        let size = self.texture_size(texture_name)
+            .ok_or_else(|| Error::SpriteLoading(format!("Texture not found: {}", texture_name)))?;
+        self.draw_texture(texture_name, None, Rect::from_pos_size(position, size), &DrawParams::default())
+    }
+
     /// Render a texture with source and destination rectangles
-    pub fn render_texture_ex(&self, _texture_name: &str, _src: Option<Rect>, _dst: Rect) -> Result<()> {
-        // TODO: Implement advanced texture rendering
+    pub fn render_texture_ex(&mut self, texture_name: &str, src: Option<Rect>, dst: Rect) -> Result<()> {
+        self.draw_texture(texture_name, src, dst, &DrawParams::default())
+    }
+
+    /// Draw part of a texture (`src`, or all of it) into `dst`
+    pub fn draw_texture(&mut self, texture_name: &str, src: Option<Rect>, dst: Rect, params: &DrawParams) -> Result<()> {
+        if self.viewport.is_identity() {
+            return self.backend.draw_texture(texture_name, src, dst, params);
+        }
+        let (dst, params) = self.quad_to_output(dst, params);
+        self.backend.draw_texture(texture_name, src, dst, &params)
+    }
+
+    /// Draw several (src, dst, params) quads from one texture, in order
+    pub fn draw_texture_batch(&mut self, texture_name: &str, quads: &[(Option<Rect>, Rect, DrawParams)]) -> Result<()> {
+        if self.viewport.is_identity() {
+            return self.backend.draw_texture_batch(texture_name, quads);
+        }
+        let quads: Vec<_> = quads
+            .iter()
+            .map(|(src, dst, params)| {
+                let (dst, params) = self.quad_to_output(*dst, params);
+                (*src, dst, params)
+            })
+            .collect();
+        self.backend.draw_texture_batch(texture_name, &quads)
+    }
+
+    /// Scale a logical destination and its rotation pivot to the output
+    fn quad_to_output(&self, dst: Rect, params: &DrawParams) -> (Rect, DrawParams) {
+        let params = DrawParams {
+            pivot: params.pivot.map(|pivot| pivot * self.viewport.scale),
+            ..*params
+        };
+        (self.viewport.rect_to_output(dst), params)
+    }
+
+    /// Set the blend mode used for shapes and lines
+    pub fn set_blend_mode(&mut self, blend: BlendMode) {
+        self.backend.set_blend_mode(blend);
+    }
+
+    /// Draw a filled rectangle
+    pub fn fill_rect(&mut self, rect: Rect, color: Color) -> Result<()> {
+        self.backend.fill_rect(self.viewport.rect_to_output(rect), color)
+    }
+
+    /// Draw the outline of a rectangle
+    pub fn draw_rect(&mut self, rect: Rect, color: Color) -> Result<()> {
+        self.backend.draw_rect(self.viewport.rect_to_output(rect), color)
+    }
+
+    /// Draw a line
+    pub fn draw_line(&mut self, from: Vector2, to: Vector2, color: Color) -> Result<()> {
+        self.backend.draw_line(self.viewport.to_output(from), self.viewport.to_output(to), color)
+    }
+
+    /// Draw text with the built-in font; `line_height` is in pixels
+    pub fn draw_text(&mut self, text: &str, position: Vector2, line_height: f32, color: Color) -> Result<()> {
+        let params = DrawParams {
+            color,
+            ..DrawParams::default()
+        };
+        for (dst, src) in font::layout(text, position, line_height) {
+            self.draw_texture(font::FONT_TEXTURE, Some(src), dst, &params)?;
+        }
         Ok(())
     }
+
+    /// Size of a text drawn with `draw_text`
+    pub fn text_size(&self, text: &str, line_height: f32) -> Vector2 {
+        font::text_size(text, line_height)
+    }
+
+    /// Restrict drawing to a rectangle, or allow drawing everywhere with `None`
+    pub fn set_clip_rect(&mut self, clip: Option<Rect>) {
+        self.clip = clip;
+        self.apply_clip();
+    }
+
+    /// Hand the clip rectangle to the backend, keeping drawing off the letterbox bars
+    fn apply_clip(&mut self) {
+        let clip = match self.clip {
+            Some(clip) => Some(self.viewport.rect_to_output(clip)),
+            None if self.viewport.is_identity() => None,
+            None => Some(self.viewport.rect()),
+        };
+        self.backend.set_clip_rect(clip);
+    }
+
+    /// Get the rectangle drawing is restricted to
+    pub fn clip_rect(&self) -> Option<Rect> {
+        self.clip
+    }
 }
 
-impl Default for VideoManager {
-    fn default() -> Self {
-        Self::new().expect("Failed to create VideoManager")
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A few of each kind of draw call, in a frame small enough to inspect by eye
+    fn render_scene(video: &mut VideoManager) -> Result<()> {
+        video.clear(Color::SKY_BLUE);
+        video.fill_rect(Rect::new(0.0, 48.0, 96.0, 16.0), Color::rgb(90, 60, 30))?;
+
+        // 4x4 checkerboard texture
+        let pixels = (0..16)
+            .flat_map(|i| if (i % 4 + i / 4) % 2 == 0 { [255, 200, 0, 255] } else { [40, 40, 40, 255] })
+            .collect();
+        video.load_texture_rgba("checker", 4, 4, pixels)?;
+        video.render_texture_ex("checker", None, Rect::new(8.0, 24.0, 16.0, 16.0))?;
+        let tilted = DrawParams {
+            flip_horizontal: true,
+            rotation: 30.0,
+            color: Color::rgba(255, 255, 255, 160),
+            ..DrawParams::default()
+        };
+        video.draw_texture("checker", Some(Rect::new(0.0, 0.0, 2.0, 4.0)), Rect::new(40.0, 20.0, 12.0, 24.0), &tilted)?;
+
+        video.set_blend_mode(BlendMode::Blend);
+        video.fill_rect(Rect::new(60.0, 30.0, 24.0, 24.0), Color::rgba(255, 0, 0, 96))?;
+        video.draw_rect(Rect::new(60.0, 30.0, 24.0, 24.0), Color::BLACK)?;
+        video.draw_line(Vector2::new(0.0, 63.0), Vector2::new(95.0, 40.0), Color::WHITE)?;
+
+        video.set_clip_rect(Some(Rect::new(0.0, 0.0, 40.0, 16.0)));
+        video.draw_text("RUSTUX 1", Vector2::new(2.0, 2.0), 8.0, Color::BLACK)?;
+        video.set_clip_rect(None);
+        Ok(())
+    }
+
+    #[test]
+    fn acquired_textures_stay_loaded_until_the_last_release() {
+        let path = std::env::temp_dir().join(format!("rustux_cache_test_{}.png", std::process::id()));
+        golden::save_png(&RgbaImage::new(3, 2), &path).unwrap();
+
+        let mut video = VideoManager::headless(8, 8).unwrap();
+        video.acquire_texture("block", &path).unwrap();
+        video.acquire_texture("block", &path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        // Already loaded, so the missing file doesn't matter
+        video.acquire_texture("block", &path).unwrap();
+        assert_eq!(video.texture_ref_count("block"), 3);
+        assert_eq!(video.texture_size("block"), Some(Vector2::new(3.0, 2.0)));
+
+        assert!(!video.release_texture("block"));
+        assert!(!video.release_texture("block"));
+        assert!(video.has_texture("block"));
+        assert!(video.release_texture("block"));
+        assert!(!video.has_texture("block"));
+
+        // Failed loads don't leave a reference behind
+        assert!(video.acquire_texture("block", &path).is_err());
+        assert_eq!(video.texture_ref_count("block"), 0);
+    }
+
+    #[test]
+    fn headless_frames_match_the_golden_image() {
+        let mut video = VideoManager::headless(96, 64).unwrap();
+        assert_eq!(video.output_size(), Vector2::new(96.0, 64.0));
+        render_scene(&mut video).unwrap();
+
+        let frame = video.screenshot().unwrap();
+        let golden = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/golden/headless_scene.png");
+        golden::assert_matches_golden(&frame, golden, 1).unwrap();
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn logical_resolution_is_scaled_and_letterboxed() {
+        let mut video = VideoManager::headless(40, 20).unwrap();
+        video.set_logical_size(Some(Vector2::new(8.0, 8.0)));
+        video.set_scaling_mode(ScalingMode::Integer);
+        assert_eq!(video.output_size(), Vector2::new(8.0, 8.0));
+        assert_eq!(video.viewport().rect(), Rect::new(12.0, 2.0, 16.0, 16.0));
+
+        video.clear(Color::WHITE);
+        video.fill_rect(Rect::new(0.0, 0.0, 1.0, 1.0), Color::rgb(255, 0, 0)).unwrap();
+        // Drawing past the logical edge stays off the bars
+        video.fill_rect(Rect::new(7.0, 0.0, 4.0, 1.0), Color::rgb(0, 0, 255)).unwrap();
+
+        let frame = video.screenshot().unwrap();
+        assert_eq!(frame.get_pixel(0, 0).0, [0, 0, 0, 255]);
+        assert_eq!(frame.get_pixel(12, 2).0, [255, 0, 0, 255]);
+        assert_eq!(frame.get_pixel(13, 3).0, [255, 0, 0, 255]);
+        assert_eq!(frame.get_pixel(14, 2).0, [255, 255, 255, 255]);
+        assert_eq!(frame.get_pixel(27, 2).0, [0, 0, 255, 255]);
+        assert_eq!(frame.get_pixel(28, 2).0, [0, 0, 0, 255]);
+
+        assert_eq!(video.window_to_logical(Vector2::new(13.0, 3.0)).floor(), Vector2::ZERO);
+
+        video.set_scaling_mode(ScalingMode::Stretch);
+        assert_eq!(video.viewport().rect(), Rect::new(0.0, 0.0, 40.0, 20.0));
+        video.set_logical_size(None);
+        assert!(video.viewport().is_identity());
+        assert_eq!(video.output_size(), Vector2::new(40.0, 20.0));
+    }
+}
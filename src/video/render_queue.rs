@@ -0,0 +1,291 @@
+//! Sprite render queue for RustUX
+//!
+//! Sprites are submitted in any order, culled against the camera, then
+//! drawn sorted by layer and texture so consecutive draws from the same
+//! texture go to the backend as one batch.
+
+use crate::math::Rect;
+use crate::sprite::Sprite;
+use crate::util::Result;
+use crate::video::{BlendMode, Camera, DrawParams, VideoManager};
+
+/// One textured quad waiting to be drawn, in screen space
+#[derive(Debug, Clone, PartialEq)]
+pub struct RenderCommand {
+    /// Rendering layer (higher = drawn on top)
+    pub layer: i32,
+    pub texture_name: String,
+    pub src: Option<Rect>,
+    pub dst: Rect,
+    pub params: DrawParams,
+}
+
+/// Counters for one flushed frame
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct RenderStats {
+    /// Sprites handed to the queue
+    pub submitted: u32,
+    /// Sprites dropped because they were outside the camera view
+    pub culled: u32,
+    /// Sprites skipped because their texture isn't loaded
+    pub missing: u32,
+    /// Batches sent to the backend (runs of one texture and blend mode)
+    pub draw_calls: u32,
+    /// Times the texture changed between batches, counting the first one
+    pub texture_switches: u32,
+}
+
+impl RenderStats {
+    /// Sprites that were actually drawn
+    pub fn drawn(&self) -> u32 {
+        self.submitted - self.culled - self.missing
+    }
+
+    /// Text for the debug overlay
+    pub fn overlay_text(&self) -> String {
+        format!(
+            "SPRITES {}/{} CULLED {}\nDRAW CALLS {} TEXTURE SWITCHES {}",
+            self.drawn(),
+            self.submitted,
+            self.culled,
+            self.draw_calls,
+            self.texture_switches
+        )
+    }
+}
+
+impl std::ops::AddAssign for RenderStats {
+    fn add_assign(&mut self, other: Self) {
+        self.submitted += other.submitted;
+        self.culled += other.culled;
+        self.missing += other.missing;
+        self.draw_calls += other.draw_calls;
+        self.texture_switches += other.texture_switches;
+    }
+}
+
+/// Collects sprite draws for a frame and issues them in batches
+#[derive(Debug, Default)]
+pub struct RenderQueue {
+    commands: Vec<RenderCommand>,
+    stats: RenderStats,
+}
+
+impl RenderQueue {
+    /// Create an empty queue
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a quad that is already in screen space
+    pub fn push(&mut self, command: RenderCommand) {
+        self.stats.submitted += 1;
+        self.commands.push(command);
+    }
+
+    /// Add a sprite positioned in screen space
+    pub fn push_sprite(&mut self, sprite: &Sprite, layer: i32) {
+        if !sprite.visible {
+            return;
+        }
+        self.push(Self::sprite_command(sprite, sprite.get_dest_rect(), layer));
+    }
+
+    /// Add a sprite positioned in world space, returning false if the camera can't see it
+    pub fn push_world_sprite(&mut self, sprite: &Sprite, layer: i32, camera: &Camera) -> bool {
+        if !sprite.visible {
+            return false;
+        }
+
+        let world_rect = sprite.get_dest_rect();
+        if !camera.is_visible(&world_rect) {
+            self.stats.submitted += 1;
+            self.stats.culled += 1;
+            return false;
+        }
+
+        self.push(Self::sprite_command(sprite, camera.world_rect_to_screen(&world_rect), layer));
+        true
+    }
+
+    fn sprite_command(sprite: &Sprite, dst: Rect, layer: i32) -> RenderCommand {
+        RenderCommand {
+            layer,
+            texture_name: sprite.texture_name.clone(),
+            src: sprite.get_source_rect(),
+            dst,
+            params: sprite.draw_params(),
+        }
+    }
+
+    /// Get the number of queued quads
+    pub fn len(&self) -> usize {
+        self.commands.len()
+    }
+
+    /// Check if nothing is queued
+    pub fn is_empty(&self) -> bool {
+        self.commands.is_empty()
+    }
+
+    /// Sort the queued quads into drawing order: by layer, then texture,
+    /// keeping submission order otherwise
+    fn sort(&mut self) {
+        self.commands.sort_by(|a, b| {
+            a.layer.cmp(&b.layer)
+                .then_with(|| a.texture_name.cmp(&b.texture_name))
+                .then_with(|| blend_order(a.params.blend).cmp(&blend_order(b.params.blend)))
+        });
+    }
+
+    /// Draw everything queued and start a new frame, returning its stats
+    pub fn flush(&mut self, video: &mut VideoManager) -> Result<RenderStats> {
+        self.sort();
+
+        let mut stats = std::mem::take(&mut self.stats);
+        let mut last_texture: Option<&str> = None;
+        let mut quads = Vec::new();
+        for (index, command) in self.commands.iter().enumerate() {
+            let next = self.commands.get(index + 1);
+            quads.push((command.src, command.dst, command.params));
+            let batch_ends = next.is_none_or(|next| {
+                next.texture_name != command.texture_name || next.params.blend != command.params.blend
+            });
+            if !batch_ends {
+                continue;
+            }
+
+            // Sprites without a loaded texture are skipped rather than failing the frame
+            if !video.has_texture(&command.texture_name) {
+                log::debug!("Skipping {} sprites with missing texture {}", quads.len(), command.texture_name);
+                stats.missing += quads.len() as u32;
+                quads.clear();
+                continue;
+            }
+
+            if last_texture != Some(command.texture_name.as_str()) {
+                stats.texture_switches += 1;
+                last_texture = Some(command.texture_name.as_str());
+            }
+            stats.draw_calls += 1;
+            video.draw_texture_batch(&command.texture_name, &quads)?;
+            quads.clear();
+        }
+
+        self.commands.clear();
+        Ok(stats)
+    }
+}
+
+/// Fixed order of blend modes inside a texture's batches
+fn blend_order(blend: BlendMode) -> u8 {
+    match blend {
+        BlendMode::None => 0,
+        BlendMode::Blend => 1,
+        BlendMode::Add => 2,
+        BlendMode::Modulate => 3,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::math::Vector2;
+    use crate::video::Color;
+
+    fn sprite(texture: &str, x: f32) -> Sprite {
+        Sprite::with_size(texture.to_string(), Vector2::new(x, 0.0), Vector2::new(4.0, 4.0))
+    }
+
+    fn video() -> VideoManager {
+        let mut video = VideoManager::headless(64, 16).unwrap();
+        for (name, color) in [("a", [255, 0, 0, 255]), ("b", [0, 0, 255, 255])] {
+            video.load_texture_rgba(name, 1, 1, color.to_vec()).unwrap();
+        }
+        video
+    }
+
+    #[test]
+    fn sprites_are_drawn_by_layer_then_texture() {
+        let mut queue = RenderQueue::new();
+        queue.push_sprite(&sprite("b", 0.0), 1);
+        queue.push_sprite(&sprite("a", 8.0), 0);
+        queue.push_sprite(&sprite("b", 16.0), 0);
+        queue.push_sprite(&sprite("a", 24.0), 0);
+        queue.push_sprite(&sprite("a", 32.0), 1);
+
+        queue.sort();
+        let order: Vec<_> = queue.commands.iter().map(|c| (c.layer, c.texture_name.as_str(), c.dst.x)).collect();
+        assert_eq!(order, [(0, "a", 8.0), (0, "a", 24.0), (0, "b", 16.0), (1, "a", 32.0), (1, "b", 0.0)]);
+    }
+
+    #[test]
+    fn flush_batches_per_texture_and_reports_stats() {
+        let mut video = video();
+        let mut camera = Camera::new(Vector2::new(64.0, 16.0));
+        camera.snap_to(Vector2::new(32.0, 8.0));
+
+        let mut queue = RenderQueue::new();
+        for x in [0.0, 8.0, 16.0] {
+            assert!(queue.push_world_sprite(&sprite("a", x), 0, &camera));
+        }
+        assert!(queue.push_world_sprite(&sprite("b", 24.0), 0, &camera));
+        assert!(!queue.push_world_sprite(&sprite("b", 200.0), 0, &camera));
+
+        let stats = queue.flush(&mut video).unwrap();
+        assert_eq!(stats, RenderStats { submitted: 5, culled: 1, missing: 0, draw_calls: 2, texture_switches: 2 });
+        assert!(queue.is_empty());
+
+        let frame = video.screenshot().unwrap();
+        assert_eq!(frame.get_pixel(17, 1).0, [255, 0, 0, 255]);
+        assert_eq!(frame.get_pixel(25, 1).0, [0, 0, 255, 255]);
+
+        // Stats start over every frame
+        assert_eq!(queue.flush(&mut video).unwrap(), RenderStats::default());
+    }
+
+    #[test]
+    fn sprites_with_missing_textures_are_skipped() {
+        let mut video = video();
+        let mut queue = RenderQueue::new();
+        queue.push_sprite(&sprite("a", 0.0), 0);
+        queue.push_sprite(&sprite("missing", 8.0), 0);
+        queue.push_sprite(&sprite("missing", 16.0), 1);
+
+        let stats = queue.flush(&mut video).unwrap();
+        assert_eq!(stats, RenderStats { submitted: 3, culled: 0, missing: 2, draw_calls: 1, texture_switches: 1 });
+        assert_eq!(stats.drawn(), 1);
+        assert_eq!(video.screenshot().unwrap().get_pixel(1, 1).0, [255, 0, 0, 255]);
+    }
+
+    #[test]
+    fn sprites_keep_their_flips_rotation_and_tint() {
+        let mut video = video();
+        video.load_texture_rgba("ab", 2, 1, [[255, 255, 255, 255], [0, 0, 0, 255]].concat()).unwrap();
+        video.clear(Color::rgb(0, 0, 255));
+
+        // Mirrored through a negative scale, tinted red at half opacity
+        let mut mirrored = Sprite::with_size("ab".to_string(), Vector2::ZERO, Vector2::new(2.0, 1.0));
+        mirrored.scale = Vector2::new(-2.0, 2.0);
+        mirrored.tint = Color::rgb(255, 0, 0);
+        mirrored.alpha = 0.5;
+        assert_eq!(mirrored.get_dest_rect(), Rect::new(0.0, 0.0, 4.0, 2.0));
+
+        // Rotated a quarter turn around its top-left corner
+        let mut rotated = Sprite::with_size("ab".to_string(), Vector2::new(10.0, 4.0), Vector2::new(2.0, 1.0));
+        rotated.rotation = 90.0;
+        rotated.pivot = Some(Vector2::ZERO);
+
+        let mut queue = RenderQueue::new();
+        queue.push_sprite(&mirrored, 0);
+        queue.push_sprite(&rotated, 0);
+        queue.flush(&mut video).unwrap();
+
+        let frame = video.screenshot().unwrap();
+        assert_eq!(frame.get_pixel(0, 0).0, [0, 0, 127, 255]);
+        assert_eq!(frame.get_pixel(3, 1).0, [128, 0, 127, 255]);
+        assert_eq!(frame.get_pixel(9, 4).0, [255, 255, 255, 255]);
+        assert_eq!(frame.get_pixel(9, 5).0, [0, 0, 0, 255]);
+        assert_eq!(frame.get_pixel(10, 4).0, [0, 0, 255, 255]);
+    }
+}
@@ -0,0 +1,138 @@
+//! SDL window render backend for RustUX
+
+use crate::math::{Rect, Vector2};
+use crate::sprite::TextureManager;
+use crate::util::{Error, Result};
+use crate::video::backend::{pixel_rect, RenderBackend};
+use crate::video::{BlendMode, Color, DrawParams};
+use image::RgbaImage;
+use sdl2::pixels::PixelFormatEnum;
+use sdl2::rect::{Point as SdlPoint, Rect as SdlRect};
+use sdl2::render::Canvas;
+use sdl2::video::{FullscreenType, Window};
+
+/// Backend drawing into an SDL window
+pub struct SdlBackend {
+    textures: TextureManager,
+    canvas: Canvas<Window>,
+}
+
+impl SdlBackend {
+    pub fn new(canvas: Canvas<Window>) -> Self {
+        Self {
+            textures: TextureManager::new(canvas.texture_creator()),
+            canvas,
+        }
+    }
+}
+
+impl RenderBackend for SdlBackend {
+    fn output_size(&self) -> Vector2 {
+        let (width, height) = self.canvas
+            .output_size()
+            .unwrap_or((crate::config::SCREEN_WIDTH, crate::config::SCREEN_HEIGHT));
+        Vector2::new(width as f32, height as f32)
+    }
+
+    fn clear(&mut self, color: Color) {
+        self.canvas.set_draw_color(color.to_sdl());
+        self.canvas.clear();
+    }
+
+    fn present(&mut self) {
+        self.canvas.present();
+    }
+
+    fn load_texture_rgba(&mut self, name: &str, width: u32, height: u32, pixels: Vec<u8>) -> Result<()> {
+        self.textures.load_texture_from_rgba(name, width, height, pixels)
+    }
+
+    fn remove_texture(&mut self, name: &str) -> bool {
+        self.textures.remove_texture(name)
+    }
+
+    fn texture_size(&self, name: &str) -> Option<Vector2> {
+        self.textures
+            .get_texture_dimensions(name)
+            .map(|(width, height)| Vector2::new(width as f32, height as f32))
+    }
+
+    fn draw_texture(&mut self, name: &str, src: Option<Rect>, dst: Rect, params: &DrawParams) -> Result<()> {
+        self.draw_texture_batch(name, &[(src, dst, *params)])
+    }
+
+    fn draw_texture_batch(&mut self, name: &str, quads: &[(Option<Rect>, Rect, DrawParams)]) -> Result<()> {
+        let texture = self.textures.get_texture_mut(name)
+            .ok_or_else(|| Error::SpriteLoading(format!("Texture not found: {}", name)))?;
+
+        // SDL queues consecutive copies of one texture itself, as long as
+        // its state doesn't change in between
+        let mut state = None;
+        for (src, dst, params) in quads {
+            let quad_state = (params.color, params.blend);
+            if state != Some(quad_state) {
+                texture.set_color_mod(params.color.r, params.color.g, params.color.b);
+                texture.set_alpha_mod(params.color.a);
+                texture.set_blend_mode(params.blend.to_sdl());
+                state = Some(quad_state);
+            }
+
+            let center = params.pivot.map(|pivot| SdlPoint::new(pivot.x.round() as i32, pivot.y.round() as i32));
+            self.canvas.copy_ex(
+                texture,
+                src.map(to_sdl_rect),
+                Some(to_sdl_rect(*dst)),
+                params.rotation,
+                center,
+                params.flip_horizontal,
+                params.flip_vertical,
+            ).map_err(Error::Video)?;
+        }
+        Ok(())
+    }
+
+    fn set_blend_mode(&mut self, blend: BlendMode) {
+        self.canvas.set_blend_mode(blend.to_sdl());
+    }
+
+    fn fill_rect(&mut self, rect: Rect, color: Color) -> Result<()> {
+        self.canvas.set_draw_color(color.to_sdl());
+        self.canvas.fill_rect(to_sdl_rect(rect)).map_err(Error::Video)
+    }
+
+    fn draw_rect(&mut self, rect: Rect, color: Color) -> Result<()> {
+        self.canvas.set_draw_color(color.to_sdl());
+        self.canvas.draw_rect(to_sdl_rect(rect)).map_err(Error::Video)
+    }
+
+    fn draw_line(&mut self, from: Vector2, to: Vector2, color: Color) -> Result<()> {
+        self.canvas.set_draw_color(color.to_sdl());
+        self.canvas
+            .draw_line(
+                SdlPoint::new(from.x.round() as i32, from.y.round() as i32),
+                SdlPoint::new(to.x.round() as i32, to.y.round() as i32),
+            )
+            .map_err(Error::Video)
+    }
+
+    fn set_clip_rect(&mut self, clip: Option<Rect>) {
+        self.canvas.set_clip_rect(clip.map(to_sdl_rect));
+    }
+
+    fn read_pixels(&self) -> Result<RgbaImage> {
+        let (width, height) = self.canvas.output_size().map_err(Error::Video)?;
+        let pixels = self.canvas.read_pixels(None, PixelFormatEnum::RGBA32).map_err(Error::Video)?;
+        RgbaImage::from_raw(width, height, pixels)
+            .ok_or_else(|| Error::Video("Frame has an unexpected size".to_string()))
+    }
+
+    fn set_fullscreen(&mut self, fullscreen: bool) -> Result<()> {
+        let mode = if fullscreen { FullscreenType::Desktop } else { FullscreenType::Off };
+        self.canvas.window_mut().set_fullscreen(mode).map_err(Error::Sdl2)
+    }
+}
+
+fn to_sdl_rect(rect: Rect) -> SdlRect {
+    let (x, y, width, height) = pixel_rect(rect);
+    SdlRect::new(x, y, width, height)
+}
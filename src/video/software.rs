@@ -0,0 +1,338 @@
+//! Headless software render backend for RustUX
+//!
+//! Draws into an in-memory RGBA image, following SDL's rules for pixel
+//! coverage, blending and color modulation, so frames can be checked in
+//! tests and on CI machines without a display.
+
+use crate::math::{Rect, Vector2};
+use crate::util::{Error, Result};
+use crate::video::backend::{pixel_rect, RenderBackend};
+use crate::video::{BlendMode, Color, DrawParams};
+use image::{Rgba, RgbaImage};
+use std::collections::HashMap;
+
+/// Backend drawing into an in-memory image
+pub struct SoftwareBackend {
+    frame: RgbaImage,
+    textures: HashMap<String, RgbaImage>,
+    /// Blend mode for shapes and lines
+    blend: BlendMode,
+    clip: Option<Rect>,
+}
+
+impl SoftwareBackend {
+    /// Create a backend with a black, transparent frame of the given size
+    pub fn new(width: u32, height: u32) -> Self {
+        Self {
+            frame: RgbaImage::new(width, height),
+            textures: HashMap::new(),
+            blend: BlendMode::None,
+            clip: None,
+        }
+    }
+
+    /// Get the frame drawn so far
+    pub fn frame(&self) -> &RgbaImage {
+        &self.frame
+    }
+
+    /// Pixel area drawing may touch as (left, top, right, bottom), exclusive
+    fn drawable_area(&self) -> (i32, i32, i32, i32) {
+        let (width, height) = (self.frame.width() as i32, self.frame.height() as i32);
+        match self.clip {
+            Some(clip) => {
+                let (x, y, w, h) = pixel_rect(clip);
+                (x.max(0), y.max(0), (x + w as i32).min(width), (y + h as i32).min(height))
+            }
+            None => (0, 0, width, height),
+        }
+    }
+
+    /// Blend one pixel into the frame, if it's inside the drawable area
+    fn plot(&mut self, x: i32, y: i32, color: [u8; 4], blend: BlendMode) {
+        let (left, top, right, bottom) = self.drawable_area();
+        if x < left || y < top || x >= right || y >= bottom {
+            return;
+        }
+        blend_pixel(self.frame.get_pixel_mut(x as u32, y as u32), color, blend);
+    }
+}
+
+impl RenderBackend for SoftwareBackend {
+    fn output_size(&self) -> Vector2 {
+        Vector2::new(self.frame.width() as f32, self.frame.height() as f32)
+    }
+
+    fn clear(&mut self, color: Color) {
+        let pixel = Rgba([color.r, color.g, color.b, color.a]);
+        for target in self.frame.pixels_mut() {
+            *target = pixel;
+        }
+    }
+
+    fn present(&mut self) {}
+
+    fn load_texture_rgba(&mut self, name: &str, width: u32, height: u32, pixels: Vec<u8>) -> Result<()> {
+        let texture = RgbaImage::from_raw(width, height, pixels)
+            .ok_or_else(|| Error::SpriteLoading(format!("Pixel data for texture '{}' has the wrong size", name)))?;
+        self.textures.insert(name.to_string(), texture);
+        Ok(())
+    }
+
+    fn remove_texture(&mut self, name: &str) -> bool {
+        self.textures.remove(name).is_some()
+    }
+
+    fn texture_size(&self, name: &str) -> Option<Vector2> {
+        self.textures
+            .get(name)
+            .map(|texture| Vector2::new(texture.width() as f32, texture.height() as f32))
+    }
+
+    fn draw_texture(&mut self, name: &str, src: Option<Rect>, dst: Rect, params: &DrawParams) -> Result<()> {
+        let (left, top, right, bottom) = self.drawable_area();
+        let Self { frame, textures, .. } = self;
+        let texture = textures.get(name)
+            .ok_or_else(|| Error::SpriteLoading(format!("Texture not found: {}", name)))?;
+
+        let src = src.unwrap_or_else(|| Rect::new(0.0, 0.0, texture.width() as f32, texture.height() as f32));
+        let (x, y, width, height) = pixel_rect(dst);
+        if width == 0 || height == 0 || src.width <= 0.0 || src.height <= 0.0 {
+            return Ok(());
+        }
+
+        let origin = Vector2::new(x as f32, y as f32);
+        let size = Vector2::new(width as f32, height as f32);
+        let pivot = params.pivot.map(|pivot| pivot.round()).unwrap_or(size * 0.5);
+        let center = origin + pivot;
+        let (sin, cos) = (params.rotation.to_radians() as f32).sin_cos();
+        let rotate = |v: Vector2| Vector2::new(v.x * cos - v.y * sin, v.x * sin + v.y * cos);
+        let unrotate = |v: Vector2| Vector2::new(v.x * cos + v.y * sin, -v.x * sin + v.y * cos);
+
+        // Pixels the rotated destination can touch
+        let corners = [Vector2::ZERO, Vector2::new(size.x, 0.0), Vector2::new(0.0, size.y), size]
+            .map(|corner| center + rotate(corner - pivot));
+        let min = corners.iter().fold(Vector2::splat(f32::MAX), |min, corner| min.min(*corner));
+        let max = corners.iter().fold(Vector2::splat(f32::MIN), |max, corner| max.max(*corner));
+        let (x0, y0) = ((min.x.floor() as i32).max(left), (min.y.floor() as i32).max(top));
+        let (x1, y1) = ((max.x.ceil() as i32).min(right), (max.y.ceil() as i32).min(bottom));
+
+        let tint = params.color;
+        for py in y0..y1 {
+            for px in x0..x1 {
+                // Position of the pixel center within the unrotated destination
+                let local = unrotate(Vector2::new(px as f32 + 0.5, py as f32 + 0.5) - center) + pivot;
+                if local.x < 0.0 || local.y < 0.0 || local.x >= size.x || local.y >= size.y {
+                    continue;
+                }
+
+                let mut u = local.x / size.x;
+                let mut v = local.y / size.y;
+                if params.flip_horizontal {
+                    u = 1.0 - u;
+                }
+                if params.flip_vertical {
+                    v = 1.0 - v;
+                }
+
+                let sx = (src.x + u * src.width).floor().clamp(src.left(), src.right() - 1.0);
+                let sy = (src.y + v * src.height).floor().clamp(src.top(), src.bottom() - 1.0);
+                if sx < 0.0 || sy < 0.0 || sx >= texture.width() as f32 || sy >= texture.height() as f32 {
+                    continue;
+                }
+
+                let Rgba([r, g, b, a]) = *texture.get_pixel(sx as u32, sy as u32);
+                let texel = [modulate(r, tint.r), modulate(g, tint.g), modulate(b, tint.b), modulate(a, tint.a)];
+                blend_pixel(frame.get_pixel_mut(px as u32, py as u32), texel, params.blend);
+            }
+        }
+
+        Ok(())
+    }
+
+    fn set_blend_mode(&mut self, blend: BlendMode) {
+        self.blend = blend;
+    }
+
+    fn fill_rect(&mut self, rect: Rect, color: Color) -> Result<()> {
+        let (x, y, width, height) = pixel_rect(rect);
+        let pixel = [color.r, color.g, color.b, color.a];
+        for py in y..y + height as i32 {
+            for px in x..x + width as i32 {
+                self.plot(px, py, pixel, self.blend);
+            }
+        }
+        Ok(())
+    }
+
+    fn draw_rect(&mut self, rect: Rect, color: Color) -> Result<()> {
+        let (x, y, width, height) = pixel_rect(rect);
+        if width == 0 || height == 0 {
+            return Ok(());
+        }
+        let pixel = [color.r, color.g, color.b, color.a];
+        let (right, bottom) = (x + width as i32 - 1, y + height as i32 - 1);
+        for px in x..=right {
+            self.plot(px, y, pixel, self.blend);
+            if bottom != y {
+                self.plot(px, bottom, pixel, self.blend);
+            }
+        }
+        for py in y + 1..bottom {
+            self.plot(x, py, pixel, self.blend);
+            if right != x {
+                self.plot(right, py, pixel, self.blend);
+            }
+        }
+        Ok(())
+    }
+
+    fn draw_line(&mut self, from: Vector2, to: Vector2, color: Color) -> Result<()> {
+        let pixel = [color.r, color.g, color.b, color.a];
+        let (mut x, mut y) = (from.x.round() as i32, from.y.round() as i32);
+        let (x1, y1) = (to.x.round() as i32, to.y.round() as i32);
+        let (dx, dy) = ((x1 - x).abs(), -(y1 - y).abs());
+        let (step_x, step_y) = ((x1 - x).signum(), (y1 - y).signum());
+        let mut error = dx + dy;
+
+        // Bresenham, including both end points
+        loop {
+            self.plot(x, y, pixel, self.blend);
+            if x == x1 && y == y1 {
+                break;
+            }
+            let doubled = 2 * error;
+            if doubled >= dy {
+                error += dy;
+                x += step_x;
+            }
+            if doubled <= dx {
+                error += dx;
+                y += step_y;
+            }
+        }
+        Ok(())
+    }
+
+    fn set_clip_rect(&mut self, clip: Option<Rect>) {
+        self.clip = clip;
+    }
+
+    fn read_pixels(&self) -> Result<RgbaImage> {
+        Ok(self.frame.clone())
+    }
+}
+
+/// Multiply two color channels
+fn modulate(a: u8, b: u8) -> u8 {
+    ((a as u32 * b as u32 + 127) / 255) as u8
+}
+
+/// Combine a source color with a frame pixel
+fn blend_pixel(target: &mut Rgba<u8>, [r, g, b, a]: [u8; 4], blend: BlendMode) {
+    let Rgba([dr, dg, db, da]) = *target;
+    let over = |s: u8, d: u8| ((s as u32 * a as u32 + d as u32 * (255 - a as u32) + 127) / 255) as u8;
+    let add = |s: u8, d: u8| (d as u32 + modulate(s, a) as u32).min(255) as u8;
+
+    *target = match blend {
+        BlendMode::None => Rgba([r, g, b, a]),
+        BlendMode::Blend => Rgba([over(r, dr), over(g, dg), over(b, db), a + modulate(da, 255 - a)]),
+        BlendMode::Add => Rgba([add(r, dr), add(g, dg), add(b, db), da]),
+        BlendMode::Modulate => Rgba([modulate(r, dr), modulate(g, dg), modulate(b, db), da]),
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const RED: Color = Color::rgb(255, 0, 0);
+
+    fn backend() -> SoftwareBackend {
+        let mut backend = SoftwareBackend::new(8, 8);
+        backend.clear(Color::BLACK);
+        // 2x1 texture: red on the left, blue on the right
+        backend.load_texture_rgba("pair", 2, 1, vec![255, 0, 0, 255, 0, 0, 255, 255]).unwrap();
+        backend
+    }
+
+    fn pixel(backend: &SoftwareBackend, x: u32, y: u32) -> [u8; 4] {
+        backend.frame().get_pixel(x, y).0
+    }
+
+    #[test]
+    fn fill_rect_covers_whole_pixels_inside_the_clip() {
+        let mut backend = backend();
+        backend.set_clip_rect(Some(Rect::new(0.0, 0.0, 3.0, 8.0)));
+        backend.fill_rect(Rect::new(1.0, 1.0, 4.0, 2.0), RED).unwrap();
+        assert_eq!(pixel(&backend, 1, 1), [255, 0, 0, 255]);
+        assert_eq!(pixel(&backend, 2, 2), [255, 0, 0, 255]);
+        assert_eq!(pixel(&backend, 3, 1), [0, 0, 0, 255]);
+        assert_eq!(pixel(&backend, 1, 3), [0, 0, 0, 255]);
+    }
+
+    #[test]
+    fn textures_are_scaled_and_flipped() {
+        let mut backend = backend();
+        backend.draw_texture("pair", None, Rect::new(0.0, 0.0, 4.0, 2.0), &DrawParams::default()).unwrap();
+        assert_eq!(pixel(&backend, 1, 1), [255, 0, 0, 255]);
+        assert_eq!(pixel(&backend, 2, 1), [0, 0, 255, 255]);
+
+        let flipped = DrawParams {
+            flip_horizontal: true,
+            ..DrawParams::default()
+        };
+        backend.draw_texture("pair", None, Rect::new(0.0, 4.0, 4.0, 2.0), &flipped).unwrap();
+        assert_eq!(pixel(&backend, 0, 4), [0, 0, 255, 255]);
+        assert_eq!(pixel(&backend, 3, 5), [255, 0, 0, 255]);
+    }
+
+    #[test]
+    fn textures_rotate_about_their_center() {
+        let mut backend = backend();
+        let rotated = DrawParams {
+            rotation: 90.0,
+            ..DrawParams::default()
+        };
+        // Clockwise by 90°: the left (red) half ends up on top
+        backend.draw_texture("pair", None, Rect::new(2.0, 2.0, 4.0, 4.0), &rotated).unwrap();
+        assert_eq!(pixel(&backend, 3, 2), [255, 0, 0, 255]);
+        assert_eq!(pixel(&backend, 3, 5), [0, 0, 255, 255]);
+    }
+
+    #[test]
+    fn textures_are_tinted_and_blended() {
+        let mut backend = backend();
+        backend.fill_rect(Rect::new(0.0, 0.0, 8.0, 8.0), Color::rgb(0, 0, 200)).unwrap();
+
+        let translucent = DrawParams {
+            color: Color::rgba(255, 255, 255, 128),
+            ..DrawParams::default()
+        };
+        backend.draw_texture("pair", Some(Rect::new(0.0, 0.0, 1.0, 1.0)), Rect::new(0.0, 0.0, 1.0, 1.0), &translucent).unwrap();
+        assert_eq!(pixel(&backend, 0, 0), [128, 0, 100, 255]);
+
+        let additive = DrawParams {
+            color: Color::rgb(0, 255, 255),
+            blend: BlendMode::Add,
+            ..DrawParams::default()
+        };
+        backend.draw_texture("pair", Some(Rect::new(1.0, 0.0, 1.0, 1.0)), Rect::new(1.0, 0.0, 1.0, 1.0), &additive).unwrap();
+        assert_eq!(pixel(&backend, 1, 0), [0, 0, 255, 255]);
+    }
+
+    #[test]
+    fn shapes_use_the_shape_blend_mode() {
+        let mut backend = backend();
+        backend.fill_rect(Rect::new(0.0, 0.0, 2.0, 2.0), Color::rgba(255, 0, 0, 128)).unwrap();
+        assert_eq!(pixel(&backend, 0, 0), [255, 0, 0, 128]);
+
+        backend.clear(Color::BLACK);
+        backend.set_blend_mode(BlendMode::Blend);
+        backend.fill_rect(Rect::new(0.0, 0.0, 2.0, 2.0), Color::rgba(255, 0, 0, 128)).unwrap();
+        assert_eq!(pixel(&backend, 0, 0), [128, 0, 0, 255]);
+
+        backend.draw_line(Vector2::new(0.0, 7.0), Vector2::new(7.0, 0.0), Color::WHITE).unwrap();
+        assert!((0..8).all(|x| pixel(&backend, x, 7 - x) == [255, 255, 255, 255]));
+    }
+}
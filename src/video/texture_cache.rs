@@ -0,0 +1,119 @@
+//! Texture reference counting for RustUX
+//!
+//! Tracks which named textures are in use and by how many owners, so a
+//! texture is loaded from disk once and stays loaded across frames until its
+//! last owner (usually a level) releases it.
+
+use crate::util::{Error, Result};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+#[derive(Debug, Clone)]
+struct CachedTexture {
+    path: PathBuf,
+    refs: u32,
+}
+
+/// Reference counts of loaded textures, keyed by texture name
+#[derive(Debug, Clone, Default)]
+pub struct TextureCache {
+    entries: HashMap<String, CachedTexture>,
+}
+
+impl TextureCache {
+    /// Create an empty cache
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a reference to a texture, returning true if it still has to be loaded
+    pub fn acquire(&mut self, name: &str, path: &Path) -> Result<bool> {
+        match self.entries.get_mut(name) {
+            Some(entry) if entry.path != path => Err(Error::SpriteLoading(format!(
+                "Texture '{}' is already loaded from {}, not {}",
+                name,
+                entry.path.display(),
+                path.display()
+            ))),
+            Some(entry) => {
+                entry.refs += 1;
+                Ok(false)
+            }
+            None => {
+                self.entries.insert(name.to_string(), CachedTexture {
+                    path: path.to_path_buf(),
+                    refs: 1,
+                });
+                Ok(true)
+            }
+        }
+    }
+
+    /// Drop a reference to a texture, returning true if it should be unloaded
+    pub fn release(&mut self, name: &str) -> bool {
+        let Some(entry) = self.entries.get_mut(name) else {
+            log::warn!("Released texture '{}' that isn't loaded", name);
+            return false;
+        };
+
+        entry.refs -= 1;
+        if entry.refs == 0 {
+            self.entries.remove(name);
+            return true;
+        }
+        false
+    }
+
+    /// Forget a texture regardless of its references
+    pub fn forget(&mut self, name: &str) {
+        self.entries.remove(name);
+    }
+
+    /// Get the number of references to a texture (0 if it isn't cached)
+    pub fn ref_count(&self, name: &str) -> u32 {
+        self.entries.get(name).map_or(0, |entry| entry.refs)
+    }
+
+    /// Get the file a cached texture was loaded from
+    pub fn path(&self, name: &str) -> Option<&Path> {
+        self.entries.get(name).map(|entry| entry.path.as_path())
+    }
+
+    /// Get the number of cached textures
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Check if no textures are cached
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn textures_load_once_and_unload_with_their_last_reference() {
+        let mut cache = TextureCache::new();
+        let path = Path::new("assets/tux.png");
+        assert!(cache.acquire("tux", path).unwrap());
+        assert!(!cache.acquire("tux", path).unwrap());
+        assert_eq!(cache.ref_count("tux"), 2);
+
+        assert!(!cache.release("tux"));
+        assert!(cache.release("tux"));
+        assert_eq!(cache.ref_count("tux"), 0);
+        assert!(cache.is_empty());
+        assert!(!cache.release("tux"));
+    }
+
+    #[test]
+    fn names_cannot_be_reused_for_other_files() {
+        let mut cache = TextureCache::new();
+        cache.acquire("tux", Path::new("assets/tux.png")).unwrap();
+        assert!(cache.acquire("tux", Path::new("assets/other.png")).is_err());
+        assert_eq!(cache.ref_count("tux"), 1);
+    }
+}
@@ -0,0 +1,161 @@
+//! Logical resolution for RustUX
+//!
+//! The game draws at a fixed logical size. The viewport maps that size onto
+//! the real output, scaled by the chosen mode and centered, with black bars
+//! filling whatever is left over.
+
+use crate::config::{SCREEN_HEIGHT, SCREEN_WIDTH};
+use crate::math::{Rect, Vector2};
+use serde::{Deserialize, Serialize};
+
+/// How the logical resolution is scaled to the output
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ScalingMode {
+    /// Largest whole-number scale that fits, for crisp pixels
+    Integer,
+    /// Largest scale that fits, keeping the aspect ratio
+    #[default]
+    Fit,
+    /// Fill the output, distorting the aspect ratio if needed
+    Stretch,
+}
+
+/// Display settings
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct VideoConfig {
+    /// Width the game draws at, in logical pixels
+    pub logical_width: u32,
+    /// Height the game draws at, in logical pixels
+    pub logical_height: u32,
+    pub scaling: ScalingMode,
+    pub fullscreen: bool,
+}
+
+impl Default for VideoConfig {
+    fn default() -> Self {
+        Self {
+            logical_width: SCREEN_WIDTH,
+            logical_height: SCREEN_HEIGHT,
+            scaling: ScalingMode::Fit,
+            fullscreen: false,
+        }
+    }
+}
+
+impl VideoConfig {
+    /// Get the logical resolution as a size
+    pub fn logical_size(&self) -> Vector2 {
+        Vector2::new(self.logical_width as f32, self.logical_height as f32)
+    }
+}
+
+/// Placement of the logical drawing area on the output
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Viewport {
+    /// Size the game draws at
+    pub logical_size: Vector2,
+    /// Output pixels per logical pixel on each axis
+    pub scale: Vector2,
+    /// Top-left corner of the drawing area on the output
+    pub offset: Vector2,
+}
+
+impl Viewport {
+    /// Fit `logical_size` onto an output of `output_size` pixels
+    pub fn new(logical_size: Vector2, output_size: Vector2, mode: ScalingMode) -> Self {
+        if logical_size.x <= 0.0 || logical_size.y <= 0.0 {
+            return Self::identity(output_size);
+        }
+
+        let ratio = output_size / logical_size;
+        let fit = ratio.x.min(ratio.y);
+        let scale = match mode {
+            // Fall back to shrinking when the output is smaller than the logical size
+            ScalingMode::Integer if fit >= 1.0 => Vector2::splat(fit.floor()),
+            ScalingMode::Integer | ScalingMode::Fit => Vector2::splat(fit),
+            ScalingMode::Stretch => ratio,
+        };
+        let offset = ((output_size - logical_size * scale) * 0.5).floor();
+
+        Self {
+            logical_size,
+            scale,
+            offset,
+        }
+    }
+
+    /// A viewport drawing straight to an output of `size` pixels
+    pub fn identity(size: Vector2) -> Self {
+        Self {
+            logical_size: size,
+            scale: Vector2::ONE,
+            offset: Vector2::ZERO,
+        }
+    }
+
+    /// Check if logical and output coordinates are the same
+    pub fn is_identity(&self) -> bool {
+        self.scale == Vector2::ONE && self.offset == Vector2::ZERO
+    }
+
+    /// Area the game draws into, in output pixels
+    pub fn rect(&self) -> Rect {
+        Rect::from_pos_size(self.offset, self.logical_size * self.scale)
+    }
+
+    /// Convert a logical position to output pixels
+    pub fn to_output(&self, position: Vector2) -> Vector2 {
+        self.offset + position * self.scale
+    }
+
+    /// Convert a logical rectangle to output pixels
+    pub fn rect_to_output(&self, rect: Rect) -> Rect {
+        Rect::from_pos_size(self.to_output(rect.top_left()), Vector2::new(rect.width, rect.height) * self.scale)
+    }
+
+    /// Convert an output position (such as the mouse) to logical coordinates
+    ///
+    /// Positions on the black bars end up outside `0..logical_size`.
+    pub fn to_logical(&self, position: Vector2) -> Vector2 {
+        (position - self.offset) / self.scale
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const LOGICAL: Vector2 = Vector2::new(320.0, 240.0);
+
+    #[test]
+    fn scaling_modes_place_the_logical_area() {
+        let output = Vector2::new(1000.0, 600.0);
+
+        let integer = Viewport::new(LOGICAL, output, ScalingMode::Integer);
+        assert_eq!(integer.scale, Vector2::splat(2.0));
+        assert_eq!(integer.rect(), Rect::new(180.0, 60.0, 640.0, 480.0));
+
+        let fit = Viewport::new(LOGICAL, output, ScalingMode::Fit);
+        assert_eq!(fit.scale, Vector2::splat(2.5));
+        assert_eq!(fit.rect(), Rect::new(100.0, 0.0, 800.0, 600.0));
+
+        let stretch = Viewport::new(LOGICAL, output, ScalingMode::Stretch);
+        assert_eq!(stretch.rect(), Rect::new(0.0, 0.0, 1000.0, 600.0));
+
+        // Too small for a whole-number scale
+        let shrunk = Viewport::new(LOGICAL, Vector2::new(160.0, 240.0), ScalingMode::Integer);
+        assert_eq!(shrunk.scale, Vector2::splat(0.5));
+        assert!(Viewport::new(LOGICAL, LOGICAL, ScalingMode::Integer).is_identity());
+    }
+
+    #[test]
+    fn output_positions_map_back_to_logical_space() {
+        let viewport = Viewport::new(LOGICAL, Vector2::new(1000.0, 600.0), ScalingMode::Integer);
+        assert_eq!(viewport.to_output(Vector2::new(10.0, 20.0)), Vector2::new(200.0, 100.0));
+        assert_eq!(viewport.to_logical(Vector2::new(200.0, 100.0)), Vector2::new(10.0, 20.0));
+        // Left bar
+        assert!(viewport.to_logical(Vector2::new(50.0, 100.0)).x < 0.0);
+    }
+}